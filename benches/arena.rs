@@ -0,0 +1,41 @@
+//! Compares parsing into `JsonValue` with parsing into one reused `Arena`, over the same
+//! generated documents. Run with `cargo bench --features arena`.
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use json_parser_rs::{parse_in_arena, to_string, Arena, GenerateOptions, Generator, Lexer, ParseOptions, SyntaxAnalyser};
+
+const ROUNDS: usize = 20;
+
+fn time(mut parse: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        parse();
+    }
+    start.elapsed() / ROUNDS as u32
+}
+
+fn main() {
+    for size in [100, 10_000, 1_000_000] {
+        let options = GenerateOptions { target_size: Some(size), ..GenerateOptions::default() };
+        let mut generator = Generator::new(size as u64, options);
+        let documents: Vec<String> = (0..1_000_000 / size).map(|_| to_string(&generator.document())).collect();
+
+        let values = time(|| {
+            for document in &documents {
+                let value = SyntaxAnalyser::new(Lexer::new(Box::new(Cursor::new(document.clone())))).parse();
+                assert!(value.is_ok());
+            }
+        });
+        let mut arena = Arena::new();
+        let parse_options = ParseOptions::default();
+        let arenas = time(|| {
+            for document in &documents {
+                let value = parse_in_arena(&mut arena, document, &parse_options);
+                assert!(value.is_ok());
+            }
+        });
+        println!("{} documents of {} bytes: JsonValue {:?}, Arena {:?}", documents.len(), size, values, arenas);
+    }
+}
@@ -0,0 +1,133 @@
+//! The documents the benchmarks run over, built from a fixed seed rather than committed, so that
+//! every machine measures the same bytes. `SAVE_CORPORA=DIR cargo bench --bench parse` writes
+//! them out, to look at or to feed to other tools.
+
+use json_parser_rs::{to_string, to_string_with_options, JsonValue, PrettyConfig, SerializeOptions};
+
+const SEED: u64 = 0x5EED_2024;
+
+pub struct Corpus {
+    pub name: &'static str,
+    pub text: String,
+}
+
+/// Every corpus, in the order they are reported.
+pub fn all() -> Vec<Corpus> {
+    let mut random = Random(SEED);
+    vec![
+        Corpus { name: "api-dump", text: to_string(&api_dump(&mut random, 20_000)) },
+        Corpus { name: "config", text: pretty(&config(&mut random, 5)) },
+        Corpus { name: "numbers", text: to_string(&numbers(&mut random, 200_000)) },
+        Corpus { name: "strings", text: to_string(&strings(&mut random, 40_000)) },
+        Corpus { name: "deep", text: to_string(&deep(2_000, 100)) },
+    ]
+}
+
+/// xorshift64*, small and the same everywhere.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[self.below(choices.len() as u64) as usize]
+    }
+}
+
+fn object(members: Vec<(&str, JsonValue)>) -> JsonValue {
+    JsonValue::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+fn string(text: impl Into<String>) -> JsonValue {
+    JsonValue::String(text.into())
+}
+
+fn pretty(value: &JsonValue) -> String {
+    to_string_with_options(value, &SerializeOptions { pretty: Some(PrettyConfig::default()), ..SerializeOptions::default() })
+}
+
+/// A large minified array of records shaped like a paginated API's users, several megabytes long.
+fn api_dump(random: &mut Random, records: usize) -> JsonValue {
+    let names = ["ada", "grace", "alan", "edsger", "barbara", "donald", "frances", "ken"];
+    let cities = ["London", "Zürich", "São Paulo", "Tōkyō", "Nairobi", "Montréal"];
+    let tags = ["admin", "beta", "billing", "support", "staff", "trial"];
+    let users = (0..records).map(|id| {
+        let name = format!("{}{}", random.pick(&names), random.below(1000));
+        object(vec![
+            ("id", JsonValue::Number(id as f64)),
+            ("name", string(name.clone())),
+            ("email", string(format!("{}@example.com", name))),
+            ("active", JsonValue::Bool(random.below(4) != 0)),
+            ("score", JsonValue::Number(random.below(100_000) as f64 / 100.0)),
+            ("manager", if random.below(3) == 0 { JsonValue::Null } else { JsonValue::Number(random.below(id as u64 + 1) as f64) }),
+            ("tags", JsonValue::Array((0..random.below(4)).map(|_| string(random.pick(&tags))).collect())),
+            ("address", object(vec![
+                ("city", string(random.pick(&cities))),
+                ("postcode", string(format!("{:05}", random.below(100_000)))),
+                ("location", JsonValue::Array(vec![JsonValue::Number(random.below(180_000) as f64 / 1000.0 - 90.0), JsonValue::Number(random.below(360_000) as f64 / 1000.0 - 180.0)])),
+            ])),
+        ])
+    });
+    object(vec![
+        ("page", JsonValue::Number(1.0)),
+        ("per_page", JsonValue::Number(records as f64)),
+        ("users", JsonValue::Array(users.collect())),
+    ])
+}
+
+/// A hand-written-looking configuration: objects nested `depth` levels, pretty-printed.
+fn config(random: &mut Random, depth: usize) -> JsonValue {
+    let keys = ["server", "database", "cache", "logging", "features", "limits", "retry"];
+    let mut members = vec![
+        ("enabled", JsonValue::Bool(random.below(2) == 0)),
+        ("timeout_ms", JsonValue::Number(random.below(30_000) as f64)),
+        ("name", string(format!("{}-{}", random.pick(&keys), random.below(100)))),
+        ("hosts", JsonValue::Array((0..3).map(|index| string(format!("10.0.{}.{}", index, random.below(255)))).collect())),
+    ];
+    if depth > 0 {
+        for key in keys.iter().take(4) {
+            members.push((key, config(random, depth - 1)));
+        }
+    }
+    object(members)
+}
+
+/// An array of integers, decimals and exponents of every size.
+fn numbers(random: &mut Random, count: usize) -> JsonValue {
+    JsonValue::Array((0..count).map(|_| {
+        let number = match random.below(4) {
+            0 => random.below(1000) as f64,
+            1 => random.next() as f64 / 7.0,
+            2 => -(random.below(1_000_000) as f64) / 1000.0,
+            _ => (random.below(1000) as f64 + 0.5) * 10f64.powi(random.below(40) as i32 - 20),
+        };
+        JsonValue::Number(number)
+    }).collect())
+}
+
+/// An array of sentences full of characters that must be escaped, or are outside ASCII.
+fn strings(random: &mut Random, count: usize) -> JsonValue {
+    let words = ["plain", "say \"hi\"", "C:\\path", "line\nbreak", "tab\there", "café", "naïve", "🙂", "日本語", "\u{1}", "</script>"];
+    JsonValue::Array((0..count).map(|_| {
+        let sentence: Vec<&str> = (0..2 + random.below(10)).map(|_| random.pick(&words)).collect();
+        string(sentence.join(" "))
+    }).collect())
+}
+
+/// An array of `count` values each nested `depth` levels deep, alternating objects and arrays.
+fn deep(count: usize, depth: usize) -> JsonValue {
+    let mut value = JsonValue::Number(0.0);
+    for level in 0..depth {
+        value = if level % 2 == 0 { JsonValue::Array(vec![value]) } else { object(vec![("a", value)]) };
+    }
+    JsonValue::Array(vec![value; count])
+}
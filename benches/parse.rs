@@ -0,0 +1,55 @@
+//! Times checking a document without building it, parsing it into a `JsonValue`, and writing
+//! it back out, over each corpus in `corpora`. Run with `cargo bench --bench parse`, optionally
+//! followed by `-- NAME` to run only the benchmarks whose names contain NAME, such as `numbers`
+//! or `serialize`. Set `SAVE_CORPORA` to a directory to write the corpora there instead.
+
+mod corpora;
+
+use std::hint::black_box;
+use std::io::Cursor;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use json_parser_rs::{to_string, EventReader, JsonValue, Lexer, SyntaxAnalyser};
+
+fn lexer(text: &str) -> Lexer {
+    Lexer::new(Box::new(Cursor::new(text.to_string())))
+}
+
+fn validate(text: &str) {
+    let mut reader = EventReader::new(lexer(text));
+    let result = reader.skip_value().and_then(|()| reader.next_event());
+    assert!(result.is_ok());
+}
+
+fn parse(text: &str) -> JsonValue {
+    SyntaxAnalyser::new(lexer(text)).parse().unwrap()
+}
+
+fn save(directory: &str) {
+    std::fs::create_dir_all(directory).unwrap();
+    for corpus in corpora::all() {
+        let path = format!("{}/{}.json", directory, corpus.name);
+        std::fs::write(&path, &corpus.text).unwrap();
+        println!("wrote {} ({} bytes)", path, corpus.text.len());
+    }
+}
+
+fn benchmarks(criterion: &mut Criterion) {
+    if let Ok(directory) = std::env::var("SAVE_CORPORA") {
+        return save(&directory);
+    }
+    for corpus in corpora::all() {
+        let text = &corpus.text;
+        let mut group = criterion.benchmark_group(corpus.name);
+        // Throughput is of the text, for serializing too, so the three rates compare.
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_function("validate", |bench| bench.iter(|| validate(black_box(text))));
+        group.bench_function("parse", |bench| bench.iter(|| parse(black_box(text))));
+        let value = parse(text);
+        group.bench_function("serialize", |bench| bench.iter(|| to_string(black_box(&value))));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,218 @@
+//! Parsing into storage that is reused from one document to the next.
+//!
+//! `JsonValue` gives every container its own vector and every string its own allocation, which
+//! a server parsing many small documents pays for again on each one. An `Arena` instead keeps
+//! the whole document in two buffers, one of nodes and one of text, that are cleared rather than
+//! freed between documents, so once they have grown to fit the largest document parsing stops
+//! allocating for the tree.
+
+use std::io::Cursor;
+use std::ops::Range;
+
+use crate::error::ParseError;
+use crate::events::{Event, EventReader};
+use crate::lexer::Lexer;
+use crate::parser::ParseOptions;
+use crate::value::JsonValue;
+
+/// One entry of a document laid out in order. A container is followed by its contents and
+/// records where they end; each member of an object is a `Key` followed by its value.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(Range<usize>),
+    Key(Range<usize>),
+    Array { end: usize, len: usize },
+    Object { end: usize, len: usize },
+}
+
+/// Storage for one parsed document at a time; see `parse_in_arena`.
+#[derive(Debug, Default)]
+pub struct Arena {
+    nodes: Vec<Node>,
+    text: String,
+    /// The containers still open while parsing.
+    open: Vec<usize>,
+}
+
+impl Arena {
+    pub fn new() -> Arena {
+        Arena::default()
+    }
+
+    /// Room for a document of `nodes` values and keys, with `text` bytes of strings and keys in all.
+    pub fn with_capacity(nodes: usize, text: usize) -> Arena {
+        Arena { nodes: Vec::with_capacity(nodes), text: String::with_capacity(text), open: Vec::new() }
+    }
+
+    /// Forgets the document parsed last, keeping the memory it used.
+    pub fn reset(&mut self) {
+        self.nodes.clear();
+        self.text.clear();
+        self.open.clear();
+    }
+
+    fn push_text(&mut self, text: &str) -> Range<usize> {
+        let start = self.text.len();
+        self.text.push_str(text);
+        start..self.text.len()
+    }
+
+    /// Adds a node, counting it as a member or element of the container it is in.
+    fn push(&mut self, node: Node) {
+        let count = match self.open.last().map(|&container| &mut self.nodes[container]) {
+            Some(Node::Array { len, .. }) => Some(len),
+            // An object's members are counted by their keys.
+            Some(Node::Object { len, .. }) if matches!(node, Node::Key(_)) => Some(len),
+            _ => None,
+        };
+        if let Some(len) = count { *len += 1; }
+        self.nodes.push(node);
+    }
+}
+
+/// Parses `input` into `arena`, replacing the document it held, and returns a view of the root.
+/// The arena cannot be used again until the view is dropped, and its memory is kept for the next
+/// document, so a loop parsing many documents only allocates while they keep getting larger.
+pub fn parse_in_arena<'a>(arena: &'a mut Arena, input: &str, options: &ParseOptions) -> Result<ArenaValue<'a>, ParseError> {
+    arena.reset();
+    let mut reader = EventReader::with_options(Lexer::new(Box::new(Cursor::new(input.to_string()))), options.clone());
+    while let Some(event) = reader.next_event()? {
+        match event {
+            Event::StartObject | Event::StartArray => {
+                let node = if event == Event::StartObject { Node::Object { end: 0, len: 0 } } else { Node::Array { end: 0, len: 0 } };
+                arena.push(node);
+                arena.open.push(arena.nodes.len() - 1);
+            }
+            Event::EndObject | Event::EndArray => {
+                let end = arena.nodes.len();
+                if let Some(container) = arena.open.pop() {
+                    if let Node::Array { end: last, .. } | Node::Object { end: last, .. } = &mut arena.nodes[container] {
+                        *last = end;
+                    }
+                }
+            }
+            Event::Key(key) => {
+                let range = arena.push_text(&key);
+                arena.push(Node::Key(range));
+            }
+            Event::Value(JsonValue::String(string)) => {
+                let range = arena.push_text(&string);
+                arena.push(Node::String(range));
+            }
+            Event::Value(JsonValue::Number(number)) => arena.push(Node::Number(number)),
+            Event::Value(JsonValue::Bool(boolean)) => arena.push(Node::Bool(boolean)),
+            Event::Value(_) => arena.push(Node::Null),
+        }
+    }
+    if arena.nodes.is_empty() { arena.push(Node::Null); }
+    Ok(ArenaValue { arena, index: 0 })
+}
+
+/// A value of the document in an `Arena`, read in place.
+#[derive(Debug, Clone, Copy)]
+pub struct ArenaValue<'a> {
+    arena: &'a Arena,
+    index: usize,
+}
+
+impl<'a> ArenaValue<'a> {
+    fn node(&self) -> &'a Node {
+        &self.arena.nodes[self.index]
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self.node(), Node::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.node() {
+            Node::Bool(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.node() {
+            Node::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        match self.node() {
+            Node::String(range) => Some(&self.arena.text[range.clone()]),
+            _ => None,
+        }
+    }
+
+    /// Members of an object or elements of an array; 0 for anything else.
+    pub fn len(&self) -> usize {
+        match self.node() {
+            Node::Array { len, .. } | Node::Object { len, .. } => *len,
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The elements of an array, or nothing for anything else.
+    pub fn elements(&self) -> impl Iterator<Item = ArenaValue<'a>> {
+        let end = match self.node() {
+            Node::Array { end, .. } => *end,
+            _ => self.index + 1,
+        };
+        let (arena, mut next) = (self.arena, self.index + 1);
+        std::iter::from_fn(move || {
+            if next >= end { return None; }
+            let value = ArenaValue { arena, index: next };
+            next = value.after();
+            Some(value)
+        })
+    }
+
+    /// The members of an object, in document order, or nothing for anything else.
+    pub fn members(&self) -> impl Iterator<Item = (&'a str, ArenaValue<'a>)> {
+        let end = match self.node() {
+            Node::Object { end, .. } => *end,
+            _ => self.index + 1,
+        };
+        let (arena, mut next) = (self.arena, self.index + 1);
+        std::iter::from_fn(move || {
+            if next >= end { return None; }
+            let Node::Key(key) = &arena.nodes[next] else { return None };
+            let value = ArenaValue { arena, index: next + 1 };
+            next = value.after();
+            Some((&arena.text[key.clone()], value))
+        })
+    }
+
+    /// The first member named `key` of an object.
+    pub fn get(&self, key: &str) -> Option<ArenaValue<'a>> {
+        self.members().find(|(name, _)| *name == key).map(|(_, value)| value)
+    }
+
+    /// Copies the value out of the arena.
+    pub fn to_value(&self) -> JsonValue {
+        match self.node() {
+            Node::Null | Node::Key(_) => JsonValue::Null,
+            Node::Bool(boolean) => JsonValue::Bool(*boolean),
+            Node::Number(number) => JsonValue::Number(*number),
+            Node::String(range) => JsonValue::String(self.arena.text[range.clone()].to_string()),
+            Node::Array { .. } => JsonValue::Array(self.elements().map(|element| element.to_value()).collect()),
+            Node::Object { .. } => JsonValue::Object(self.members().map(|(key, value)| (key.to_string(), value.to_value())).collect()),
+        }
+    }
+
+    /// Where the next value after this one, and everything in it, starts.
+    fn after(&self) -> usize {
+        match self.node() {
+            Node::Array { end, .. } | Node::Object { end, .. } => *end,
+            _ => self.index + 1,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::fmt;
+
+use crate::pointer::escape_token;
+use crate::value::JsonValue;
+
+/// An array whose elements are not all of the same JSON type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MixedArray {
+    pub pointer: String,
+    /// Element types in order of first appearance.
+    pub types: Vec<&'static str>,
+}
+
+/// Finds every array, at any depth, that mixes element types, e.g. `[1, "a", true]`.
+/// JSON allows this, so it is only a problem for schemas that expect uniform arrays.
+pub fn find_mixed_arrays(value: &JsonValue) -> Vec<MixedArray> {
+    let mut found = Vec::new();
+    collect_mixed_arrays(value, &mut String::new(), &mut found);
+    found
+}
+
+fn collect_mixed_arrays(value: &JsonValue, pointer: &mut String, found: &mut Vec<MixedArray>) {
+    let length = pointer.len();
+    match value {
+        JsonValue::Object(members) => {
+            for (key, member) in members {
+                pointer.push('/');
+                pointer.push_str(&escape_token(key));
+                collect_mixed_arrays(member, pointer, found);
+                pointer.truncate(length);
+            }
+        }
+        JsonValue::Array(elements) => {
+            let mut types = Vec::new();
+            for element in elements {
+                if !types.contains(&element.type_name()) {
+                    types.push(element.type_name());
+                }
+            }
+            if types.len() > 1 {
+                found.push(MixedArray { pointer: pointer.clone(), types });
+            }
+
+            for (index, element) in elements.iter().enumerate() {
+                pointer.push_str(&format!("/{}", index));
+                collect_mixed_arrays(element, pointer, found);
+                pointer.truncate(length);
+            }
+        }
+        _ => { }
+    }
+}
+
+/// Where a document first departs from a schema given to `matches_schema`, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    pub pointer: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() { "the root" } else { &self.pointer };
+        write!(f, "at {}: {}", pointer, self.message)
+    }
+}
+
+/// Checks `value` against a schema written as JSON itself. A string names the expected type
+/// (`"string"`, `"number"`, `"boolean"`, `"null"`, `"object"`, `"array"` or `"any"`); an object
+/// requires each of its members, matching recursively, and allows others; an array with one
+/// element requires every element to match it, and `[]` accepts any array.
+///
+/// `{"name": "string", "tags": ["string"], "owner": {"id": "number"}}`
+pub fn matches_schema(value: &JsonValue, schema: &JsonValue) -> Result<(), SchemaMismatch> {
+    match_schema(value, schema, &mut String::new())
+}
+
+fn match_schema(value: &JsonValue, schema: &JsonValue, pointer: &mut String) -> Result<(), SchemaMismatch> {
+    let mismatch = |pointer: &String, message: String| Err(SchemaMismatch { pointer: pointer.clone(), message });
+    let length = pointer.len();
+
+    match schema {
+        JsonValue::String(expected) => {
+            if !["string", "number", "boolean", "null", "object", "array", "any"].contains(&expected.as_str()) {
+                return mismatch(pointer, format!("invalid schema: unknown type '{}'", expected));
+            }
+            if expected != "any" && expected != value.type_name() {
+                return mismatch(pointer, format!("expected {}, found {}", expected, value.type_name()));
+            }
+        }
+        JsonValue::Object(fields) => {
+            let JsonValue::Object(members) = value else {
+                return mismatch(pointer, format!("expected object, found {}", value.type_name()));
+            };
+            for (key, field) in fields {
+                let Some((_, member)) = members.iter().find(|(name, _)| name == key) else {
+                    return mismatch(pointer, format!("missing member '{}'", key));
+                };
+                pointer.push('/');
+                pointer.push_str(&escape_token(key));
+                match_schema(member, field, pointer)?;
+                pointer.truncate(length);
+            }
+        }
+        JsonValue::Array(items) => {
+            let JsonValue::Array(elements) = value else {
+                return mismatch(pointer, format!("expected array, found {}", value.type_name()));
+            };
+            match items.as_slice() {
+                [] => { }
+                [item] => {
+                    for (index, element) in elements.iter().enumerate() {
+                        pointer.push_str(&format!("/{}", index));
+                        match_schema(element, item, pointer)?;
+                        pointer.truncate(length);
+                    }
+                }
+                _ => return mismatch(pointer, "invalid schema: an array schema has at most one element".to_string()),
+            }
+        }
+        _ => return mismatch(pointer, format!("invalid schema: {} is not a type", schema)),
+    }
+    Ok(())
+}
+
+/// A matter of layout in the text of a document, found by `lint`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    pub line: usize,
+    /// A short name for the kind of issue, such as "trailing-whitespace".
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Finds layout problems in the text of a document, which parsing ignores: indentation mixing
+/// tabs and spaces within a line or across the document, whitespace at the end of a line, and
+/// a missing newline at the end. A string cannot span lines, so the whitespace at either end of
+/// a line is never part of a value. Lines may end in `\r\n`.
+pub fn lint(text: &str) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut issue = |line, code, message: String| issues.push(LintIssue { line, code, message });
+    // The character the document is indented with, and the first line indented with it.
+    let mut style: Option<(char, usize)> = None;
+    for (index, line) in text.split('\n').enumerate() {
+        let number = index + 1;
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let content = line.trim_start_matches([' ', '\t']);
+        let indentation = &line[..line.len() - content.len()];
+
+        if content.is_empty() {
+            if !line.is_empty() { issue(number, "trailing-whitespace", "line holds only whitespace".to_string()); }
+            continue;
+        }
+        if content.ends_with([' ', '\t']) { issue(number, "trailing-whitespace", "trailing whitespace".to_string()); }
+        if indentation.contains(' ') && indentation.contains('\t') {
+            issue(number, "mixed-indentation", "indentation mixes tabs and spaces".to_string());
+            continue;
+        }
+        let Some(used) = indentation.chars().next() else { continue };
+        match style {
+            None => style = Some((used, number)),
+            Some((expected, first)) if expected != used => {
+                let names = |character| if character == '\t' { "tabs" } else { "spaces" };
+                issue(number, "inconsistent-indentation", format!("indented with {}, but line {} is indented with {}", names(used), first, names(expected)));
+            }
+            Some(_) => {}
+        }
+    }
+    if !text.is_empty() && !text.ends_with('\n') {
+        issue(text.split('\n').count(), "missing-final-newline", "no newline at the end of the file".to_string());
+    }
+    issues
+}
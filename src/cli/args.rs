@@ -0,0 +1,1459 @@
+use json_parser_rs::{parse_pointer, Alphabet, ArrayMerge, DuplicateKeys, Encoding, GenerateOptions, InvalidUtf8, JsonValue, LeadingZeros, Mode, ParseOptions, DEFAULT_MAX_DEPTH};
+
+use super::help::{diff_help, explore_help, format_help, generate_help, get_help, help, merge_help, patch_help, set_help, stats_help, validate_help};
+use super::diagnostic::ErrorFormat;
+use super::output::Level;
+
+/// Deepest nesting `--max-depth` allows. Values are dropped, printed and compared by recursion,
+/// which could overflow the stack of a worker thread on anything deeper.
+pub const MAX_DEPTH_LIMIT: usize = 1000;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Input {
+    Stdin,
+    File(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ColorChoice {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StatsFormat {
+    Text,
+    Json,
+}
+
+/// The tally printed on stderr once every input has been checked.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Summary {
+    /// A line of text after more than one input, unless quiet.
+    #[default]
+    Auto,
+    Text,
+    Json,
+    None,
+}
+
+/// A question asked about the value at a pointer in each document.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Query {
+    /// `--get`: the value itself.
+    Value(String),
+    /// `--type`: its type name, e.g. `object` or `boolean`.
+    Type(String),
+    /// `--length`: how many members, elements or characters it has.
+    Length(String),
+}
+
+impl Query {
+    pub fn pointer(&self) -> &str {
+        match self {
+            Query::Value(pointer) | Query::Type(pointer) | Query::Length(pointer) => pointer,
+        }
+    }
+}
+
+/// What `--select` does with a pointer that does not resolve.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Missing {
+    #[default]
+    Error,
+    Null,
+}
+
+/// What `--keys` and `--paths` list instead of validation results.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Listing {
+    /// The keys of the object at a pointer.
+    Keys(String),
+    /// The pointer of every value below the root, or with `leaves` only of scalars and empty containers.
+    Paths { leaves: bool },
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Format {
+    Pretty,
+    Compact,
+    /// RFC 8785 (JCS) canonical form.
+    Canonical,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum DiffFormat {
+    Text,
+    /// An RFC 6902 JSON Patch.
+    Patch,
+}
+
+/// How documents are printed, by every command that prints them.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Formatting {
+    pub format: Option<Format>,
+    /// Written once per nesting level in pretty output; the library default when `None`.
+    pub indent: Option<String>,
+    pub sort_keys: bool,
+    pub escape_unicode: bool,
+    pub collapse_whitespace: bool,
+}
+
+/// Collects the formatting options as they are parsed, then checks that they agree.
+#[derive(Default)]
+struct FormattingOptions {
+    formatting: Formatting,
+    indent_width: Option<usize>,
+    tab: bool,
+    canonical: bool,
+}
+
+impl FormattingOptions {
+    /// Takes `arg`, and its value from `args`, if it is a formatting option.
+    fn parse(&mut self, arg: &str, args: &mut dyn Iterator<Item = String>) -> Result<bool, String> {
+        if let Some(width) = value("--indent", arg, args)? {
+            let width: usize = width.parse().map_err(|_| format!("invalid value for '--indent': '{}' is not a number", width))?;
+            self.indent_width = Some(width);
+            return Ok(true);
+        }
+
+        match arg {
+            "--pretty" => self.formatting.format = Some(Format::Pretty),
+            "--compact" => self.formatting.format = Some(Format::Compact),
+            "--tab" => self.tab = true,
+            "--canonical" => self.canonical = true,
+            "--sort-keys" => self.formatting.sort_keys = true,
+            "--escape-unicode" => self.formatting.escape_unicode = true,
+            "--collapse-whitespace" => self.formatting.collapse_whitespace = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    fn finish(self) -> Result<Formatting, String> {
+        let mut formatting = self.formatting;
+        formatting.indent = match (self.indent_width, self.tab) {
+            (Some(_), true) => return Err("'--indent' cannot be combined with '--tab'".to_string()),
+            (Some(width), false) => Some(" ".repeat(width)),
+            (None, true) => Some("\t".to_string()),
+            (None, false) => None,
+        };
+        if formatting.indent.is_some() && formatting.format == Some(Format::Compact) {
+            return Err("'--indent' and '--tab' cannot be combined with '--compact'".to_string());
+        }
+        if self.canonical {
+            if formatting.format.is_some() || formatting.indent.is_some() {
+                return Err("'--canonical' cannot be combined with '--pretty', '--compact', '--indent' or '--tab'".to_string());
+            }
+            if formatting.escape_unicode {
+                return Err("'--canonical' cannot be combined with '--escape-unicode', which RFC 8785 does not allow".to_string());
+            }
+            if formatting.collapse_whitespace {
+                return Err("'--canonical' cannot be combined with '--collapse-whitespace'".to_string());
+            }
+            formatting.format = Some(Format::Canonical);
+        }
+        Ok(formatting)
+    }
+}
+
+/// Options every command accepts: how documents are read and how much is reported.
+#[derive(Debug, PartialEq, Clone)]
+pub struct GlobalOptions {
+    pub level: Level,
+    pub color: ColorChoice,
+    /// The `--lenient` preset: `Mode::Lenient` and each of the `allow_*` options below that
+    /// no `--no-*` flag turns off.
+    pub lenient: bool,
+    /// The `--strict` preset: plain RFC 8259, refusing every option that relaxes it.
+    pub strict: bool,
+    /// Set by `--allow-comments` or `--no-comments`, whichever came last; `None` leaves it to
+    /// the preset. Likewise for the four options below.
+    pub allow_comments: Option<bool>,
+    pub allow_trailing_commas: Option<bool>,
+    pub allow_single_quotes: Option<bool>,
+    pub allow_unquoted_keys: Option<bool>,
+    pub allow_nan: Option<bool>,
+    pub allow_trailing_data: bool,
+    pub replace_invalid: bool,
+    pub duplicate_keys: DuplicateKeys,
+    /// Compare and store object keys composed to NFC.
+    pub normalize_keys: bool,
+    pub invalid_utf8: InvalidUtf8,
+    pub leading_zeros: LeadingZeros,
+    /// How inputs are decoded before they are parsed.
+    pub encoding: Encoding,
+    pub max_depth: usize,
+    pub max_bytes: Option<usize>,
+    pub max_tokens: Option<usize>,
+    pub max_key_length: Option<usize>,
+    pub max_value_bytes: Option<usize>,
+    /// Where results go instead of standard output.
+    pub output: Option<String>,
+    /// Let `output` be one of the files read.
+    pub force: bool,
+    pub error_format: ErrorFormat,
+}
+
+impl Default for GlobalOptions {
+    fn default() -> GlobalOptions {
+        GlobalOptions {
+            level: Level::Normal,
+            color: ColorChoice::Auto,
+            lenient: false,
+            strict: false,
+            allow_comments: None,
+            allow_trailing_commas: None,
+            allow_single_quotes: None,
+            allow_unquoted_keys: None,
+            allow_nan: None,
+            allow_trailing_data: false,
+            replace_invalid: false,
+            duplicate_keys: DuplicateKeys::Allow,
+            normalize_keys: false,
+            invalid_utf8: InvalidUtf8::Error,
+            leading_zeros: LeadingZeros::Error,
+            encoding: Encoding::Utf8,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_bytes: None,
+            max_tokens: None,
+            max_key_length: None,
+            max_value_bytes: None,
+            output: None,
+            force: false,
+            error_format: ErrorFormat::Human,
+        }
+    }
+}
+
+impl GlobalOptions {
+    /// Takes `arg`, and its value from `args`, if it is a global option.
+    fn parse(&mut self, arg: &str, args: &mut dyn Iterator<Item = String>) -> Result<bool, String> {
+        if let Some(when) = value("--color", arg, args)? {
+            self.color = color_choice(&when)?;
+            return Ok(true);
+        }
+        if let Some(policy) = value("--duplicate-keys", arg, args)? {
+            self.duplicate_keys = match policy.as_str() {
+                "allow" => DuplicateKeys::Allow,
+                "warn" => DuplicateKeys::Warn,
+                "error" => DuplicateKeys::Error,
+                _ => return Err(format!("invalid value for '--duplicate-keys': '{}' (expected 'allow', 'warn' or 'error')", policy)),
+            };
+            return Ok(true);
+        }
+        if let Some(policy) = value("--invalid-utf8", arg, args)? {
+            self.invalid_utf8 = match policy.as_str() {
+                "error" => InvalidUtf8::Error,
+                "replace" => InvalidUtf8::Replace,
+                _ => return Err(format!("invalid value for '--invalid-utf8': '{}' (expected 'error' or 'replace')", policy)),
+            };
+            return Ok(true);
+        }
+        if let Some(policy) = value("--leading-zeros", arg, args)? {
+            self.leading_zeros = match policy.as_str() {
+                "error" => LeadingZeros::Error,
+                "decimal" => LeadingZeros::Decimal,
+                "octal" => LeadingZeros::Octal,
+                _ => return Err(format!("invalid value for '--leading-zeros': '{}' (expected 'error', 'decimal' or 'octal')", policy)),
+            };
+            return Ok(true);
+        }
+        if let Some(encoding) = value("--encoding", arg, args)? {
+            self.encoding = match encoding.as_str() {
+                "utf-8" => Encoding::Utf8,
+                "utf-16le" => Encoding::Utf16Le,
+                "utf-16be" => Encoding::Utf16Be,
+                _ => return Err(format!("invalid value for '--encoding': '{}' (expected 'utf-8', 'utf-16le' or 'utf-16be')", encoding)),
+            };
+            return Ok(true);
+        }
+        if let Some(format) = value("--error-format", arg, args)? {
+            self.error_format = match format.as_str() {
+                "human" => ErrorFormat::Human,
+                "json" => ErrorFormat::Json,
+                _ => return Err(format!("invalid value for '--error-format': '{}' (expected 'human' or 'json')", format)),
+            };
+            return Ok(true);
+        }
+        if let Some(levels) = value("--max-depth", arg, args)? {
+            self.max_depth = depth(&levels)?;
+            return Ok(true);
+        }
+        if let Some(size) = value("--max-bytes", arg, args)? {
+            self.max_bytes = Some(limit("--max-bytes", &size)?);
+            return Ok(true);
+        }
+        if let Some(count) = value("--max-tokens", arg, args)? {
+            self.max_tokens = Some(limit("--max-tokens", &count)?);
+            return Ok(true);
+        }
+        if let Some(length) = value("--max-key-length", arg, args)? {
+            self.max_key_length = Some(limit("--max-key-length", &length)?);
+            return Ok(true);
+        }
+        if let Some(size) = value("--max-value-bytes", arg, args)? {
+            self.max_value_bytes = Some(limit("--max-value-bytes", &size)?);
+            return Ok(true);
+        }
+        if let Some(path) = value("--output", arg, args)?.or(value("-o", arg, args)?) {
+            self.output = Some(path).filter(|path| path != "-");
+            return Ok(true);
+        }
+
+        match arg {
+            "-q" | "--quiet" => self.level = Level::Quiet,
+            "-v" | "--verbose" => self.level = Level::Verbose,
+            "--lenient" => self.lenient = true,
+            "--strict" => self.strict = true,
+            "--allow-comments" => self.allow_comments = Some(true),
+            "--allow-trailing-commas" => self.allow_trailing_commas = Some(true),
+            "--allow-single-quotes" => self.allow_single_quotes = Some(true),
+            "--allow-unquoted-keys" => self.allow_unquoted_keys = Some(true),
+            "--allow-nan" => self.allow_nan = Some(true),
+            "--no-comments" => self.allow_comments = Some(false),
+            "--no-trailing-commas" => self.allow_trailing_commas = Some(false),
+            "--no-single-quotes" => self.allow_single_quotes = Some(false),
+            "--no-unquoted-keys" => self.allow_unquoted_keys = Some(false),
+            "--no-nan" => self.allow_nan = Some(false),
+            "--allow-trailing-data" => self.allow_trailing_data = true,
+            "--replace-invalid" => self.replace_invalid = true,
+            "--normalize-keys" => self.normalize_keys = true,
+            "--force" => self.force = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// Rejects options that cannot be combined, once all of them are known.
+    fn finish(&self) -> Result<(), String> {
+        let allowed = [self.allow_comments, self.allow_trailing_commas, self.allow_single_quotes, self.allow_unquoted_keys, self.allow_nan].contains(&Some(true));
+        let relaxed = self.lenient || allowed || self.allow_trailing_data || self.replace_invalid || self.invalid_utf8 == InvalidUtf8::Replace || self.leading_zeros != LeadingZeros::Error;
+        if self.strict && relaxed {
+            return Err("'--strict' cannot be combined with '--lenient', any '--allow-*' option, '--replace-invalid', '--invalid-utf8=replace' or '--leading-zeros' other than 'error'".to_string());
+        }
+        if self.normalize_keys && !cfg!(feature = "unicode-normalization") {
+            return Err("'--normalize-keys' requires building with the 'unicode-normalization' feature".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn parse_options(&self) -> ParseOptions {
+        #[cfg(feature = "unicode-normalization")]
+        let normalize_key = self.normalize_keys.then_some(json_parser_rs::nfc_key as fn(&str) -> String);
+        #[cfg(not(feature = "unicode-normalization"))]
+        let normalize_key = None;
+        ParseOptions {
+            mode: if self.lenient { Mode::Lenient } else { Mode::Strict },
+            allow_comments: self.allow_comments.unwrap_or(self.lenient),
+            allow_trailing_commas: self.allow_trailing_commas.unwrap_or(self.lenient),
+            allow_single_quotes: self.allow_single_quotes.unwrap_or(self.lenient),
+            allow_unquoted_keys: self.allow_unquoted_keys.unwrap_or(self.lenient),
+            allow_nan: self.allow_nan.unwrap_or(self.lenient),
+            allow_trailing_data: self.allow_trailing_data,
+            replace_invalid: self.replace_invalid,
+            duplicate_keys: self.duplicate_keys,
+            normalize_key,
+            invalid_utf8: self.invalid_utf8,
+            leading_zeros: self.leading_zeros,
+            max_depth: self.max_depth,
+            max_bytes: self.max_bytes,
+            max_tokens: self.max_tokens,
+            max_key_length: self.max_key_length,
+            max_value_bytes: self.max_value_bytes,
+        }
+    }
+}
+
+/// A modification applied to each document, in command-line order.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Edit {
+    Set(String, JsonValue),
+    Delete(String),
+    /// Replace the document with an object of its leaves, keyed by their paths.
+    Flatten,
+    /// The inverse of `Flatten`.
+    Unflatten,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub inputs: Vec<Input>,
+    pub stdin_filename: String,
+    pub count_lines: bool,
+    pub gets: Vec<Query>,
+    /// `--select NAME=POINTER`: members of an object printed instead of each document.
+    pub selections: Vec<(String, String)>,
+    pub missing: Missing,
+    pub listing: Option<Listing>,
+    /// Print documents only down to this depth, summarizing the containers below it.
+    pub depth: Option<usize>,
+    /// Print only this many members or elements of each container, and how many more there are.
+    pub sample: Option<usize>,
+    /// With `depth` or `sample`, cut strings longer than this many characters short.
+    pub max_string_len: Option<usize>,
+    pub json: bool,
+    pub edits: Vec<Edit>,
+    pub create_missing: bool,
+    pub ignore_missing: bool,
+    /// Apply `--set` to the text of each document, leaving its formatting and comments alone.
+    pub preserve_formatting: bool,
+    /// What joins member names in the paths of `Edit::Flatten` and `Edit::Unflatten`.
+    pub separator: String,
+    pub write: bool,
+    /// Only report which documents printing would change, and exit 1 if any would.
+    pub check: bool,
+    /// With `check`, also print each change as a unified diff.
+    pub check_diff: bool,
+    pub formatting: Formatting,
+    pub homogeneous_arrays: bool,
+    /// Warn about the layout of the input text: indentation, trailing whitespace, final newline.
+    pub lint: bool,
+    pub ndjson: bool,
+    /// Print the elements of each document's root array one per line, reading one at a time.
+    pub to_ndjson: bool,
+    /// With `ndjson`, print the records as the elements of one array.
+    pub from_ndjson: bool,
+    /// With `ndjson`, wait at the end of the file for more records until Ctrl-C.
+    pub follow: bool,
+    /// Show how far through each input reading has got, when stderr is a terminal.
+    pub progress: bool,
+    /// Start no more inputs once one has failed.
+    pub fail_fast: bool,
+    /// Check files again whenever they change, until Ctrl-C.
+    pub watch: bool,
+    pub summary: Summary,
+    pub stats: Option<StatsFormat>,
+    pub validate_utf8_only: bool,
+    pub schema: Option<String>,
+    /// Read directories among the inputs for files with one of `extensions`.
+    pub recursive: bool,
+    pub extensions: Vec<String>,
+    /// Also read files and directories whose names start with a dot.
+    pub hidden: bool,
+    /// A list of more files to read, one per line or, with `null_separated`, per NUL.
+    pub files_from: Option<Input>,
+    pub null_separated: bool,
+    /// Inputs checked at once; as many as the machine has cores when `None`.
+    pub jobs: Option<usize>,
+    pub global: GlobalOptions,
+}
+
+impl Default for Args {
+    fn default() -> Args {
+        Args {
+            inputs: Vec::new(),
+            stdin_filename: "<stdin>".to_string(),
+            count_lines: false,
+            gets: Vec::new(),
+            selections: Vec::new(),
+            missing: Missing::Error,
+            listing: None,
+            depth: None,
+            sample: None,
+            max_string_len: None,
+            json: false,
+            edits: Vec::new(),
+            create_missing: false,
+            ignore_missing: false,
+            preserve_formatting: false,
+            separator: ".".to_string(),
+            write: false,
+            check: false,
+            check_diff: false,
+            formatting: Formatting::default(),
+            homogeneous_arrays: false,
+            lint: false,
+            ndjson: false,
+            to_ndjson: false,
+            from_ndjson: false,
+            follow: false,
+            progress: false,
+            fail_fast: false,
+            watch: false,
+            summary: Summary::Auto,
+            stats: None,
+            validate_utf8_only: false,
+            schema: None,
+            recursive: false,
+            extensions: vec!["json".to_string()],
+            hidden: false,
+            files_from: None,
+            null_separated: false,
+            jobs: None,
+            global: GlobalOptions::default(),
+        }
+    }
+}
+
+impl Args {
+    /// Whether documents are printed back out, edited or just reformatted, rather than reported on.
+    pub fn rewrites(&self) -> bool {
+        !self.edits.is_empty() || self.formatting != Formatting::default()
+    }
+
+    pub fn name<'a>(&'a self, input: &'a Input) -> &'a str {
+        match input {
+            Input::Stdin => &self.stdin_filename,
+            Input::File(path) => path,
+        }
+    }
+}
+
+/// Returns the value of an option given either as `--name=value` or as `--name value`.
+fn value(name: &str, arg: &str, args: &mut dyn Iterator<Item = String>) -> Result<Option<String>, String> {
+    if arg == name {
+        return args.next().map(Some).ok_or_else(|| format!("option '{}' requires a value", name));
+    }
+    Ok(arg.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')).map(str::to_string))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DiffArgs {
+    pub old: Input,
+    pub new: Input,
+    pub format: DiffFormat,
+    pub global: GlobalOptions,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MergeArgs {
+    /// The base document followed by the overlays.
+    pub inputs: Vec<Input>,
+    pub null_deletes: bool,
+    pub arrays: ArrayMerge,
+    pub formatting: Formatting,
+    pub global: GlobalOptions,
+}
+
+fn color_choice(when: &str) -> Result<ColorChoice, String> {
+    match when {
+        "auto" => Ok(ColorChoice::Auto),
+        "always" => Ok(ColorChoice::Always),
+        "never" => Ok(ColorChoice::Never),
+        _ => Err(format!("invalid value for '--color': '{}' (expected 'auto', 'always' or 'never')", when)),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PatchArgs {
+    pub document: Input,
+    pub patch: Input,
+    pub dry_run: bool,
+    pub write: bool,
+    pub formatting: Formatting,
+    pub global: GlobalOptions,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ExploreArgs {
+    pub input: Input,
+    /// Levels of each value printed before the containers below are summarized.
+    pub depth: usize,
+    pub formatting: Formatting,
+    pub global: GlobalOptions,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GenerateArgs {
+    /// Where the random documents start from; taken from the clock when `None`.
+    pub seed: Option<u64>,
+    pub count: usize,
+    pub options: GenerateOptions,
+    pub formatting: Formatting,
+    pub global: GlobalOptions,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Box<Args>),
+    Diff(DiffArgs),
+    Merge(MergeArgs),
+    Patch(PatchArgs),
+    Explore(ExploreArgs),
+    Generate(GenerateArgs),
+    /// Print this help text.
+    Help(String),
+    Version,
+}
+
+impl Command {
+    /// The global options of a command that reads documents.
+    pub fn global(&self) -> Option<&GlobalOptions> {
+        match self {
+            Command::Run(args) => Some(&args.global),
+            Command::Diff(args) => Some(&args.global),
+            Command::Merge(args) => Some(&args.global),
+            Command::Patch(args) => Some(&args.global),
+            Command::Explore(args) => Some(&args.global),
+            Command::Generate(args) => Some(&args.global),
+            Command::Help(_) | Command::Version => None,
+        }
+    }
+
+    /// Every file the command reads, schemas included.
+    pub fn files(&self) -> Vec<&str> {
+        let inputs: Vec<&Input> = match self {
+            Command::Run(args) => args.inputs.iter().collect(),
+            Command::Diff(args) => vec![&args.old, &args.new],
+            Command::Merge(args) => args.inputs.iter().collect(),
+            Command::Patch(args) => vec![&args.document, &args.patch],
+            Command::Explore(args) => vec![&args.input],
+            Command::Generate(_) | Command::Help(_) | Command::Version => Vec::new(),
+        };
+        let mut files: Vec<&str> = inputs.into_iter().filter_map(|input| match input {
+            Input::Stdin => None,
+            Input::File(path) => Some(path.as_str()),
+        }).collect();
+        if let Command::Run(args) = self {
+            files.extend(args.schema.as_deref());
+        }
+        files
+    }
+}
+
+/// Parses the arguments following a command's name.
+type CommandParser = fn(&mut dyn Iterator<Item = String>) -> Result<Command, String>;
+
+fn command_parser(name: &str) -> Option<CommandParser> {
+    match name {
+        "validate" => Some(parse_validate),
+        "format" => Some(parse_format),
+        "get" => Some(parse_get),
+        "set" => Some(parse_set),
+        "stats" => Some(parse_stats),
+        "diff" => Some(parse_diff),
+        "merge" => Some(parse_merge),
+        "patch" => Some(parse_patch),
+        "explore" => Some(parse_explore),
+        "generate" => Some(parse_generate),
+        _ => None,
+    }
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let command = parse_command(args)?;
+    if let Some(global) = command.global() {
+        global.finish()?;
+    }
+    Ok(command)
+}
+
+fn parse_command(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    let mut only_files = false;
+    let mut formatting = FormattingOptions::default();
+    let mut diff = false;
+    let (mut paths, mut leaves) = (false, false);
+
+    let mut args = args.into_iter();
+    // Global options may come before a command's name, and are then read as if they came after it.
+    let (mut leading, mut global, mut command) = (Vec::new(), GlobalOptions::default(), None);
+    while let Some(arg) = args.next() {
+        command = command_parser(&arg);
+        if command.is_some() { break; }
+        let mut values = Vec::new();
+        let taken = global.parse(&arg, &mut args.by_ref().inspect(|value| values.push(value.clone())))?;
+        leading.push(arg);
+        leading.extend(values);
+        if !taken { break; }
+    }
+    if let Some(command) = command {
+        return command(&mut leading.into_iter().chain(args));
+    }
+    let mut args = leading.into_iter().chain(args);
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            parsed.inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if parsed.global.parse(&arg, &mut args)? {
+            continue;
+        }
+        if let Some(name) = value("--stdin-filename", &arg, &mut args)? {
+            parsed.stdin_filename = name;
+            continue;
+        }
+        if let Some(pointer) = value("--get", &arg, &mut args)? {
+            parse_pointer(&pointer)?;
+            parsed.gets.push(Query::Value(pointer));
+            continue;
+        }
+        if let Some(selection) = value("--select", &arg, &mut args)? {
+            parsed.selections.push(select(&selection)?);
+            continue;
+        }
+        if let Some(missing) = value("--missing", &arg, &mut args)? {
+            parsed.missing = match missing.as_str() {
+                "error" => Missing::Error,
+                "null" => Missing::Null,
+                _ => return Err(format!("invalid value for '--missing': '{}' (expected 'error' or 'null')", missing)),
+            };
+            continue;
+        }
+        if let Some(pointer) = value("--type", &arg, &mut args)? {
+            parse_pointer(&pointer)?;
+            parsed.gets.push(Query::Type(pointer));
+            continue;
+        }
+        if let Some(pointer) = value("--length", &arg, &mut args)? {
+            parse_pointer(&pointer)?;
+            parsed.gets.push(Query::Length(pointer));
+            continue;
+        }
+        if let Some(depth) = value("--depth", &arg, &mut args)? {
+            parsed.depth = Some(number("--depth", &depth)?);
+            continue;
+        }
+        if let Some(count) = value("--sample", &arg, &mut args)? {
+            parsed.sample = Some(number("--sample", &count)?);
+            continue;
+        }
+        if let Some(length) = value("--max-string-len", &arg, &mut args)? {
+            parsed.max_string_len = Some(number("--max-string-len", &length)?);
+            continue;
+        }
+        if let Some(pointer) = arg.strip_prefix("--keys=") {
+            parse_pointer(pointer)?;
+            parsed.listing = Some(Listing::Keys(pointer.to_string()));
+            continue;
+        }
+        if formatting.parse(&arg, &mut args)? {
+            continue;
+        }
+        if let Some(path) = value("--schema", &arg, &mut args)? {
+            parsed.schema = Some(path);
+            continue;
+        }
+        if let Some(list) = value("--ext", &arg, &mut args)? {
+            parsed.extensions = extensions(&list)?;
+            continue;
+        }
+        if let Some(list) = value("--files-from", &arg, &mut args)? {
+            parsed.files_from = Some(input(list));
+            continue;
+        }
+        if let Some(count) = value("--jobs", &arg, &mut args)?.or(value("-j", &arg, &mut args)?) {
+            parsed.jobs = Some(jobs(&count)?);
+            continue;
+        }
+        if let Some(format) = value("--summary", &arg, &mut args)? {
+            parsed.summary = summary(&format)?;
+            continue;
+        }
+        if let Some(format) = value("--stats-format", &arg, &mut args)? {
+            parsed.stats = Some(stats_format("--stats-format", &format)?);
+            continue;
+        }
+        if let Some(pointer) = value("--delete", &arg, &mut args)? {
+            parse_pointer(&pointer)?;
+            parsed.edits.push(Edit::Delete(pointer));
+            continue;
+        }
+        if let Some(separator) = value("--separator", &arg, &mut args)? {
+            if separator.is_empty() {
+                return Err("invalid value for '--separator': '' (expected at least one character)".to_string());
+            }
+            parsed.separator = separator;
+            continue;
+        }
+
+        match arg.as_str() {
+            "--set" => {
+                let (Some(pointer), Some(literal)) = (args.next(), args.next()) else {
+                    return Err("option '--set' requires a pointer and a value".to_string());
+                };
+                parsed.edits.push(set_edit("--set", pointer, &literal)?);
+            }
+            "-h" | "--help" => return Ok(Command::Help(help())),
+            "-V" | "--version" => return Ok(Command::Version),
+            "--diff" => diff = true,
+            "--count-lines" => parsed.count_lines = true,
+            "--json" => parsed.json = true,
+            "--keys" => parsed.listing = Some(Listing::Keys(String::new())),
+            "--paths" => paths = true,
+            "--leaves" => leaves = true,
+            "--flatten" => parsed.edits.push(Edit::Flatten),
+            "--unflatten" => parsed.edits.push(Edit::Unflatten),
+            "--preserve-formatting" => parsed.preserve_formatting = true,
+            "--create-missing" => parsed.create_missing = true,
+            "--ignore-missing" => parsed.ignore_missing = true,
+            "--write" => parsed.write = true,
+            "--check" => parsed.check = true,
+            "-r" | "--recursive" => parsed.recursive = true,
+            "--hidden" => parsed.hidden = true,
+            "-0" | "--null" => parsed.null_separated = true,
+            "--homogeneous-arrays" => parsed.homogeneous_arrays = true,
+            "--lint" => parsed.lint = true,
+            "--ndjson" => parsed.ndjson = true,
+            "--to-ndjson" => parsed.to_ndjson = true,
+            "--from-ndjson" => parsed.from_ndjson = true,
+            "--follow" => parsed.follow = true,
+            "--progress" => parsed.progress = true,
+            "--fail-fast" => parsed.fail_fast = true,
+            "--keep-going" => parsed.fail_fast = false,
+            "--watch" => parsed.watch = true,
+            "--validate-utf8-only" => parsed.validate_utf8_only = true,
+            "--stats" => parsed.stats = parsed.stats.or(Some(StatsFormat::Text)),
+            _ => operand(arg, &mut only_files, &mut parsed.inputs)?,
+        }
+    }
+
+    parsed.formatting = formatting.finish()?;
+    if paths {
+        if parsed.listing.is_some() {
+            return Err("'--keys' and '--paths' cannot be combined".to_string());
+        }
+        parsed.listing = Some(Listing::Paths { leaves });
+    } else if leaves {
+        return Err("'--leaves' requires '--paths'".to_string());
+    }
+    if diff {
+        if parsed != (Args { inputs: parsed.inputs.clone(), global: parsed.global.clone(), ..Args::default() }) {
+            return Err("'--diff' cannot be combined with options other than the global ones".to_string());
+        }
+        return diff_command(parsed.inputs, DiffFormat::Text, parsed.global);
+    }
+    run_command(parsed)
+}
+
+/// Checks that the options of a validating, querying or editing run agree, and reads standard input when no files are given.
+fn run_command(mut parsed: Args) -> Result<Command, String> {
+    if parsed.null_separated && parsed.files_from.is_none() {
+        return Err("'--null' requires '--files-from'".to_string());
+    }
+    if parsed.files_from == Some(Input::Stdin) && parsed.inputs.contains(&Input::Stdin) {
+        return Err("'--files-from -' reads the list from standard input, so no document can be read from it".to_string());
+    }
+    if parsed.inputs.is_empty() && parsed.files_from.is_none() {
+        parsed.inputs.push(Input::Stdin);
+    }
+    if parsed.watch && (parsed.inputs.contains(&Input::Stdin) || parsed.files_from.is_some()) {
+        return Err("'--watch' requires files or directories to watch, not standard input or '--files-from'".to_string());
+    }
+    if parsed.watch && (parsed.follow || parsed.write || parsed.check || parsed.global.output.is_some()) {
+        return Err("'--watch' cannot be combined with '--follow', '--write', '--check' or '--output'".to_string());
+    }
+    if parsed.watch && matches!(parsed.summary, Summary::Text | Summary::Json) {
+        return Err("'--summary' cannot be combined with '--watch', which runs until Ctrl-C".to_string());
+    }
+    if !parsed.recursive && !parsed.watch && (parsed.hidden || parsed.extensions != Args::default().extensions) {
+        return Err("'--ext' and '--hidden' require '--recursive' or '--watch'".to_string());
+    }
+    if !parsed.gets.is_empty() && parsed.rewrites() {
+        return Err("'--get', '--type' and '--length' cannot be combined with editing or formatting options".to_string());
+    }
+    let checks = parsed.homogeneous_arrays || parsed.schema.is_some();
+    if parsed.missing != Missing::Error && parsed.selections.is_empty() {
+        return Err("'--missing' requires '--select'".to_string());
+    }
+    if !parsed.selections.is_empty() && (!parsed.gets.is_empty() || !parsed.edits.is_empty() || parsed.write || parsed.check) {
+        return Err("'--select' cannot be combined with '--get', '--type', '--length', '--write', '--check' or editing options".to_string());
+    }
+    for (index, (name, _)) in parsed.selections.iter().enumerate() {
+        if parsed.selections[..index].iter().any(|(other, _)| other == name) {
+            return Err(format!("'--select' names '{}' more than once", name));
+        }
+    }
+    let queries = !parsed.gets.is_empty() || !parsed.selections.is_empty();
+    let preview = parsed.depth.is_some() || parsed.sample.is_some();
+    let reports = queries || parsed.listing.is_some() || preview || parsed.stats.is_some() || parsed.validate_utf8_only;
+    if parsed.to_ndjson && (parsed.from_ndjson || parsed.ndjson || reports || checks || !parsed.edits.is_empty() || parsed.write || parsed.check) {
+        return Err("'--to-ndjson' can only be combined with formatting options".to_string());
+    }
+    if parsed.from_ndjson && (reports || parsed.write || parsed.check) {
+        return Err("'--from-ndjson' cannot be combined with '--write', '--check' or options that print something other than the document".to_string());
+    }
+    if parsed.follow && (!parsed.ndjson || parsed.from_ndjson || parsed.write || parsed.check || parsed.recursive || parsed.files_from.is_some()) {
+        return Err("'--follow' requires '--ndjson' and cannot be combined with '--from-ndjson', '--write', '--check', '--recursive' or '--files-from'".to_string());
+    }
+    if parsed.follow && (parsed.inputs.is_empty() || parsed.inputs.contains(&Input::Stdin)) {
+        return Err("'--follow' requires files, not standard input".to_string());
+    }
+    parsed.ndjson |= parsed.from_ndjson;
+    if parsed.preserve_formatting && (parsed.edits.is_empty() || parsed.edits.iter().any(|edit| !matches!(edit, Edit::Set(..)))) {
+        return Err("'--preserve-formatting' requires '--set' and cannot be combined with other edits".to_string());
+    }
+    if parsed.preserve_formatting && (parsed.formatting != Formatting::default() || parsed.create_missing || parsed.ndjson) {
+        return Err("'--preserve-formatting' cannot be combined with formatting options, '--create-missing' or '--ndjson'".to_string());
+    }
+    if parsed.max_string_len.is_some() && !preview {
+        return Err("'--max-string-len' requires '--depth' or '--sample'".to_string());
+    }
+    let printing = parsed.formatting.format != Some(Format::Canonical) && parsed.edits.is_empty();
+    if preview && (!printing || parsed.write || parsed.check || queries || parsed.listing.is_some() || parsed.stats.is_some() || parsed.ndjson || checks) {
+        let option = if parsed.depth.is_some() { "--depth" } else { "--sample" };
+        return Err(format!("'{}' only combines with '--depth', '--sample', '--max-string-len', '--pretty', '--compact', '--indent', '--tab', '--sort-keys' and '--escape-unicode'", option));
+    }
+    if parsed.listing.is_some() && (queries || parsed.rewrites() || parsed.stats.is_some() || parsed.ndjson || checks) {
+        return Err("'--keys' and '--paths' cannot be combined with '--get', '--stats', '--ndjson', '--schema', '--homogeneous-arrays' or editing and formatting options".to_string());
+    }
+    if parsed.stats.is_some() && (queries || parsed.rewrites() || parsed.ndjson || checks) {
+        return Err("'--stats' cannot be combined with '--get', '--ndjson', '--schema', '--homogeneous-arrays' or editing and formatting options".to_string());
+    }
+    if parsed.validate_utf8_only && (queries || parsed.listing.is_some() || preview || parsed.rewrites() || parsed.stats.is_some() || parsed.ndjson || checks) {
+        return Err("'--validate-utf8-only' cannot be combined with options that need the parsed document".to_string());
+    }
+    if parsed.stdin_filename != Args::default().stdin_filename && (parsed.inputs.iter().any(|input| input != &Input::Stdin) || parsed.files_from.is_some()) {
+        return Err("'--stdin-filename' only names standard input and cannot be combined with files to read".to_string());
+    }
+    if parsed.lint && (reports || parsed.rewrites() || parsed.ndjson || parsed.to_ndjson) {
+        return Err("'--lint' cannot be combined with '--ndjson', '--to-ndjson' or options that print something other than a validation result".to_string());
+    }
+    if parsed.write && !parsed.rewrites() {
+        return Err("'--write' requires '--set', '--delete' or a formatting option".to_string());
+    }
+    if parsed.check && !parsed.rewrites() {
+        return Err("'--check' requires '--set', '--delete' or a formatting option".to_string());
+    }
+    if parsed.check && (parsed.write || parsed.ndjson) {
+        return Err("'--check' cannot be combined with '--write' or '--ndjson'".to_string());
+    }
+    if parsed.write && parsed.ndjson {
+        return Err("'--write' cannot be combined with '--ndjson'".to_string());
+    }
+    if parsed.write && parsed.inputs.contains(&Input::Stdin) {
+        return Err("'--write' cannot be used with standard input".to_string());
+    }
+    if parsed.write && parsed.global.output.is_some() {
+        return Err("'--write' cannot be combined with '--output'".to_string());
+    }
+
+    Ok(Command::Run(Box::new(parsed)))
+}
+
+/// Takes an argument no option claimed: `--` ends the options, `-` is standard input and anything else is a file.
+fn operand(arg: String, only_files: &mut bool, inputs: &mut Vec<Input>) -> Result<(), String> {
+    match arg.as_str() {
+        "--" => *only_files = true,
+        "-" => inputs.push(Input::Stdin),
+        flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+        _ => inputs.push(Input::File(arg)),
+    }
+    Ok(())
+}
+
+fn input(path: String) -> Input {
+    if path == "-" { Input::Stdin } else { Input::File(path) }
+}
+
+/// Removes the first operand, for commands whose leading operands are not files.
+fn take_operand(inputs: &mut Vec<Input>) -> Option<String> {
+    if inputs.is_empty() { return None; }
+    match inputs.remove(0) {
+        Input::Stdin => Some("-".to_string()),
+        Input::File(text) => Some(text),
+    }
+}
+
+/// Parses the `NAME=POINTER` of `--select`.
+fn select(selection: &str) -> Result<(String, String), String> {
+    let Some((name, pointer)) = selection.split_once('=') else {
+        return Err(format!("invalid value for '--select': '{}' (expected NAME=POINTER, e.g. 'port=/server/port')", selection));
+    };
+    parse_pointer(pointer)?;
+    Ok((name.to_string(), pointer.to_string()))
+}
+
+fn set_edit(option: &str, pointer: String, literal: &str) -> Result<Edit, String> {
+    parse_pointer(&pointer)?;
+    let value = literal.parse().map_err(|error| format!("invalid value for '{} {}': {}", option, pointer, error))?;
+    Ok(Edit::Set(pointer, value))
+}
+
+/// Parses the comma-separated list of `--ext`, with or without leading dots.
+fn extensions(list: &str) -> Result<Vec<String>, String> {
+    let extensions: Vec<String> = list.split(',').map(|extension| extension.trim().trim_start_matches('.').to_string()).collect();
+    if extensions.iter().any(String::is_empty) {
+        return Err(format!("invalid value for '--ext': '{}' (expected extensions separated by commas, e.g. 'json,jsonc')", list));
+    }
+    Ok(extensions)
+}
+
+fn number(option: &str, text: &str) -> Result<usize, String> {
+    text.parse().map_err(|_| format!("invalid value for '{}': '{}' is not a number", option, text))
+}
+
+/// Reads a `--max-depth`, which may not be over `MAX_DEPTH_LIMIT`.
+fn depth(text: &str) -> Result<usize, String> {
+    match number("--max-depth", text)? {
+        levels if levels > MAX_DEPTH_LIMIT => Err(format!("invalid value for '--max-depth': {} is over the limit of {}", levels, MAX_DEPTH_LIMIT)),
+        levels => Ok(levels),
+    }
+}
+
+/// Reads a count such as `1000`, `64K`, `10M` or `2G`, the suffixes standing for powers of 1024.
+fn limit(name: &str, count: &str) -> Result<usize, String> {
+    let (digits, unit) = match count.char_indices().last() {
+        Some((at, 'K' | 'k')) => (&count[..at], 1 << 10),
+        Some((at, 'M' | 'm')) => (&count[..at], 1 << 20),
+        Some((at, 'G' | 'g')) => (&count[..at], 1 << 30),
+        _ => (count, 1),
+    };
+    digits.parse::<usize>().ok().and_then(|number| number.checked_mul(unit))
+        .ok_or_else(|| format!("invalid value for '{}': '{}' (expected a number, optionally followed by K, M or G)", name, count))
+}
+
+fn jobs(count: &str) -> Result<usize, String> {
+    match count.parse() {
+        Ok(0) | Err(_) => Err(format!("invalid value for '--jobs': '{}' (expected a positive number)", count)),
+        Ok(jobs) => Ok(jobs),
+    }
+}
+
+fn summary(format: &str) -> Result<Summary, String> {
+    match format {
+        "text" => Ok(Summary::Text),
+        "json" => Ok(Summary::Json),
+        "none" => Ok(Summary::None),
+        _ => Err(format!("invalid value for '--summary': '{}' (expected 'text', 'json' or 'none')", format)),
+    }
+}
+
+fn stats_format(option: &str, format: &str) -> Result<StatsFormat, String> {
+    match format {
+        "text" => Ok(StatsFormat::Text),
+        "json" => Ok(StatsFormat::Json),
+        _ => Err(format!("invalid value for '{}': '{}' (expected 'text' or 'json')", option, format)),
+    }
+}
+
+fn parse_validate(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            parsed.inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if parsed.global.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(name) = value("--stdin-filename", &arg, args)? {
+            parsed.stdin_filename = name;
+            continue;
+        }
+        if let Some(path) = value("--schema", &arg, args)? {
+            parsed.schema = Some(path);
+            continue;
+        }
+        if let Some(list) = value("--ext", &arg, args)? {
+            parsed.extensions = extensions(&list)?;
+            continue;
+        }
+        if let Some(list) = value("--files-from", &arg, args)? {
+            parsed.files_from = Some(input(list));
+            continue;
+        }
+        if let Some(count) = value("--jobs", &arg, args)?.or(value("-j", &arg, args)?) {
+            parsed.jobs = Some(jobs(&count)?);
+            continue;
+        }
+        if let Some(format) = value("--summary", &arg, args)? {
+            parsed.summary = summary(&format)?;
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(validate_help())),
+            "-r" | "--recursive" => parsed.recursive = true,
+            "--hidden" => parsed.hidden = true,
+            "-0" | "--null" => parsed.null_separated = true,
+            "--count-lines" => parsed.count_lines = true,
+            "--ndjson" => parsed.ndjson = true,
+            "--follow" => parsed.follow = true,
+            "--progress" => parsed.progress = true,
+            "--fail-fast" => parsed.fail_fast = true,
+            "--keep-going" => parsed.fail_fast = false,
+            "--watch" => parsed.watch = true,
+            "--homogeneous-arrays" => parsed.homogeneous_arrays = true,
+            "--lint" => parsed.lint = true,
+            "--validate-utf8-only" => parsed.validate_utf8_only = true,
+            _ => operand(arg, &mut only_files, &mut parsed.inputs)?,
+        }
+    }
+
+    run_command(parsed)
+}
+
+fn parse_format(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    let mut formatting = FormattingOptions::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            parsed.inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if parsed.global.parse(&arg, args)? || formatting.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(name) = value("--stdin-filename", &arg, args)? {
+            parsed.stdin_filename = name;
+            continue;
+        }
+        if let Some(list) = value("--files-from", &arg, args)? {
+            parsed.files_from = Some(input(list));
+            continue;
+        }
+        if let Some(format) = value("--summary", &arg, args)? {
+            parsed.summary = summary(&format)?;
+            continue;
+        }
+        if let Some(depth) = value("--depth", &arg, args)? {
+            parsed.depth = Some(number("--depth", &depth)?);
+            continue;
+        }
+        if let Some(count) = value("--sample", &arg, args)? {
+            parsed.sample = Some(number("--sample", &count)?);
+            continue;
+        }
+        if let Some(length) = value("--max-string-len", &arg, args)? {
+            parsed.max_string_len = Some(number("--max-string-len", &length)?);
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(format_help())),
+            "-0" | "--null" => parsed.null_separated = true,
+            "--ndjson" => parsed.ndjson = true,
+            "--write" => parsed.write = true,
+            "--check" => parsed.check = true,
+            "--diff" => parsed.check_diff = true,
+            "--progress" => parsed.progress = true,
+            _ => operand(arg, &mut only_files, &mut parsed.inputs)?,
+        }
+    }
+
+    parsed.formatting = formatting.finish()?;
+    parsed.formatting.format.get_or_insert(Format::Pretty);
+    if parsed.check_diff && !parsed.check {
+        return Err("'--diff' requires '--check'".to_string());
+    }
+    run_command(parsed)
+}
+
+fn parse_get(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            parsed.inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if parsed.global.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(name) = value("--stdin-filename", &arg, args)? {
+            parsed.stdin_filename = name;
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(get_help())),
+            "--json" => parsed.json = true,
+            "--ndjson" => parsed.ndjson = true,
+            _ => operand(arg, &mut only_files, &mut parsed.inputs)?,
+        }
+    }
+
+    let Some(pointer) = take_operand(&mut parsed.inputs) else {
+        return Err("'get' requires a pointer".to_string());
+    };
+    parse_pointer(&pointer)?;
+    parsed.gets.push(Query::Value(pointer));
+    run_command(parsed)
+}
+
+fn parse_set(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args::default();
+    let mut formatting = FormattingOptions::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            parsed.inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if parsed.global.parse(&arg, args)? || formatting.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(name) = value("--stdin-filename", &arg, args)? {
+            parsed.stdin_filename = name;
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(set_help())),
+            "--create-missing" => parsed.create_missing = true,
+            "--write" => parsed.write = true,
+            "--ndjson" => parsed.ndjson = true,
+            _ => operand(arg, &mut only_files, &mut parsed.inputs)?,
+        }
+    }
+
+    let (Some(pointer), Some(literal)) = (take_operand(&mut parsed.inputs), take_operand(&mut parsed.inputs)) else {
+        return Err("'set' requires a pointer and a value".to_string());
+    };
+    parsed.edits.push(set_edit("set", pointer, &literal)?);
+    parsed.formatting = formatting.finish()?;
+    run_command(parsed)
+}
+
+fn parse_stats(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut parsed = Args { stats: Some(StatsFormat::Text), ..Args::default() };
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            parsed.inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if parsed.global.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(name) = value("--stdin-filename", &arg, args)? {
+            parsed.stdin_filename = name;
+            continue;
+        }
+        if let Some(format) = value("--format", &arg, args)? {
+            parsed.stats = Some(stats_format("--format", &format)?);
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(stats_help())),
+            _ => operand(arg, &mut only_files, &mut parsed.inputs)?,
+        }
+    }
+
+    run_command(parsed)
+}
+
+fn parse_diff(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut inputs = Vec::new();
+    let mut format = DiffFormat::Text;
+    let mut global = GlobalOptions::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if global.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(name) = value("--format", &arg, args)? {
+            format = match name.as_str() {
+                "text" => DiffFormat::Text,
+                "patch" => DiffFormat::Patch,
+                _ => return Err(format!("invalid value for '--format': '{}' (expected 'text' or 'patch')", name)),
+            };
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(diff_help())),
+            _ => operand(arg, &mut only_files, &mut inputs)?,
+        }
+    }
+
+    diff_command(inputs, format, global)
+}
+
+fn diff_command(inputs: Vec<Input>, format: DiffFormat, global: GlobalOptions) -> Result<Command, String> {
+    let Ok([old, new]) = <[Input; 2]>::try_from(inputs) else {
+        return Err("'diff' requires exactly two files".to_string());
+    };
+    if old == Input::Stdin && new == Input::Stdin {
+        return Err("'diff' can read only one of its files from standard input".to_string());
+    }
+    Ok(Command::Diff(DiffArgs { old, new, format, global }))
+}
+
+fn parse_merge(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut inputs = Vec::new();
+    let mut null_deletes = true;
+    let mut arrays = ArrayMerge::Replace;
+    let mut formatting = FormattingOptions::default();
+    let mut global = GlobalOptions::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if global.parse(&arg, args)? || formatting.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(strategy) = value("--arrays", &arg, args)? {
+            arrays = match strategy.as_str() {
+                "replace" => ArrayMerge::Replace,
+                "concat" => ArrayMerge::Concat,
+                "union" => ArrayMerge::Union,
+                _ => return Err(format!("invalid value for '--arrays': '{}' (expected 'replace', 'concat' or 'union')", strategy)),
+            };
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(merge_help())),
+            "--no-null-deletes" => null_deletes = false,
+            _ => operand(arg, &mut only_files, &mut inputs)?,
+        }
+    }
+
+    if inputs.len() < 2 {
+        return Err("'merge' requires a base file and at least one overlay".to_string());
+    }
+    if inputs.iter().filter(|input| **input == Input::Stdin).count() > 1 {
+        return Err("'merge' can read only one of its files from standard input".to_string());
+    }
+    Ok(Command::Merge(MergeArgs { inputs, null_deletes, arrays, formatting: formatting.finish()?, global }))
+}
+
+fn parse_patch(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut inputs = Vec::new();
+    let mut dry_run = false;
+    let mut write = false;
+    let mut formatting = FormattingOptions::default();
+    let mut global = GlobalOptions::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if global.parse(&arg, args)? || formatting.parse(&arg, args)? {
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(patch_help())),
+            "--dry-run" => dry_run = true,
+            "--write" => write = true,
+            _ => operand(arg, &mut only_files, &mut inputs)?,
+        }
+    }
+
+    let Ok([document, patch]) = <[Input; 2]>::try_from(inputs) else {
+        return Err("'patch' requires a file and a patch".to_string());
+    };
+    if document == Input::Stdin && patch == Input::Stdin {
+        return Err("'patch' can read only one of its files from standard input".to_string());
+    }
+    if dry_run && write {
+        return Err("'--dry-run' cannot be combined with '--write'".to_string());
+    }
+    if write && document == Input::Stdin {
+        return Err("'--write' cannot be used with standard input".to_string());
+    }
+    if write && global.output.is_some() {
+        return Err("'--write' cannot be combined with '--output'".to_string());
+    }
+    Ok(Command::Patch(PatchArgs { document, patch, dry_run, write, formatting: formatting.finish()?, global }))
+}
+
+fn parse_explore(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let mut inputs = Vec::new();
+    let mut depth = 3;
+    let mut formatting = FormattingOptions::default();
+    let mut global = GlobalOptions::default();
+    let mut only_files = false;
+
+    while let Some(arg) = args.next() {
+        if only_files {
+            inputs.push(Input::File(arg));
+            continue;
+        }
+
+        if global.parse(&arg, args)? || formatting.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(levels) = value("--depth", &arg, args)? {
+            depth = number("--depth", &levels)?;
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(explore_help())),
+            _ => operand(arg, &mut only_files, &mut inputs)?,
+        }
+    }
+
+    let input = match (inputs.pop(), inputs.is_empty()) {
+        (Some(Input::File(path)), true) => Input::File(path),
+        (Some(Input::Stdin), true) => return Err("'explore' reads its commands from standard input, so the document must be a file".to_string()),
+        _ => return Err("'explore' requires exactly one file".to_string()),
+    };
+    Ok(Command::Explore(ExploreArgs { input, depth, formatting: formatting.finish()?, global }))
+}
+
+fn parse_generate(args: &mut dyn Iterator<Item = String>) -> Result<Command, String> {
+    let (mut seed, mut count) = (None, 1);
+    let mut options = GenerateOptions::default();
+    let mut formatting = FormattingOptions::default();
+    let mut global = GlobalOptions::default();
+
+    while let Some(arg) = args.next() {
+        // Taken before the global options, where '--max-depth' limits parsing instead.
+        if let Some(levels) = value("--max-depth", &arg, args)? {
+            options.max_depth = depth(&levels)?;
+            continue;
+        }
+        if global.parse(&arg, args)? || formatting.parse(&arg, args)? {
+            continue;
+        }
+        if let Some(number) = value("--seed", &arg, args)? {
+            seed = Some(number.parse().map_err(|_| format!("invalid value for '--seed': '{}' is not a number", number))?);
+            continue;
+        }
+        if let Some(children) = value("--max-children", &arg, args)? {
+            options.max_children = number("--max-children", &children)?;
+            continue;
+        }
+        if let Some(documents) = value("--count", &arg, args)? {
+            count = number("--count", &documents)?;
+            continue;
+        }
+        if let Some(size) = value("--size", &arg, args)? {
+            options.target_size = Some(limit("--size", &size)?);
+            continue;
+        }
+        if let Some(alphabet) = value("--alphabet", &arg, args)? {
+            options.alphabet = match alphabet.as_str() {
+                "ascii" => Alphabet::Ascii,
+                "unicode" => Alphabet::Unicode,
+                "hostile" => Alphabet::Hostile,
+                _ => return Err(format!("invalid value for '--alphabet': '{}' (expected 'ascii', 'unicode' or 'hostile')", alphabet)),
+            };
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help(generate_help())),
+            flag if flag.starts_with('-') => return Err(format!("unknown option '{}'", flag)),
+            _ => return Err(format!("'generate' reads no files, but was given '{}'", arg)),
+        }
+    }
+
+    Ok(Command::Generate(GenerateArgs { seed, count, options, formatting: formatting.finish()?, global }))
+}
@@ -0,0 +1,187 @@
+use json_parser_rs::{ErrorKind, JsonValue, ParseError};
+
+const DEFAULT_WIDTH: usize = 80;
+const ELLIPSIS: &str = "...";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+/// How diagnostics are printed on standard error.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum ErrorFormat {
+    /// `name:line:column: error: message`, with the source line and a caret.
+    #[default]
+    Human,
+    /// One JSON object per line, as described at `json`.
+    Json,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns: &usize| columns > 2 * ELLIPSIS.len())
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Formats an error or warning as `name:line:column: error: message`, followed by the offending
+/// source line and a caret under the column when the line is available. With `color`, the label
+/// and the caret are highlighted for a terminal.
+pub fn render(severity: Severity, name: &str, error: &ParseError, source_line: Option<&str>, color: bool) -> String {
+    let highlight = match severity {
+        Severity::Error => RED,
+        Severity::Warning => YELLOW,
+    };
+    let severity = severity.label();
+    let (start, end) = if color { (highlight, RESET) } else { ("", "") };
+    if error.kind == ErrorKind::Io {
+        return format!("{}: {}{}:{} {}\n", name, start, severity, end, error.message);
+    }
+
+    let mut output = format!("{}:{}:{}: {}{}:{} {}\n", name, error.line, error.column, start, severity, end, error.message);
+    if let Some(line) = source_line {
+        let (snippet, caret) = snippet(line, error.column, terminal_width());
+        output.push_str(&snippet);
+        output.push('\n');
+        output.push_str(start);
+        output.push_str(&caret);
+        output.push_str(end);
+        output.push('\n');
+    }
+    output
+}
+
+/// Formats a problem found outside the parser, such as a failed schema check, as `name: error: message`,
+/// or `name:line: error: message` when it concerns one line of the input.
+pub fn render_problem(severity: Severity, name: &str, line: Option<usize>, message: &str) -> String {
+    match line {
+        Some(line) => format!("{}:{}: {}: {}\n", name, line, severity.label(), message),
+        None => format!("{}: {}: {}\n", name, severity.label(), message),
+    }
+}
+
+/// Formats a parse error or warning for `--error-format=json`; see `json`.
+pub fn json_error(severity: Severity, name: &str, error: &ParseError) -> String {
+    let start = (error.kind != ErrorKind::Io).then_some((error.line, error.column, error.offset));
+    json(severity, name, code(error.kind), &error.message, start, error.end)
+}
+
+/// Formats a problem found outside the parser for `--error-format=json`; see `json`.
+pub fn json_problem(severity: Severity, name: &str, line: Option<usize>, code: &str, message: &str) -> String {
+    let mut object = fields(severity, name, code, message);
+    if let Some(line) = line {
+        object.push(("line".to_string(), number(line)));
+    }
+    JsonValue::Object(object).to_string() + "\n"
+}
+
+/// Formats a diagnostic as a single line holding a JSON object, with these members in this order:
+///
+/// - `file`: the input's name, as in text diagnostics
+/// - `severity`: `"error"` or `"warning"`
+/// - `code`: what kind of problem it is, e.g. `"unexpected-token"` (see `code`) or, from outside
+///   the parser, `"schema"`, `"mixed-array"`, `"pointer-not-found"`, `"not-flat"` (from
+///   `--unflatten`), `"not-an-array"` (from `--to-ndjson`) or `"patch"`
+/// - `message`: the text a human diagnostic shows
+/// - `line`, `column` and `offset`: where the problem starts, when it has a place in the input;
+///   lines and columns count characters from 1, offsets count bytes from 0
+/// - `end`: an object with the same three members, just past the offending token, when known
+///
+/// This format is relied on by tools: members may be added, but not removed, renamed or reordered.
+fn json(severity: Severity, name: &str, code: &str, message: &str, start: Option<(usize, usize, usize)>, end: Option<(usize, usize, usize)>) -> String {
+    let mut object = fields(severity, name, code, message);
+    if let Some(start) = start {
+        object.extend(position(start));
+    }
+    if let Some(end) = end {
+        object.push(("end".to_string(), JsonValue::Object(position(end))));
+    }
+    JsonValue::Object(object).to_string() + "\n"
+}
+
+fn fields(severity: Severity, name: &str, code: &str, message: &str) -> Vec<(String, JsonValue)> {
+    vec![
+        ("file".to_string(), JsonValue::String(name.to_string())),
+        ("severity".to_string(), JsonValue::String(severity.label().to_string())),
+        ("code".to_string(), JsonValue::String(code.to_string())),
+        ("message".to_string(), JsonValue::String(message.to_string())),
+    ]
+}
+
+fn position((line, column, offset): (usize, usize, usize)) -> Vec<(String, JsonValue)> {
+    vec![("line".to_string(), number(line)), ("column".to_string(), number(column)), ("offset".to_string(), number(offset))]
+}
+
+fn number(value: usize) -> JsonValue {
+    JsonValue::Number(value as f64)
+}
+
+/// The `code` of each kind of parse error in JSON diagnostics.
+pub fn code(kind: ErrorKind) -> &'static str {
+    match kind {
+        ErrorKind::UnexpectedToken => "unexpected-token",
+        ErrorKind::UnexpectedEnd => "unexpected-end",
+        ErrorKind::NumberOutOfRange => "number-out-of-range",
+        ErrorKind::InvalidString => "invalid-string",
+        ErrorKind::InvalidUtf8 => "invalid-utf8",
+        ErrorKind::InvalidEncoding => "invalid-encoding",
+        ErrorKind::TooDeep => "too-deep",
+        ErrorKind::DuplicateKey => "duplicate-key",
+        ErrorKind::InvalidFrame => "invalid-frame",
+        ErrorKind::LimitExceeded => "limit-exceeded",
+        ErrorKind::Io => "io",
+    }
+}
+
+/// A one-line location summary, easier to act on than a snippet in very large inputs.
+pub fn summary(error: &ParseError) -> String {
+    format!("error at line {}, column {}, byte {}\n", error.line, error.column, error.offset)
+}
+
+/// Returns the source line (cut down to a window around the column if it is wider than `width`)
+/// and a matching caret line. Tabs before the column are copied into the caret line so the caret
+/// stays aligned whatever the tab width.
+fn snippet(line: &str, column: usize, width: usize) -> (String, String) {
+    let chars: Vec<char> = line.trim_end_matches(['\n', '\r']).chars().collect();
+    let index = column.saturating_sub(1).min(chars.len());
+
+    let (start, end) = if chars.len() <= width {
+        (0, chars.len())
+    } else {
+        let start = index.saturating_sub(width / 2).min(chars.len() - width);
+        (start, start + width)
+    };
+
+    let mut snippet = String::new();
+    let mut caret = String::new();
+    if start > 0 {
+        snippet.push_str(ELLIPSIS);
+        caret.push_str(&" ".repeat(ELLIPSIS.len()));
+    }
+    snippet.extend(&chars[start..end]);
+    if end < chars.len() {
+        snippet.push_str(ELLIPSIS);
+    }
+
+    for c in &chars[start..index] {
+        caret.push(if *c == '\t' { '\t' } else { ' ' });
+    }
+    caret.push('^');
+
+    (snippet, caret)
+}
@@ -0,0 +1,110 @@
+use std::io::{stdout, BufRead, Write};
+
+use json_parser_rs::{escape_token, JsonValue, SerializeOptions};
+
+use crate::cli::output::Output;
+use crate::cli::preview;
+
+pub const COMMANDS: &str = "Commands:
+  get [POINTER]     Print the value at a JSON Pointer, the root by default
+  keys [POINTER]    Print the keys of an object, one per line
+  type [POINTER]    Print the type of a value
+  len [POINTER]     Print the number of members, elements or characters
+  find <TEXT>       Print the pointer of every value whose key or string
+                    contains TEXT, ignoring case
+  help              Print this list
+  quit              Leave
+";
+
+/// Answers commands read from `commands`, one per line, about `document` until `quit` or the end
+/// of the input. Values are printed down to `depth` levels, with the containers below summarized.
+/// Mistakes are reported and the session goes on.
+pub fn explore(document: &JsonValue, commands: &mut dyn BufRead, prompt: bool, depth: usize, options: &SerializeOptions, output: &Output) {
+    let mut line = String::new();
+    loop {
+        if prompt {
+            print!("> ");
+            let _ = stdout().flush();
+        }
+        line.clear();
+        if commands.read_line(&mut line).unwrap_or(0) == 0 { break; }
+
+        let line = line.trim();
+        let (command, argument) = line.split_once(char::is_whitespace).map_or((line, ""), |(command, argument)| (command, argument.trim()));
+        let result = match command {
+            "" => Ok(String::new()),
+            "quit" | "exit" => break,
+            "help" => Ok(COMMANDS.to_string()),
+            "get" => lookup(document, argument).map(|value| preview::render(&preview::from_value(value, depth), options) + "\n"),
+            "keys" => lookup(document, argument).and_then(|value| keys(argument, value)),
+            "type" => lookup(document, argument).map(|value| format!("{}\n", value.type_name())),
+            "len" => lookup(document, argument).and_then(|value| length(argument, value)),
+            "find" if !argument.is_empty() => Ok(find(document, &argument.to_lowercase())),
+            "find" => Err("'find' requires the text to look for".to_string()),
+            _ => Err(format!("unknown command '{}'\n{}", command, COMMANDS.trim_end())),
+        };
+        match result {
+            Ok(text) => output.result(&text),
+            Err(message) => output.error(&format!("error: {}\n", message)),
+        }
+    }
+}
+
+fn lookup<'a>(document: &'a JsonValue, pointer: &str) -> Result<&'a JsonValue, String> {
+    document.pointer(pointer).ok_or_else(|| format!("pointer not found: '{}'", pointer))
+}
+
+fn keys(pointer: &str, value: &JsonValue) -> Result<String, String> {
+    match value {
+        JsonValue::Object(members) => Ok(members.iter().map(|(key, _)| format!("{}\n", key)).collect()),
+        _ => Err(format!("value at '{}' is not an object but {}", pointer, value.type_name())),
+    }
+}
+
+fn length(pointer: &str, value: &JsonValue) -> Result<String, String> {
+    match value {
+        JsonValue::Object(members) => Ok(format!("{}\n", members.len())),
+        JsonValue::Array(elements) => Ok(format!("{}\n", elements.len())),
+        JsonValue::String(string) => Ok(format!("{}\n", string.chars().count())),
+        _ => Err(format!("value at '{}' is {}, which has no length", pointer, value)),
+    }
+}
+
+/// Lists the pointers of the values under keys containing `text`, and of the strings containing
+/// it, in document order; `text` is already lowercase.
+fn find(document: &JsonValue, text: &str) -> String {
+    let mut found = String::new();
+    search(document, text, &mut String::new(), &mut found);
+    if found.is_empty() { found.push_str("nothing found\n"); }
+    found
+}
+
+fn search(value: &JsonValue, text: &str, pointer: &mut String, found: &mut String) {
+    let length = pointer.len();
+    match value {
+        JsonValue::Object(members) => {
+            for (key, member) in members {
+                pointer.push('/');
+                pointer.push_str(&escape_token(key));
+                if key.to_lowercase().contains(text) || matches!(member, JsonValue::String(string) if string.to_lowercase().contains(text)) {
+                    found.push_str(pointer);
+                    found.push('\n');
+                }
+                search(member, text, pointer, found);
+                pointer.truncate(length);
+            }
+        }
+        JsonValue::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                pointer.push_str(&format!("/{}", index));
+                if matches!(element, JsonValue::String(string) if string.to_lowercase().contains(text)) {
+                    found.push_str(pointer);
+                    found.push('\n');
+                }
+                search(element, text, pointer, found);
+                pointer.truncate(length);
+            }
+        }
+        _ => {}
+    }
+}
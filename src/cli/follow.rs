@@ -0,0 +1,89 @@
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+/// How long a follower waits before looking for more of its file.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" {
+    fn signal(signum: std::os::raw::c_int, handler: extern "C" fn(std::os::raw::c_int)) -> usize;
+}
+
+#[cfg(unix)]
+extern "C" fn interrupt(_: std::os::raw::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Makes Ctrl-C end following, as the end of the file would, or watching, instead of killing the
+/// process, so what was seen so far decides the exit status. Elsewhere than Unix, Ctrl-C still
+/// kills it.
+pub fn catch_interrupt() {
+    #[cfg(unix)]
+    unsafe {
+        const SIGINT: std::os::raw::c_int = 2;
+        signal(SIGINT, interrupt);
+    }
+}
+
+/// Whether Ctrl-C has been caught since `catch_interrupt`.
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Reads a file as `tail -f` does: at its end, waits for more to be written instead of stopping.
+/// When the file shrinks, or another file takes its name as when logs are rotated, the new
+/// contents are read from the start. Reads stop for good, as at the end of the file, once Ctrl-C
+/// has been caught.
+pub struct Follower {
+    path: PathBuf,
+    file: File,
+    position: u64,
+}
+
+impl Follower {
+    pub fn open(path: &str) -> io::Result<Follower> {
+        Ok(Follower { path: PathBuf::from(path), file: File::open(path)?, position: 0 })
+    }
+
+    /// Whether the file now at the path is not the one being read, or is shorter than what has
+    /// been read of it. A missing file is waited for, as a rotated log is recreated.
+    fn replaced(&self) -> io::Result<bool> {
+        let current = match fs::metadata(&self.path) {
+            Ok(current) => current,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(error) => return Err(error),
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let open = self.file.metadata()?;
+            if (current.dev(), current.ino()) != (open.dev(), open.ino()) { return Ok(true); }
+        }
+        Ok(current.len() < self.position)
+    }
+}
+
+impl Read for Follower {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if interrupted() { return Ok(0); }
+            let read = self.file.read(buffer)?;
+            if read > 0 {
+                self.position += read as u64;
+                return Ok(read);
+            }
+            if self.replaced()? {
+                self.file = File::open(&self.path)?;
+                self.position = 0;
+                continue;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,642 @@
+use json_parser_rs::DEFAULT_MAX_DEPTH;
+
+use super::args::MAX_DEPTH_LIMIT;
+use super::explore::COMMANDS;
+
+pub const USAGE: &str = "Usage: json-parser-rs [COMMAND] [OPTIONS] [FILE]...";
+
+/// Options every command accepts, listed at the end of each command's help.
+fn global_options() -> String {
+    format!("\
+Global options:
+  Accepted by every command, after its name or before it, as in
+  'json-parser-rs --lenient format FILE'.
+      --lenient                Accept input strict JSON rejects where the
+                               meaning is clear: numbers too large for a
+                               double, unpaired surrogates in escapes and
+                               byte order marks between tokens, plus
+                               everything the five --allow-* options below
+                               accept. A --no-* option turns one of those
+                               back off, e.g. --lenient --no-comments
+      --strict                 Accept only JSON as RFC 8259 defines it, which
+                               is the default; refuses to be combined with
+                               --lenient or any --allow-* option, so scripts
+                               can rely on it whatever the defaults become
+      --allow-comments         Accept // and /* */ comments, as in JSONC
+      --allow-trailing-commas  Accept a comma after the last member of an
+                               object or element of an array
+      --allow-single-quotes    Accept strings in single quotes, as in JSON5
+      --allow-unquoted-keys    Accept object keys without quotes that are
+                               identifiers, such as max_size or $id
+      --allow-nan              Accept NaN, Infinity and -Infinity as numbers;
+                               they are written back as null
+      --no-comments, --no-trailing-commas, --no-single-quotes,
+      --no-unquoted-keys, --no-nan
+                               Reject what the matching --allow-* option
+                               accepts, even under --lenient
+      --allow-trailing-data    Ignore anything after the first complete value
+      --duplicate-keys <POLICY>
+                               What to do about a key repeated within one
+                               object: 'allow', 'warn' or 'error'
+                               [default: allow]
+      --normalize-keys         Compose object keys to Unicode NFC as they are
+                               read, so a key written with a combining accent
+                               is the same key as one written precomposed;
+                               needs the 'unicode-normalization' feature
+      --invalid-utf8 <POLICY>  What to do about bytes that are not UTF-8, such
+                               as CESU-8 surrogates: 'error', or 'replace'
+                               each bad sequence with U+FFFD [default: error]
+      --leading-zeros <POLICY> What to do about numbers such as 0755, which
+                               JSON does not allow: 'error', or read them as
+                               'decimal' or 'octal'. Nothing in the input says
+                               which was meant, and guessing wrong changes the
+                               value without warning [default: error]
+      --encoding <ENCODING>    Decode inputs from 'utf-8', 'utf-16le' or
+                               'utf-16be' before parsing them; an input that
+                               looks like UTF-16 without this is reported as
+                               such [default: utf-8]
+      --max-depth <N>          Reject documents nesting objects and arrays
+                               more than N deep, N being at most {limit}
+                               [default: {max_depth}]
+      --max-bytes <SIZE>       Stop reading an input once it is longer than
+                               SIZE bytes; K, M and G multiply by 1024,
+                               1024² and 1024³. With --ndjson this covers all
+                               the records together
+      --max-tokens <N>         Stop reading a document once it has more than N
+                               tokens, comments included; K, M and G are
+                               accepted as for --max-bytes. A document on
+                               standard input is read whole before it is
+                               parsed, so bound it with --max-bytes as well
+      --max-key-length <N>     Reject an object key longer than N bytes as
+                               written, escapes included
+      --max-value-bytes <SIZE> Reject a string value longer than SIZE bytes as
+                               written, escapes included; K, M and G are
+                               accepted as for --max-bytes
+      --replace-invalid        Read unrecognizable values, such as a stray
+                               bareword, as null with a warning
+      --error-format <FORMAT>  Print diagnostics as 'human' text, or as 'json':
+                               one object per line with the members file,
+                               severity, code and message, then line, column,
+                               offset and end where known; nothing else is
+                               then printed on standard error [default: human]
+      --color <WHEN>           Highlight documents and diagnostics: 'auto'
+                               (when printing to a terminal and NO_COLOR is
+                               not set), 'always' or 'never' [default: auto]
+  -o, --output <FILE>          Write results to FILE instead of standard
+                               output, replacing it atomically; '-' is
+                               standard output
+      --force                  Let --output overwrite a file being read
+  -q, --quiet                  Print nothing but diagnostics
+  -v, --verbose                Also print the options in effect and the size
+                               and validation time of each input
+  -h, --help                   Print this help and exit
+", max_depth = DEFAULT_MAX_DEPTH, limit = MAX_DEPTH_LIMIT)
+}
+
+const FORMATTING_OPTIONS: &str = "      --pretty                 Print each document indented (the default)
+      --compact                Print each document on a single line
+      --indent <N>             Indent pretty output by N spaces per level; 0
+                               keeps the line breaks but not the indentation
+                               [default: 2]
+      --tab                    Indent pretty output with tabs
+      --canonical              Print each document in the canonical form of
+                               RFC 8785 (JCS): sorted keys, no whitespace and
+                               fixed number and string formatting
+      --escape-unicode         Write non-ASCII characters in printed documents
+                               as \\uXXXX escapes
+      --sort-keys              Print object keys in sorted order at every
+                               level; array elements keep their order
+      --collapse-whitespace    Print each run of whitespace in string values
+                               as one space; keys are left alone
+";
+
+const DEPTH_OPTIONS: &str = "      --depth <N>              Print each document only N levels deep, showing
+                               deeper objects and arrays as {…12 keys…} or
+                               […3 items…]. The output is not valid JSON
+      --sample <N>             Print only the first N members of each object
+                               and elements of each array, then a line such as
+                               '… 1,024 more items'. The output is not valid
+                               JSON
+      --max-string-len <N>     With --depth or --sample, cut strings longer
+                               than N characters short
+";
+
+const FILES: &str = "  [FILE]...                    Files to read. '-' reads standard input, which
+                               is also the default when no files are given
+";
+
+pub fn help() -> String {
+    format!("\
+json-parser-rs {version}
+Validate, query and edit JSON documents.
+
+{usage}
+
+Commands:
+  validate       Check that documents are valid JSON (the default)
+  format         Print documents reformatted
+  get            Print the value at a JSON Pointer
+  set            Assign a value at a JSON Pointer
+  stats          Count the values in documents
+  diff           Compare two documents
+  merge          Combine documents as JSON Merge Patches
+  patch          Apply a JSON Patch to a document
+  explore        Answer questions about a document typed one per line
+  generate       Print random documents, for tests and load testing
+
+Run 'json-parser-rs <COMMAND> --help' for the options of a command. Without a
+command, documents are validated and every option below is accepted. Write
+'./diff' or '-- diff' to validate a file named like a command.
+
+Arguments:
+{files}
+Options:
+      --stdin-filename <NAME>  Name used for standard input in messages, which
+                               is then the only input [default: <stdin>]
+      --files-from <LIST>      Also read each file named in LIST, one per
+                               line; '-' reads the list from standard input
+  -0, --null                   Separate the names in --files-from with NUL
+                               bytes instead, as 'find -print0' writes them
+      --count-lines            Follow each error with its line, column and
+                               byte offset in the input
+      --get <POINTER>          Print the value at a JSON Pointer (RFC 6901)
+                               instead of a validation result; repeatable
+      --type <POINTER>         Print the type of the value at a pointer:
+                               object, array, string, number, boolean or null
+      --length <POINTER>       Print the number of members, elements or
+                               characters of the object, array or string at
+                               a pointer
+      --select <NAME=POINTER>  Print an object with a member NAME holding the
+                               value at POINTER instead of a validation
+                               result; repeatable
+      --missing <POLICY>       What --select does when a pointer does not
+                               resolve: 'error' or 'null' [default: error]
+      --json                   Print strings from --get as JSON rather than
+                               raw text, and --keys and --paths as an array
+      --keys[=<POINTER>]       Print the keys of the object at a pointer, the
+                               root by default, one per line instead of a
+                               validation result
+      --paths                  Print the JSON Pointer of every value below
+                               the root, one per line. Lines --keys and
+                               --paths print are written as JSON strings
+                               when they hold line breaks or start with '\"'
+      --leaves                 Only print the paths --paths finds to scalars
+                               and empty objects and arrays
+      --diff <OLD> <NEW>       Compare two documents instead of validating
+                               them; the same as 'json-parser-rs diff'
+      --set <POINTER> <VALUE>  Assign a JSON value at a pointer and print the
+                               modified document; repeatable, applied in order
+      --delete <POINTER>       Remove the value at a pointer and print the
+                               modified document; repeatable. Edits apply in
+                               order, so after deleting /list/0 the element
+                               that was /list/1 is addressed as /list/0
+      --flatten                Print the document as one object mapping the
+                               path of each scalar, empty object and empty
+                               array to it, e.g. {{\"a.b[0]\": 1}}
+      --unflatten              Rebuild a document from the object --flatten
+                               prints
+      --separator <SEP>        What joins member names in the paths of
+                               --flatten and --unflatten [default: .]
+      --preserve-formatting    Make --set change only the text of the values
+                               it replaces, keeping the whitespace and
+                               comments around them; the values must exist
+      --create-missing         Let --set create missing parent objects
+      --ignore-missing         Let --delete skip pointers that do not resolve
+      --write                  Write modified or reformatted documents back
+                               to their files, replacing each atomically;
+                               files that would not change are left alone
+      --check                  Only list the documents that printing would
+                               change, exiting 1 if there are any
+{formatting}{depth}      --ndjson                 Treat each line of the input as a separate
+                               document, skipping blank lines. Output is one
+                               compact document per line
+      --to-ndjson              Print each element of the root array as one
+                               compact line, reading one element at a time
+      --from-ndjson            Read the input as --ndjson does and print its
+                               records as the elements of one array, laid out
+                               as --pretty, --compact or --indent say
+      --follow                 With --ndjson, keep checking records as they
+                               are appended to the files, as 'tail -f' shows
+                               them, until Ctrl-C; a truncated or replaced file
+                               is read again from its start. Files are followed
+                               at once, whatever --jobs says, and reported on
+                               as records arrive
+      --schema <FILE>          Also check each document against a JSON Schema
+                               (type, required, properties, items, enum,
+                               minimum, maximum, minLength and maxLength)
+      --validate-utf8-only     Only check that each input is UTF-8 and well
+                               formed JSON, for speed: string escapes are
+                               checked to be well formed but not decoded, so
+                               unpaired surrogates are accepted, and numbers
+                               are not checked to be in range
+      --progress               While reading a large input, show the bytes
+                               read, how fast and, for files, how much is
+                               left; only when stderr is a terminal
+      --stats                  Print counts of each kind of value, the maximum
+                               depth, the longest array, the number of distinct
+                               keys and the total size of string values
+      --stats-format <FORMAT>  Print --stats as 'text' or 'json'
+                               [default: text]
+  -r, --recursive              Read directories for the files beneath them
+                               with a matching extension, skipping binary files
+      --ext <LIST>             Extensions --recursive reads, separated by
+                               commas; '.ndjson' files are read as --ndjson
+                               [default: json]
+      --hidden                 Let --recursive read files and directories
+                               whose names start with a dot
+  -j, --jobs <N>               Check N inputs at once; reports are still
+                               printed in input order [default: the number
+                               of cores]
+      --fail-fast              Start no more inputs once one has failed;
+                               those already being checked with --jobs are
+                               still finished and reported
+      --keep-going             Check every input whatever fails, which is the
+                               default; undoes an earlier --fail-fast
+      --summary <FORMAT>       Once every input is checked, print a tally of
+                               the outcomes on stderr as 'text', which is done
+                               anyway after more than one input unless --quiet,
+                               or as 'json', adding the time each input took
+                               with --verbose; 'none' prints nothing
+      --watch                  Check the files, and those beneath directories
+                               as --recursive finds them, again whenever they
+                               change, each after a line with the time (UTC)
+                               and what happened, until Ctrl-C. The exit
+                               status is that of the last check of each file
+      --homogeneous-arrays     Warn about arrays mixing element types
+      --lint                   Also warn about the layout of each input:
+                               indentation mixing tabs and spaces, trailing
+                               whitespace and a missing final newline
+  -V, --version                Print the version and exit
+
+{global}
+Exit status:
+  0  every input is valid JSON
+  1  at least one input is not valid JSON, or does not match the schema, or
+     --unflatten could not rebuild it, or --to-ndjson found no root array
+  2  the command line could not be understood
+  3  at least one input could not be opened or read
+  4  a --get, --type, --length, --select, --set, --delete or --keys pointer
+     did not resolve, or --keys found something other than an object
+  5  at least one input is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+  6  a --length pointer resolved to a number, boolean or null, which have no
+     length
+", version = env!("CARGO_PKG_VERSION"), usage = USAGE, files = FILES, formatting = FORMATTING_OPTIONS, depth = DEPTH_OPTIONS, global = global_options())
+}
+
+pub fn validate_help() -> String {
+    format!("\
+Check that documents are valid JSON, and optionally that they match a schema.
+
+Usage: json-parser-rs validate [OPTIONS] [FILE]...
+
+Arguments:
+{files}
+Options:
+      --stdin-filename <NAME>  Name used for standard input in messages, which
+                               is then the only input [default: <stdin>]
+      --files-from <LIST>      Also read each file named in LIST, one per
+                               line; '-' reads the list from standard input
+  -0, --null                   Separate the names in --files-from with NUL
+                               bytes instead, as 'find -print0' writes them
+      --count-lines            Follow each error with its line, column and
+                               byte offset in the input
+      --ndjson                 Treat each line of the input as a separate
+                               document, skipping blank lines
+      --follow                 With --ndjson, keep checking records as they
+                               are appended to the files, as 'tail -f' shows
+                               them, until Ctrl-C; a truncated or replaced file
+                               is read again from its start. Files are followed
+                               at once, whatever --jobs says, and reported on
+                               as records arrive
+      --schema <FILE>          Also check each document against a JSON Schema
+                               (type, required, properties, items, enum,
+                               minimum, maximum, minLength and maxLength)
+  -r, --recursive              Read directories for the files beneath them
+                               with a matching extension, skipping binary files
+      --ext <LIST>             Extensions --recursive reads, separated by
+                               commas; '.ndjson' files are read as --ndjson
+                               [default: json]
+      --hidden                 Let --recursive read files and directories
+                               whose names start with a dot
+  -j, --jobs <N>               Check N inputs at once; reports are still
+                               printed in input order [default: the number
+                               of cores]
+      --fail-fast              Start no more inputs once one has failed;
+                               those already being checked with --jobs are
+                               still finished and reported
+      --keep-going             Check every input whatever fails, which is the
+                               default; undoes an earlier --fail-fast
+      --summary <FORMAT>       Once every input is checked, print a tally of
+                               the outcomes on stderr as 'text', which is done
+                               anyway after more than one input unless --quiet,
+                               or as 'json', adding the time each input took
+                               with --verbose; 'none' prints nothing
+      --watch                  Check the files, and those beneath directories
+                               as --recursive finds them, again whenever they
+                               change, each after a line with the time (UTC)
+                               and what happened, until Ctrl-C. The exit
+                               status is that of the last check of each file
+      --homogeneous-arrays     Warn about arrays mixing element types
+      --lint                   Also warn about the layout of each input:
+                               indentation mixing tabs and spaces, trailing
+                               whitespace and a missing final newline
+      --validate-utf8-only     Only check that each input is UTF-8 and well
+                               formed JSON, for speed: string escapes are
+                               checked to be well formed but not decoded, so
+                               unpaired surrogates are accepted, and numbers
+                               are not checked to be in range
+      --progress               While reading a large input, show the bytes
+                               read, how fast and, for files, how much is
+                               left; only when stderr is a terminal
+
+{global}
+Exit status:
+  0  every input is valid JSON
+  1  at least one input is not valid JSON, or does not match the schema
+  2  the command line could not be understood
+  3  at least one input could not be opened or read
+  5  at least one input is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", files = FILES, global = global_options())
+}
+
+pub fn format_help() -> String {
+    format!("\
+Print documents reformatted, or write them back to their files.
+
+Usage: json-parser-rs format [OPTIONS] [FILE]...
+
+Arguments:
+{files}
+Options:
+{formatting}{depth}      --ndjson                 Treat each line of the input as a separate
+                               document, printed as one compact line
+      --write                  Write each document back to its file,
+                               replacing it atomically; files already
+                               formatted are left alone
+      --files-from <LIST>      Also read each file named in LIST, one per
+                               line; '-' reads the list from standard input
+  -0, --null                   Separate the names in --files-from with NUL
+                               bytes instead, as 'find -print0' writes them
+      --check                  Change nothing; list the documents that are not
+                               already formatted, exiting 1 if there are any
+      --diff                   With --check, also show how each would change,
+                               as a unified diff
+      --progress               While reading a large input, show the bytes
+                               read, how fast and, for files, how much is
+                               left; only when stderr is a terminal
+      --summary <FORMAT>       Once every input is checked, print a tally of
+                               the outcomes on stderr as 'text', which is done
+                               anyway after more than one input unless --quiet,
+                               or as 'json', adding the time each input took
+                               with --verbose; 'none' prints nothing
+      --stdin-filename <NAME>  Name used for standard input in messages, which
+                               is then the only input [default: <stdin>]
+
+{global}
+Exit status:
+  0  every input was formatted, or with --check is already formatted
+  1  at least one input is not valid JSON, or with --check is not formatted
+  2  the command line could not be understood
+  3  at least one input could not be opened, read or written
+  5  at least one input is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", files = FILES, formatting = FORMATTING_OPTIONS, depth = DEPTH_OPTIONS, global = global_options())
+}
+
+pub fn get_help() -> String {
+    format!("\
+Print the value at a JSON Pointer (RFC 6901) in each document.
+
+Usage: json-parser-rs get [OPTIONS] <POINTER> [FILE]...
+
+Arguments:
+  <POINTER>                    Where the value is, e.g. /users/0/name
+{files}
+Options:
+      --json                   Print strings as JSON rather than raw text
+      --ndjson                 Treat each line of the input as a separate
+                               document
+      --stdin-filename <NAME>  Name used for standard input in messages, which
+                               is then the only input [default: <stdin>]
+
+{global}
+Exit status:
+  0  the value was printed from every input
+  1  at least one input is not valid JSON
+  2  the command line could not be understood
+  3  at least one input could not be opened or read
+  4  the pointer did not resolve in at least one input
+  5  at least one input is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", files = FILES, global = global_options())
+}
+
+pub fn set_help() -> String {
+    format!("\
+Assign a JSON value at a JSON Pointer (RFC 6901) in each document, and print
+the modified documents or write them back to their files.
+
+Usage: json-parser-rs set [OPTIONS] <POINTER> <VALUE> [FILE]...
+
+Arguments:
+  <POINTER>                    Where to assign, e.g. /server/port
+  <VALUE>                      The JSON value to assign, e.g. 8080 or '\"a\"'
+{files}
+Options:
+      --create-missing         Create missing parent objects
+      --write                  Write each document back to its file
+{formatting}      --ndjson                 Treat each line of the input as a separate
+                               document, printed as one compact line
+      --stdin-filename <NAME>  Name used for standard input in messages, which
+                               is then the only input [default: <stdin>]
+
+{global}
+Exit status:
+  0  the value was assigned in every input
+  1  at least one input is not valid JSON
+  2  the command line could not be understood
+  3  at least one input could not be opened, read or written
+  4  the pointer's parent did not resolve in at least one input
+  5  at least one input is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", files = FILES, formatting = FORMATTING_OPTIONS, global = global_options())
+}
+
+pub fn stats_help() -> String {
+    format!("\
+Print counts of each kind of value, the maximum depth, the longest array, the
+number of distinct keys and the total size of string values in each document.
+
+Usage: json-parser-rs stats [OPTIONS] [FILE]...
+
+Arguments:
+{files}
+Options:
+      --format <FORMAT>        Print the counts as 'text' or 'json'
+                               [default: text]
+      --stdin-filename <NAME>  Name used for standard input in messages, which
+                               is then the only input [default: <stdin>]
+
+{global}
+Exit status:
+  0  every input is valid JSON
+  1  at least one input is not valid JSON
+  2  the command line could not be understood
+  3  at least one input could not be opened or read
+  5  at least one input is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", files = FILES, global = global_options())
+}
+
+pub fn diff_help() -> String {
+    format!("\
+Compare two JSON documents structurally and list their differences.
+
+Usage: json-parser-rs diff [OPTIONS] <OLD> <NEW>
+
+Arguments:
+  <OLD>                        The original document; '-' reads standard input
+  <NEW>                        The document to compare it with; '-' reads
+                               standard input
+
+Objects are compared regardless of the order of their members, and arrays
+element by element.
+
+Options:
+      --format <FORMAT>        Print differences as 'text', one line per
+                               change with its JSON Pointer and values, or as
+                               an RFC 6902 JSON Patch with 'patch'
+                               [default: text]
+
+{global}
+Exit status:
+  0  the documents are equal
+  1  the documents differ, or one is not valid JSON
+  2  the command line could not be understood
+  3  a document could not be opened or read
+  5  a document is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", global = global_options())
+}
+
+pub fn merge_help() -> String {
+    format!("\
+Merge overlay documents onto a base document, in order, and print the result.
+
+Usage: json-parser-rs merge [OPTIONS] <BASE> <OVERLAY>...
+
+Arguments:
+  <BASE>                       The document to start from; '-' reads standard
+                               input
+  <OVERLAY>...                 Documents merged onto it as RFC 7386 JSON Merge
+                               Patches
+
+Objects are merged member by member at every level; other values in an
+overlay, arrays included unless --arrays says otherwise, replace what was
+there. A null member in an overlay removes the member of the same name.
+
+Options:
+      --arrays <STRATEGY>      How an array in an overlay combines with one
+                               already there: replace, concat (append its
+                               elements) or union (append those not already
+                               there) [default: replace]
+      --no-null-deletes        Set members to null instead of removing them
+{formatting}
+{global}
+Exit status:
+  0  the documents were merged
+  1  a document is not valid JSON
+  2  the command line could not be understood
+  3  a document could not be opened or read
+  5  a document is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", formatting = FORMATTING_OPTIONS, global = global_options())
+}
+
+pub fn patch_help() -> String {
+    format!("\
+Apply an RFC 6902 JSON Patch to a document and print the result.
+
+Usage: json-parser-rs patch [OPTIONS] <FILE> <PATCH>
+
+Arguments:
+  <FILE>                       The document to patch; '-' reads standard input
+  <PATCH>                      An array of add, remove, replace, move, copy
+                               and test operations; '-' reads standard input
+
+Every operation must apply for the document to change: if one fails, nothing
+is printed or written, and the error names the operation and the reason.
+
+Options:
+      --dry-run                Only check that the patch applies; print nothing
+      --write                  Write the patched document back to <FILE>
+{formatting}
+{global}
+Exit status:
+  0  the patch was applied
+  1  a document is not valid JSON, or the patch does not apply
+  2  the command line could not be understood
+  3  a document could not be opened, read or written
+  5  a document is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", formatting = FORMATTING_OPTIONS, global = global_options())
+}
+
+pub fn explore_help() -> String {
+    format!("\
+Load a document and answer commands about it, read one per line from standard
+input, until 'quit' or the end of the input.
+
+Usage: json-parser-rs explore [OPTIONS] <FILE>
+
+Arguments:
+  <FILE>                       The document to explore
+
+{commands}
+Options:
+      --depth <N>              Levels of each value 'get' prints before the
+                               containers below are summarized [default: 3]
+{formatting}
+{global}
+Exit status:
+  0  the session ended
+  1  the document is not valid JSON
+  2  the command line could not be understood
+  3  the document could not be opened or read
+  5  the document is over a --max-bytes, --max-tokens, --max-key-length
+     or --max-value-bytes limit
+", commands = COMMANDS, formatting = FORMATTING_OPTIONS, global = global_options())
+}
+
+pub fn generate_help() -> String {
+    format!("\
+Print random documents, one after another. The same seed and options always
+give the same documents, so they can be used as fixtures.
+
+Usage: json-parser-rs generate [OPTIONS]
+
+Options:
+      --seed <N>               Where the random sequence starts [default: taken
+                               from the clock, and printed with --verbose]
+      --count <N>              Documents to print [default: 1]
+      --max-depth <N>          Nest objects and arrays at most N deep, N being
+                               at most {limit} [default: 5]
+      --max-children <N>       Give each object or array at most N members or
+                               elements [default: 8]
+      --alphabet <SET>         Characters of strings and keys: 'ascii',
+                               'unicode' (several scripts and emoji) or
+                               'hostile' (quotes, backslashes, control
+                               characters, separators and other troublemakers)
+                               [default: ascii]
+      --size <SIZE>            Make each document an array roughly SIZE bytes
+                               long when compact; K, M and G multiply by 1024,
+                               1024² and 1024³
+{formatting}
+{global}
+Exit status:
+  0  the documents were printed
+  2  the command line could not be understood
+", formatting = FORMATTING_OPTIONS, global = global_options(), limit = MAX_DEPTH_LIMIT)
+}
@@ -0,0 +1,13 @@
+pub mod args;
+pub mod diagnostic;
+pub mod explore;
+pub mod follow;
+pub mod help;
+pub mod output;
+pub mod preview;
+pub mod progress;
+pub mod recent;
+pub mod unified;
+pub mod walk;
+pub mod watch;
+pub mod write;
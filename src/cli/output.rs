@@ -0,0 +1,147 @@
+use std::io::{stderr, stdout, Write};
+use std::sync::{Mutex, PoisonError};
+
+use json_parser_rs::ParseError;
+
+use super::diagnostic::{self, ErrorFormat, Severity};
+use super::progress;
+use super::write::Pending;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Default)]
+pub enum Level {
+    Quiet,
+    #[default]
+    Normal,
+    Verbose,
+}
+
+/// Something printed by a recording `Output`, held back along with the stream it is meant for.
+pub enum Message {
+    Result(String),
+    Error(String),
+    Verbose(String),
+}
+
+/// Routes everything the binary prints, so that every command respects --quiet and --verbose
+/// the same way. Writes go through write_all rather than print! so a closed pipe is not a panic.
+pub struct Output {
+    level: Level,
+    color_results: bool,
+    color_errors: bool,
+    error_format: ErrorFormat,
+    file: Option<Mutex<Pending>>,
+    recorded: Option<Mutex<Vec<Message>>>,
+}
+
+impl Output {
+    pub fn new(level: Level) -> Output {
+        Output { level, color_results: false, color_errors: false, error_format: ErrorFormat::Human, file: None, recorded: None }
+    }
+
+    /// Enables syntax highlighting of results and diagnostics, chosen separately for each stream.
+    pub fn with_color(mut self, results: bool, errors: bool) -> Output {
+        self.color_results = results;
+        self.color_errors = errors;
+        self
+    }
+
+    /// With `ErrorFormat::Json`, diagnostics are the only thing written to stderr.
+    pub fn with_error_format(mut self, error_format: ErrorFormat) -> Output {
+        self.error_format = error_format;
+        self
+    }
+
+    /// Writes results to `file` instead of printing them, for --output. They are written even
+    /// with --quiet, which only quietens the terminal.
+    pub fn writing_to(mut self, file: Pending) -> Output {
+        self.file = Some(Mutex::new(file));
+        self
+    }
+
+    pub fn into_file(self) -> Option<Pending> {
+        self.file.map(|file| file.into_inner().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// An output with the same settings that holds everything back, to `replay` later, so that
+    /// inputs checked concurrently can still be reported one after another.
+    pub fn recording(&self) -> Output {
+        Output { recorded: Some(Mutex::new(Vec::new())), file: None, ..*self }
+    }
+
+    pub fn into_recorded(self) -> Vec<Message> {
+        self.recorded.map(|recorded| recorded.into_inner().unwrap_or_else(PoisonError::into_inner)).unwrap_or_default()
+    }
+
+    /// Prints messages held back by a recording output, each written whole.
+    pub fn replay(&self, messages: Vec<Message>) {
+        for message in messages {
+            match message {
+                Message::Result(text) => self.result(&text),
+                Message::Error(text) => self.error(&text),
+                Message::Verbose(text) => self.verbose(&text),
+            }
+        }
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.level == Level::Quiet
+    }
+
+    pub fn errors_as_json(&self) -> bool {
+        self.error_format == ErrorFormat::Json
+    }
+
+    /// Reports a parse error or warning, shown with `source_line` when it is available.
+    pub fn diagnostic(&self, severity: Severity, name: &str, error: &ParseError, source_line: Option<&str>) {
+        match self.error_format {
+            ErrorFormat::Human => self.error(&diagnostic::render(severity, name, error, source_line, self.color_errors)),
+            ErrorFormat::Json => self.error(&diagnostic::json_error(severity, name, error)),
+        }
+    }
+
+    /// Reports a problem found outside the parser, optionally on one line of the input.
+    pub fn problem(&self, severity: Severity, name: &str, line: Option<usize>, code: &str, message: &str) {
+        match self.error_format {
+            ErrorFormat::Human => self.error(&diagnostic::render_problem(severity, name, line, message)),
+            ErrorFormat::Json => self.error(&diagnostic::json_problem(severity, name, line, code, message)),
+        }
+    }
+
+    /// Holds a message back when recording, returning whether it was.
+    fn record(&self, message: impl FnOnce() -> Message) -> bool {
+        let Some(recorded) = &self.recorded else { return false };
+        recorded.lock().unwrap_or_else(PoisonError::into_inner).push(message());
+        true
+    }
+
+    pub fn colors_results(&self) -> bool {
+        self.color_results
+    }
+
+    /// Regular results on stdout, suppressed by --quiet, or written to the --output file.
+    pub fn result(&self, text: &str) {
+        if self.record(|| Message::Result(text.to_string())) { return; }
+        if let Some(file) = &self.file {
+            file.lock().unwrap_or_else(PoisonError::into_inner).write(text.as_bytes());
+        } else if self.level >= Level::Normal {
+            progress::clear();
+            let _ = stdout().write_all(text.as_bytes());
+        }
+    }
+
+    /// Diagnostics on stderr, always shown.
+    pub fn error(&self, text: &str) {
+        if self.record(|| Message::Error(text.to_string())) { return; }
+        progress::clear();
+        let _ = stderr().write_all(text.as_bytes());
+    }
+
+    /// Extra detail on stderr, only shown with --verbose, and never among JSON diagnostics.
+    pub fn verbose(&self, text: &str) {
+        if self.record(|| Message::Verbose(text.to_string())) { return; }
+        if self.level >= Level::Verbose && self.error_format == ErrorFormat::Human {
+            progress::clear();
+            let _ = stderr().write_all(text.as_bytes());
+        }
+    }
+}
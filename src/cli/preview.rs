@@ -0,0 +1,167 @@
+use json_parser_rs::{to_string_with_options, Event, EventReader, JsonValue, KeyOrder, ParseError, SerializeOptions};
+
+/// What `--depth` and `--sample` keep of a document: containers down to the depth, and how many
+/// members or elements each of those below it holds. An object or array also counts the members
+/// or elements that `--sample` left out after those kept.
+pub enum Node {
+    Scalar(JsonValue),
+    Object(Vec<(String, Node)>, usize),
+    Array(Vec<Node>, usize),
+    ElidedObject(usize),
+    ElidedArray(usize),
+}
+
+/// Reads a whole document from `reader`, keeping the contents of containers nested less than
+/// `depth` levels deep; deeper ones are read through only to count what they hold. With
+/// `sample`, only the first that many members or elements of each container are kept, and the
+/// rest are read through to count them. Strings longer than `max_string_len` characters are cut
+/// short with an ellipsis.
+pub fn read(reader: &mut EventReader, depth: usize, sample: Option<usize>, max_string_len: Option<usize>) -> Result<Node, ParseError> {
+    let node = match reader.next_event()? {
+        Some(event) => node(reader, event, depth, sample, max_string_len)?,
+        None => Node::Scalar(JsonValue::Null),
+    };
+    // Rejects anything after the root, as parsing does.
+    reader.next_event()?;
+    Ok(node)
+}
+
+fn node(reader: &mut EventReader, event: Event, depth: usize, sample: Option<usize>, max_string_len: Option<usize>) -> Result<Node, ParseError> {
+    let full = |kept: usize| sample.is_some_and(|sample| kept >= sample);
+    match event {
+        Event::StartObject if depth == 0 => Ok(Node::ElidedObject(count(reader, true)?)),
+        Event::StartArray if depth == 0 => Ok(Node::ElidedArray(count(reader, false)?)),
+        Event::StartObject => {
+            let mut members = Vec::new();
+            loop {
+                if full(members.len()) { return Ok(Node::Object(members, count(reader, true)?)); }
+                let Some(Event::Key(key)) = reader.next_event()? else { break };
+                let Some(event) = reader.next_event()? else { break };
+                members.push((key, node(reader, event, depth - 1, sample, max_string_len)?));
+            }
+            Ok(Node::Object(members, 0))
+        }
+        Event::StartArray => {
+            let mut elements = Vec::new();
+            loop {
+                if full(elements.len()) { return Ok(Node::Array(elements, count(reader, false)?)); }
+                match reader.next_event()? {
+                    Some(Event::EndArray) | None => break,
+                    Some(event) => elements.push(node(reader, event, depth - 1, sample, max_string_len)?),
+                }
+            }
+            Ok(Node::Array(elements, 0))
+        }
+        Event::Value(JsonValue::String(string)) => Ok(Node::Scalar(JsonValue::String(truncate(string, max_string_len)))),
+        Event::Value(value) => Ok(Node::Scalar(value)),
+        // The reader only reports keys and ends where the loops above look for them.
+        Event::Key(_) | Event::EndObject | Event::EndArray => Ok(Node::Scalar(JsonValue::Null)),
+    }
+}
+
+/// The same as `read`, for a document already in memory.
+pub fn from_value(value: &JsonValue, depth: usize) -> Node {
+    match value {
+        JsonValue::Object(members) if depth == 0 => Node::ElidedObject(members.len()),
+        JsonValue::Array(elements) if depth == 0 => Node::ElidedArray(elements.len()),
+        JsonValue::Object(members) => Node::Object(members.iter().map(|(key, member)| (key.clone(), from_value(member, depth - 1))).collect(), 0),
+        JsonValue::Array(elements) => Node::Array(elements.iter().map(|element| from_value(element, depth - 1)).collect(), 0),
+        _ => Node::Scalar(value.clone()),
+    }
+}
+
+/// Reads to the end of the container just started, returning how many members or elements it has.
+fn count(reader: &mut EventReader, object: bool) -> Result<usize, ParseError> {
+    let (mut count, mut nested) = (0, 0);
+    loop {
+        match reader.next_event()? {
+            None => return Ok(count),
+            Some(Event::EndObject | Event::EndArray) if nested == 0 => return Ok(count),
+            Some(Event::EndObject | Event::EndArray) => nested -= 1,
+            // An object's members are counted by their keys, an array's elements by where they start.
+            Some(Event::Key(_)) => if object && nested == 0 { count += 1 },
+            Some(event) => {
+                if !object && nested == 0 { count += 1; }
+                if matches!(event, Event::StartObject | Event::StartArray) { nested += 1; }
+            }
+        }
+    }
+}
+
+fn truncate(string: String, max_string_len: Option<usize>) -> String {
+    match max_string_len {
+        Some(length) if string.chars().nth(length).is_some() => string.chars().take(length).chain(['\u{2026}']).collect(),
+        _ => string,
+    }
+}
+
+/// Formats a node the way the serializer formats a value, with placeholders such as
+/// `{…12 keys…}` and `[…4097 items…]` for the containers left out, and a last line such as
+/// `… 4,095 more items` in those `--sample` cut short.
+pub fn render(node: &Node, options: &SerializeOptions) -> String {
+    let mut output = String::new();
+    write(node, options, 0, &mut output);
+    output
+}
+
+fn write(node: &Node, options: &SerializeOptions, level: usize, output: &mut String) {
+    match node {
+        Node::Scalar(value) => output.push_str(&to_string_with_options(value, options)),
+        Node::ElidedObject(0) => output.push_str("{}"),
+        Node::ElidedArray(0) => output.push_str("[]"),
+        Node::ElidedObject(count) => output.push_str(&format!("{{\u{2026}{} {}\u{2026}}}", count, if *count == 1 { "key" } else { "keys" })),
+        Node::ElidedArray(count) => output.push_str(&format!("[\u{2026}{} {}\u{2026}]", count, if *count == 1 { "item" } else { "items" })),
+        Node::Object(members, omitted) => {
+            // Sorting only orders the sample; the members left out may sort anywhere among them.
+            let mut members: Vec<&(String, Node)> = members.iter().collect();
+            if matches!(options.key_order, KeyOrder::Sorted) { members.sort_by(|a, b| a.0.cmp(&b.0)); }
+            let mut members: Vec<Option<&(String, Node)>> = members.into_iter().map(Some).collect();
+            if *omitted > 0 { members.push(None); }
+            container(('{', '}'), &members, options, level, output, |member, output| match member {
+                Some((key, node)) => {
+                    output.push_str(&to_string_with_options(&JsonValue::String(key.clone()), options));
+                    output.push_str(if options.pretty.is_some() { ": " } else { ":" });
+                    write(node, options, level + 1, output);
+                }
+                None => output.push_str(&more(*omitted, "key", "keys")),
+            });
+        }
+        Node::Array(elements, omitted) => {
+            let mut elements: Vec<Option<&Node>> = elements.iter().map(Some).collect();
+            if *omitted > 0 { elements.push(None); }
+            container(('[', ']'), &elements, options, level, output, |element, output| match element {
+                Some(node) => write(node, options, level + 1, output),
+                None => output.push_str(&more(*omitted, "item", "items")),
+            });
+        }
+    }
+}
+
+/// The line standing in for what `--sample` left out of a container, as in `… 99,988 more items`.
+fn more(count: usize, one: &str, many: &str) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) { grouped.push(','); }
+        grouped.push(digit);
+    }
+    format!("\u{2026} {} more {}", grouped, if count == 1 { one } else { many })
+}
+
+fn container<T>((open, close): (char, char), items: &[T], options: &SerializeOptions, level: usize, output: &mut String, mut item: impl FnMut(&T, &mut String)) {
+    output.push(open);
+    for (index, value) in items.iter().enumerate() {
+        if index > 0 { output.push(','); }
+        newline(options, level + 1, output);
+        item(value, output);
+    }
+    if !items.is_empty() { newline(options, level, output); }
+    output.push(close);
+}
+
+fn newline(options: &SerializeOptions, level: usize, output: &mut String) {
+    if let Some(pretty) = &options.pretty {
+        output.push('\n');
+        output.push_str(&pretty.indent.repeat(level));
+    }
+}
@@ -0,0 +1,128 @@
+use std::io::{stderr, BufRead, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How often the progress line is redrawn, at most.
+const INTERVAL: Duration = Duration::from_millis(250);
+
+/// Bytes read between looks at the clock, so that counting costs next to nothing.
+const CHECK_BYTES: u64 = 64 * 1024;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether a progress line is on the screen, behind a lock so lines are drawn and cleared whole.
+static SHOWN: Mutex<bool> = Mutex::new(false);
+
+/// Turns on --progress. Only call this when stderr is a terminal: the line is redrawn in place
+/// with a carriage return and an ANSI erase, which anything else would record as noise.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Erases the progress line, if one is shown, so that whatever is printed next starts a clean line.
+pub fn clear() {
+    if !enabled() { return; }
+    let mut shown = SHOWN.lock().unwrap_or_else(PoisonError::into_inner);
+    if *shown {
+        let _ = stderr().write_all(b"\r\x1b[K");
+        *shown = false;
+    }
+}
+
+fn draw(line: &str) {
+    let mut shown = SHOWN.lock().unwrap_or_else(PoisonError::into_inner);
+    let _ = stderr().write_all(format!("\r{}\x1b[K", line).as_bytes());
+    *shown = true;
+}
+
+/// Counts the bytes read through it and, with --progress, shows how far through its input it
+/// is, and how soon it will be done when the size of the input is known. The line is cleared
+/// when the meter is dropped.
+pub struct Meter<R> {
+    inner: R,
+    name: String,
+    total: Option<u64>,
+    read: u64,
+    checked: u64,
+    started: Instant,
+    drawn: Instant,
+}
+
+impl<R> Meter<R> {
+    pub fn new(inner: R, name: &str, total: Option<u64>) -> Meter<R> {
+        let now = Instant::now();
+        Meter { inner, name: name.to_string(), total, read: 0, checked: 0, started: now, drawn: now }
+    }
+
+    fn count(&mut self, bytes: usize) {
+        self.read += bytes as u64;
+        if self.read - self.checked < CHECK_BYTES || !enabled() { return; }
+        self.checked = self.read;
+        if self.drawn.elapsed() < INTERVAL { return; }
+        self.drawn = Instant::now();
+        draw(&self.describe());
+    }
+
+    fn describe(&self) -> String {
+        let seconds = self.started.elapsed().as_secs_f64();
+        let rate = self.read as f64 / seconds.max(0.001);
+        let Some(total) = self.total.filter(|&total| total > 0) else {
+            return format!("{}: {} read, {}/s", self.name, size(self.read as f64), size(rate));
+        };
+        let percent = (self.read as f64 / total as f64 * 100.0).min(100.0);
+        let left = total.saturating_sub(self.read) as f64 / rate.max(1.0);
+        format!("{}: {} of {} ({:.0}%), {}/s, {} left", self.name, size(self.read as f64), size(total as f64), percent, size(rate), duration(left))
+    }
+}
+
+impl<R> Drop for Meter<R> {
+    fn drop(&mut self) {
+        clear();
+    }
+}
+
+impl<R: Read> Read for Meter<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buffer)?;
+        self.count(read);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for Meter<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.inner.consume(amount);
+        self.count(amount);
+    }
+}
+
+/// A byte count in the largest binary unit it reaches, as in "1.5 GiB".
+fn size(bytes: f64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < units.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{:.0} B", value) } else { format!("{:.1} {}", value, units[unit]) }
+}
+
+/// Seconds as "1h 02m", "3m 05s" or "42s".
+fn duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    match seconds {
+        3600.. => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+        60.. => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}s", seconds),
+    }
+}
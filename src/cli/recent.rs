@@ -0,0 +1,102 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{BufRead, Read};
+use std::rc::Rc;
+
+/// Lines kept, enough for the one an error is on and the one before it, which is where the
+/// parser reports an input that ends too soon.
+const LINES: usize = 3;
+
+/// Longest line kept. A longer one is dropped, and errors on it are shown without the line:
+/// the parser already holds it once, and this should not hold it again.
+const MAX_LINE: usize = 1 << 20;
+
+/// What has been read of an input that cannot be read again, such as standard input: its size,
+/// its first bytes, and its last few lines.
+#[derive(Default)]
+pub struct Recent {
+    bytes: u64,
+    start: Vec<u8>,
+    /// Number of the line being read, counting from 1, and the lines before it, newest last.
+    line: usize,
+    lines: VecDeque<Option<Vec<u8>>>,
+    /// The line being read, or `None` once it is longer than `MAX_LINE`.
+    current: Option<Vec<u8>>,
+}
+
+impl Recent {
+    pub fn new() -> Recent {
+        Recent { line: 1, current: Some(Vec::new()), ..Recent::default() }
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Up to the first four bytes, enough to recognize UTF-16.
+    pub fn start(&self) -> &[u8] {
+        &self.start
+    }
+
+    /// Line `number`, if it is one of the last few read and not too long to keep.
+    pub fn line(&self, number: usize) -> Option<String> {
+        let line = match self.line.checked_sub(number)? {
+            0 => self.current.as_ref(),
+            back => self.lines.get(self.lines.len().checked_sub(back)?)?.as_ref(),
+        };
+        line.map(|line| String::from_utf8_lossy(line).into_owned())
+    }
+
+    fn record(&mut self, mut bytes: &[u8]) {
+        self.bytes += bytes.len() as u64;
+        let wanted = 4usize.saturating_sub(self.start.len()).min(bytes.len());
+        self.start.extend_from_slice(&bytes[..wanted]);
+        while !bytes.is_empty() {
+            let end = bytes.iter().position(|&byte| byte == b'\n').map_or(bytes.len(), |newline| newline + 1);
+            if let Some(current) = &mut self.current {
+                current.extend_from_slice(&bytes[..end]);
+                if current.len() > MAX_LINE { self.current = None; }
+            }
+            if bytes[end - 1] == b'\n' {
+                if self.lines.len() == LINES - 1 { self.lines.pop_front(); }
+                self.lines.push_back(self.current.replace(Vec::new()));
+                self.line += 1;
+            }
+            bytes = &bytes[end..];
+        }
+    }
+}
+
+/// Passes reads through to `inner`, noting in a shared `Recent` what they returned.
+pub struct Tap<R> {
+    inner: R,
+    recent: Rc<RefCell<Recent>>,
+}
+
+impl<R> Tap<R> {
+    pub fn new(inner: R, recent: Rc<RefCell<Recent>>) -> Tap<R> {
+        Tap { inner, recent }
+    }
+}
+
+impl<R: BufRead> Read for Tap<R> {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.fill_buf()?.read(buffer)?;
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for Tap<R> {
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        // What is consumed was returned by the last `fill_buf`, which returns it again.
+        if let Ok(buffer) = self.inner.fill_buf() {
+            self.recent.borrow_mut().record(&buffer[..amount.min(buffer.len())]);
+        }
+        self.inner.consume(amount);
+    }
+}
@@ -0,0 +1,116 @@
+/// Lines of unchanged context shown around each change.
+const CONTEXT: usize = 3;
+
+/// Above this many line pairs left after trimming the common start and end, the lines between are
+/// shown as replaced wholesale rather than spending quadratic time and memory on a minimal diff.
+const MAX_TABLE: usize = 4_000_000;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Line {
+    Same(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+/// Renders the differences between `old` and `new` as a unified diff, like `diff -u`, or returns
+/// an empty string when they are equal.
+pub fn diff(old_name: &str, new_name: &str, old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.split_inclusive('\n').collect();
+    let new: Vec<&str> = new.split_inclusive('\n').collect();
+    let lines = align(&old, &new);
+    if lines.iter().all(|line| matches!(line, Line::Same(..))) {
+        return String::new();
+    }
+
+    let mut text = format!("--- {}\n+++ {}\n", old_name, new_name);
+    let changed: Vec<usize> = (0..lines.len()).filter(|&index| !matches!(lines[index], Line::Same(..))).collect();
+    let mut group = 0;
+    while group < changed.len() {
+        // Extend the hunk while the next change is close enough for the contexts to touch.
+        let mut last = group;
+        while last + 1 < changed.len() && changed[last + 1] - changed[last] <= 2 * CONTEXT {
+            last += 1;
+        }
+        let start = changed[group].saturating_sub(CONTEXT);
+        let end = (changed[last] + CONTEXT + 1).min(lines.len());
+        hunk(&mut text, &lines[start..end], &old, &new);
+        group = last + 1;
+    }
+    text
+}
+
+fn hunk(text: &mut String, lines: &[Line], old: &[&str], new: &[&str]) {
+    let old_start = lines.iter().find_map(|line| match *line {
+        Line::Same(index, _) | Line::Removed(index) => Some(index),
+        Line::Added(_) => None,
+    });
+    let new_start = lines.iter().find_map(|line| match *line {
+        Line::Same(_, index) | Line::Added(index) => Some(index),
+        Line::Removed(_) => None,
+    });
+    let old_count = lines.iter().filter(|line| !matches!(line, Line::Added(_))).count();
+    let new_count = lines.iter().filter(|line| !matches!(line, Line::Removed(_))).count();
+    // As in diff -u, a count of one is left out and an empty side is numbered 0.
+    let range = |start: Option<usize>, count: usize| match (start, count) {
+        (Some(start), 1) => format!("{}", start + 1),
+        (Some(start), _) => format!("{},{}", start + 1, count),
+        (None, _) => "0,0".to_string(),
+    };
+    text.push_str(&format!("@@ -{} +{} @@\n", range(old_start, old_count), range(new_start, new_count)));
+
+    for line in lines {
+        let (marker, content) = match *line {
+            Line::Same(index, _) => (' ', old[index]),
+            Line::Removed(index) => ('-', old[index]),
+            Line::Added(index) => ('+', new[index]),
+        };
+        text.push(marker);
+        text.push_str(content);
+        if !content.ends_with('\n') {
+            text.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+/// Pairs up the lines of `old` and `new` along a longest common subsequence.
+fn align(old: &[&str], new: &[&str]) -> Vec<Line> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(a, b)| a == b).count();
+    let (old_middle, new_middle) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut lines: Vec<Line> = (0..prefix).map(|index| Line::Same(index, index)).collect();
+    if old_middle.len().saturating_mul(new_middle.len()) > MAX_TABLE {
+        lines.extend((prefix..old.len() - suffix).map(Line::Removed));
+        lines.extend((prefix..new.len() - suffix).map(Line::Added));
+    } else {
+        // longest[i][j] is the length of the longest common subsequence of old_middle[i..] and new_middle[j..].
+        let width = new_middle.len() + 1;
+        let mut longest = vec![0usize; (old_middle.len() + 1) * width];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                longest[i * width + j] = if old_middle[i] == new_middle[j] {
+                    longest[(i + 1) * width + j + 1] + 1
+                } else {
+                    longest[(i + 1) * width + j].max(longest[i * width + j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < old_middle.len() || j < new_middle.len() {
+            if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+                lines.push(Line::Same(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == new_middle.len() || (i < old_middle.len() && longest[(i + 1) * width + j] >= longest[i * width + j + 1]) {
+                lines.push(Line::Removed(prefix + i));
+                i += 1;
+            } else {
+                lines.push(Line::Added(prefix + j));
+                j += 1;
+            }
+        }
+    }
+    lines.extend((0..suffix).map(|offset| Line::Same(old.len() - suffix + offset, new.len() - suffix + offset)));
+    lines
+}
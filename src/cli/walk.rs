@@ -0,0 +1,77 @@
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use json_parser_rs::Encoding;
+
+use super::args::Input;
+
+/// How much of a file is looked at to decide whether it is binary.
+const SNIFF_BYTES: u64 = 8192;
+
+/// The files found by `expand`, and what went wrong along the way.
+#[derive(Default)]
+pub struct Expanded {
+    pub inputs: Vec<Input>,
+    /// Files with a matching extension passed over because they look binary.
+    pub skipped: Vec<String>,
+    pub errors: Vec<(String, io::Error)>,
+}
+
+/// Replaces each directory among `inputs` with the files beneath it whose extension is one of
+/// `extensions`, in name order. Entries whose names start with a dot are left out unless `hidden`
+/// is set, and a directory reached twice, as through a symbolic link loop, is only read once.
+pub fn expand(inputs: &[Input], extensions: &[String], hidden: bool) -> Expanded {
+    let mut expanded = Expanded::default();
+    let mut visited = HashSet::new();
+    for input in inputs {
+        match input {
+            Input::File(path) if Path::new(path).is_dir() => walk(Path::new(path), extensions, hidden, &mut visited, &mut expanded),
+            _ => expanded.inputs.push(input.clone()),
+        }
+    }
+    expanded
+}
+
+fn walk(directory: &Path, extensions: &[String], hidden: bool, visited: &mut HashSet<PathBuf>, expanded: &mut Expanded) {
+    let entries = fs::canonicalize(directory).and_then(|canonical| {
+        if !visited.insert(canonical) { return Ok(Vec::new()); }
+        fs::read_dir(directory)?.map(|entry| entry.map(|entry| entry.path())).collect::<io::Result<Vec<_>>>()
+    });
+    let mut entries = match entries {
+        Ok(entries) => entries,
+        Err(error) => {
+            expanded.errors.push((directory.display().to_string(), error));
+            return;
+        }
+    };
+    entries.sort();
+
+    for path in entries {
+        let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        if !hidden && name.starts_with('.') { continue; }
+
+        if path.is_dir() {
+            walk(&path, extensions, hidden, visited, expanded);
+            continue;
+        }
+        let extension = path.extension().map(|extension| extension.to_string_lossy()).unwrap_or_default();
+        if !extensions.iter().any(|wanted| *wanted == extension) { continue; }
+
+        let display = path.display().to_string();
+        match looks_binary(&path) {
+            Ok(false) => expanded.inputs.push(Input::File(display)),
+            Ok(true) => expanded.skipped.push(display),
+            Err(error) => expanded.errors.push((display, error)),
+        }
+    }
+}
+
+/// Whether the start of the file holds a NUL byte, which no JSON text contains, other than as
+/// half of a UTF-16 character.
+fn looks_binary(path: &Path) -> io::Result<bool> {
+    let mut start = Vec::new();
+    File::open(path)?.take(SNIFF_BYTES).read_to_end(&mut start)?;
+    Ok(start.contains(&0) && Encoding::detect(&start) == Encoding::Utf8)
+}
@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::args::Input;
+use super::{follow, walk};
+
+/// How long a watcher waits between looks at its files.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a file must stay the same after changing before it is reported, so that a save
+/// written in several steps, or several saves in quick succession, are reported once.
+const SETTLE: Duration = Duration::from_millis(200);
+
+pub enum Event {
+    /// A file seen for the first time, when watching starts or when it is created.
+    Found(String),
+    Changed(String),
+    Removed(String),
+}
+
+/// What a file looked like when last seen. A file replaced by renaming another over it, as
+/// editors save atomically, is a different file even if its size and time are the same.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Stamp {
+    length: u64,
+    modified: Option<SystemTime>,
+    #[cfg(unix)]
+    file: (u64, u64),
+}
+
+fn stamp(path: &str) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some(Stamp {
+        length: metadata.len(),
+        modified: metadata.modified().ok(),
+        #[cfg(unix)]
+        file: {
+            use std::os::unix::fs::MetadataExt;
+            (metadata.dev(), metadata.ino())
+        },
+    })
+}
+
+/// Polls files, and the files beneath directories as `--recursive` finds them, for changes.
+pub struct Watcher {
+    inputs: Vec<Input>,
+    extensions: Vec<String>,
+    hidden: bool,
+    known: BTreeMap<String, Stamp>,
+    /// Files that have changed since they were last reported, with when they were last seen to.
+    settling: BTreeMap<String, (Stamp, Instant)>,
+}
+
+impl Watcher {
+    pub fn new(inputs: &[Input], extensions: &[String], hidden: bool) -> Watcher {
+        Watcher { inputs: inputs.to_vec(), extensions: extensions.to_vec(), hidden, known: BTreeMap::new(), settling: BTreeMap::new() }
+    }
+
+    /// Waits until files appear, change or disappear and returns what happened to them: removals
+    /// first, then the rest in name order. Returns nothing once Ctrl-C has been caught.
+    pub fn next(&mut self) -> Vec<Event> {
+        loop {
+            if follow::interrupted() { return Vec::new(); }
+            let events = self.scan();
+            if !events.is_empty() { return events; }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn scan(&mut self) -> Vec<Event> {
+        let now = Instant::now();
+        let mut current = BTreeMap::new();
+        for input in walk::expand(&self.inputs, &self.extensions, self.hidden).inputs {
+            if let Input::File(path) = input {
+                if let Some(stamp) = stamp(&path) { current.insert(path, stamp); }
+            }
+        }
+
+        let mut events = Vec::new();
+        self.settling.retain(|path, _| current.contains_key(path));
+        let removed: Vec<String> = self.known.keys().filter(|path| !current.contains_key(*path)).cloned().collect();
+        for path in removed {
+            self.known.remove(&path);
+            events.push(Event::Removed(path));
+        }
+        for (path, stamp) in current {
+            if self.known.get(&path) == Some(&stamp) {
+                self.settling.remove(&path);
+                continue;
+            }
+            match self.settling.get(&path) {
+                Some((settling, since)) if *settling == stamp => {
+                    if now.duration_since(*since) < SETTLE { continue; }
+                    self.settling.remove(&path);
+                    let event = if self.known.contains_key(&path) { Event::Changed(path.clone()) } else { Event::Found(path.clone()) };
+                    self.known.insert(path, stamp);
+                    events.push(event);
+                }
+                _ => {
+                    self.settling.insert(path, (stamp, now));
+                }
+            }
+        }
+        events
+    }
+}
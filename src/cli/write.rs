@@ -0,0 +1,109 @@
+use std::fs::{self, File, OpenOptions, Permissions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Replaces the contents of the file at `path`, returning false without touching it when they are
+/// already `contents`. The new contents go to a temporary file in the same directory, which is
+/// given the original's permissions and then renamed over it, so a failure at any point leaves
+/// either the old file or the new one, never a truncated mix.
+pub fn replace(path: &str, contents: &[u8]) -> io::Result<bool> {
+    if fs::read(path)? == contents {
+        return Ok(false);
+    }
+    let permissions = fs::metadata(path)?.permissions();
+    write_atomically(Path::new(path), contents, Some(permissions))?;
+    Ok(true)
+}
+
+/// A file being written the same way as `replace`, whether or not it exists yet, for output too
+/// large to hold in memory first: written to the temporary file as it comes, which `commit`
+/// renames over `path`. Dropped without committing, the temporary file is removed.
+pub struct Pending {
+    path: PathBuf,
+    temporary: PathBuf,
+    file: Option<BufWriter<File>>,
+    permissions: Option<Permissions>,
+    committed: bool,
+    /// The first write that failed; later writes are skipped and `commit` returns it.
+    error: Option<io::Error>,
+}
+
+impl Pending {
+    pub fn create(path: &str) -> io::Result<Pending> {
+        let permissions = fs::metadata(path).ok().map(|metadata| metadata.permissions());
+        let temporary = temporary_path(Path::new(path))?;
+        let file = OpenOptions::new().write(true).create_new(true).open(&temporary)?;
+        Ok(Pending { path: PathBuf::from(path), temporary, file: Some(BufWriter::new(file)), permissions, committed: false, error: None })
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_some() { return; }
+        if let Some(Err(error)) = self.file.as_mut().map(|file| file.write_all(bytes)) {
+            self.error = Some(error);
+        }
+    }
+
+    pub fn commit(mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() { return Err(error); }
+        let Some(file) = self.file.take() else { return Ok(()) };
+        let file = file.into_inner().map_err(io::IntoInnerError::into_error)?;
+        file.sync_all()?;
+        finish(&self.temporary, &self.path, self.permissions.take())?;
+        self.committed = true;
+        Ok(())
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.temporary);
+        }
+    }
+}
+
+/// Whether both paths name the same existing file, however they are spelled.
+pub fn same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn write_atomically(path: &Path, contents: &[u8], permissions: Option<Permissions>) -> io::Result<()> {
+    let temporary = temporary_path(path)?;
+    // create_new rather than truncating: a leftover or concurrent temporary file is an error, not ours to clobber.
+    let mut file = OpenOptions::new().write(true).create_new(true).open(&temporary)?;
+    if let Err(error) = file.write_all(contents).and_then(|()| file.sync_all()) {
+        let _ = fs::remove_file(&temporary);
+        return Err(error);
+    }
+    finish(&temporary, path, permissions)
+}
+
+/// Gives the written `temporary` file `permissions` and renames it over `path`, or removes it.
+fn finish(temporary: &Path, path: &Path, permissions: Option<Permissions>) -> io::Result<()> {
+    let renamed = permissions.map_or(Ok(()), |permissions| fs::set_permissions(temporary, permissions))
+        .and_then(|()| fs::rename(temporary, path));
+    if let Err(error) = renamed {
+        let _ = fs::remove_file(temporary);
+        return Err(error);
+    }
+
+    // Make the rename itself durable; not every platform can open a directory, so this is best effort.
+    if let Some(directory) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        let _ = File::open(directory).and_then(|directory| directory.sync_all());
+    }
+    Ok(())
+}
+
+/// `dir/.name.json-parser-rs.tmp` for `dir/name`.
+fn temporary_path(path: &Path) -> io::Result<PathBuf> {
+    let Some(name) = path.file_name() else {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a file name"));
+    };
+    let mut temporary = std::ffi::OsString::from(".");
+    temporary.push(name);
+    temporary.push(".json-parser-rs.tmp");
+    Ok(path.with_file_name(temporary))
+}
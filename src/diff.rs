@@ -0,0 +1,96 @@
+use std::fmt;
+
+use crate::pointer::escape_token;
+use crate::value::JsonValue;
+
+/// One difference between two documents, as found by `diff`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added { pointer: String, value: JsonValue },
+    Removed { pointer: String, value: JsonValue },
+    Changed { pointer: String, old: JsonValue, new: JsonValue },
+}
+
+impl Change {
+    /// JSON Pointer to the value that differs.
+    pub fn pointer(&self) -> &str {
+        match self {
+            Change::Added { pointer, .. } | Change::Removed { pointer, .. } | Change::Changed { pointer, .. } => pointer,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer().is_empty() { "the root" } else { self.pointer() };
+        match self {
+            Change::Added { value, .. } => write!(f, "added {}: {}", pointer, value),
+            Change::Removed { value, .. } => write!(f, "removed {}: {}", pointer, value),
+            Change::Changed { old, new, .. } => write!(f, "changed {}: {} -> {}", pointer, old, new),
+        }
+    }
+}
+
+/// Compares two documents structurally: the order of object members and the formatting of the
+/// source do not matter. Array elements are compared by index, so inserting at the front of an
+/// array changes every element after it. Changes are listed in the order applying them as a
+/// patch requires, so array elements are removed from the end.
+pub fn diff(old: &JsonValue, new: &JsonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+    compare(old, new, &mut String::new(), &mut changes);
+    changes
+}
+
+fn compare(old: &JsonValue, new: &JsonValue, pointer: &mut String, changes: &mut Vec<Change>) {
+    let length = pointer.len();
+    match (old, new) {
+        (JsonValue::Object(old_members), JsonValue::Object(new_members)) => {
+            for (key, old_member) in old_members {
+                pointer.push('/');
+                pointer.push_str(&escape_token(key));
+                match new_members.iter().find(|(name, _)| name == key) {
+                    Some((_, new_member)) => compare(old_member, new_member, pointer, changes),
+                    None => changes.push(Change::Removed { pointer: pointer.clone(), value: old_member.clone() }),
+                }
+                pointer.truncate(length);
+            }
+            for (key, new_member) in new_members {
+                if old_members.iter().any(|(name, _)| name == key) { continue; }
+                changes.push(Change::Added { pointer: format!("{}/{}", pointer, escape_token(key)), value: new_member.clone() });
+            }
+        }
+        (JsonValue::Array(old_elements), JsonValue::Array(new_elements)) => {
+            for (index, (old_element, new_element)) in old_elements.iter().zip(new_elements).enumerate() {
+                pointer.push_str(&format!("/{}", index));
+                compare(old_element, new_element, pointer, changes);
+                pointer.truncate(length);
+            }
+            for (index, old_element) in old_elements.iter().enumerate().skip(new_elements.len()).rev() {
+                changes.push(Change::Removed { pointer: format!("{}/{}", pointer, index), value: old_element.clone() });
+            }
+            for (index, new_element) in new_elements.iter().enumerate().skip(old_elements.len()) {
+                changes.push(Change::Added { pointer: format!("{}/{}", pointer, index), value: new_element.clone() });
+            }
+        }
+        (old, new) if old != new => changes.push(Change::Changed { pointer: pointer.clone(), old: old.clone(), new: new.clone() }),
+        _ => { }
+    }
+}
+
+/// Writes changes as an RFC 6902 JSON Patch, which turns the old document into the new one.
+pub fn to_patch(changes: &[Change]) -> JsonValue {
+    let operation = |op: &str, pointer: &str, value: Option<&JsonValue>| {
+        let mut members = vec![
+            ("op".to_string(), JsonValue::String(op.to_string())),
+            ("path".to_string(), JsonValue::String(pointer.to_string())),
+        ];
+        members.extend(value.map(|value| ("value".to_string(), value.clone())));
+        JsonValue::Object(members)
+    };
+
+    JsonValue::Array(changes.iter().map(|change| match change {
+        Change::Added { pointer, value } => operation("add", pointer, Some(value)),
+        Change::Removed { pointer, .. } => operation("remove", pointer, None),
+        Change::Changed { pointer, new, .. } => operation("replace", pointer, Some(new)),
+    }).collect())
+}
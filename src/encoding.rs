@@ -0,0 +1,134 @@
+//! Reading JSON that was saved in UTF-16, which the parser itself does not accept.
+
+use std::io::{self, Read};
+
+/// How the bytes of an input encode its text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// The name `--encoding` takes.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf-8",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+        }
+    }
+
+    /// Guesses the encoding of an input from its first bytes: a UTF-16 byte order mark, or NULs
+    /// alternating with the first two characters, as ASCII is written in UTF-16 and never in
+    /// UTF-8 JSON. Anything else is taken to be UTF-8.
+    pub fn detect(start: &[u8]) -> Encoding {
+        match start {
+            [0xFF, 0xFE, ..] => Encoding::Utf16Le,
+            [0xFE, 0xFF, ..] => Encoding::Utf16Be,
+            [1..=255, 0] | [1..=255, 0, 1..=255, 0, ..] => Encoding::Utf16Le,
+            [0, 1..=255] | [0, 1..=255, 0, 1..=255, ..] => Encoding::Utf16Be,
+            _ => Encoding::Utf8,
+        }
+    }
+
+    /// Wraps `reader` so that it reads as UTF-8. A UTF-16 byte order mark at the start is
+    /// dropped. An unpaired surrogate fails the read with `io::ErrorKind::InvalidData`, naming
+    /// its byte offset, once everything before it has been read.
+    pub fn decode<'a>(self, reader: Box<dyn Read + 'a>) -> Box<dyn Read + 'a> {
+        match self {
+            Encoding::Utf8 => reader,
+            Encoding::Utf16Le | Encoding::Utf16Be => Box::new(Utf16Decoder {
+                reader,
+                big_endian: self == Encoding::Utf16Be,
+                undecoded: Vec::new(),
+                decoded: Vec::new(),
+                position: 0,
+                started: false,
+                offset: 0,
+                failure: None,
+            }),
+        }
+    }
+}
+
+/// Bytes read from the underlying reader at a time.
+const CHUNK: usize = 8 * 1024;
+
+struct Utf16Decoder<'a> {
+    reader: Box<dyn Read + 'a>,
+    big_endian: bool,
+    /// Bytes read but not yet decoded: half a code unit, or the first half of a surrogate pair.
+    undecoded: Vec<u8>,
+    /// UTF-8 decoded but not yet returned, from `position` on.
+    decoded: Vec<u8>,
+    position: usize,
+    started: bool,
+    /// Bytes of the underlying reader decoded so far, where `undecoded` starts.
+    offset: u64,
+    /// Why decoding stopped, returned by every read once `decoded` is used up.
+    failure: Option<String>,
+}
+
+impl Utf16Decoder<'_> {
+    /// Decodes the next chunk into `decoded`, returning false at the end of the input or where
+    /// it stops being UTF-16, after setting `failure`.
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; CHUNK];
+        let read = self.reader.read(&mut chunk)?;
+        self.undecoded.extend_from_slice(&chunk[..read]);
+        let at_end = read == 0;
+
+        let mut units: Vec<u16> = self.undecoded.chunks_exact(2)
+            .map(|pair| if self.big_endian { u16::from_be_bytes([pair[0], pair[1]]) } else { u16::from_le_bytes([pair[0], pair[1]]) })
+            .collect();
+        // A high surrogate at the end of a chunk waits for its other half.
+        if !at_end && matches!(units.last(), Some(0xD800..=0xDBFF)) { units.pop(); }
+        let used = units.len() * 2;
+        self.undecoded.drain(..used);
+        if at_end && !self.undecoded.is_empty() {
+            let offset = self.offset + used as u64;
+            self.failure = Some(format!("invalid UTF-16: the input ends halfway through a character at byte {}", offset));
+        }
+
+        self.decoded.clear();
+        self.position = 0;
+        for character in char::decode_utf16(units) {
+            let character = match character {
+                Ok(character) => character,
+                Err(error) => {
+                    let surrogate = error.unpaired_surrogate();
+                    self.failure = Some(format!("invalid UTF-16: unpaired surrogate 0x{:04X} at byte {}", surrogate, self.offset));
+                    return Ok(false);
+                }
+            };
+            self.offset += 2 * character.len_utf16() as u64;
+            if !self.started && character == '\u{feff}' {
+                self.started = true;
+                continue;
+            }
+            self.started = true;
+            let mut buffer = [0; 4];
+            self.decoded.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+        }
+        Ok(!at_end)
+    }
+}
+
+impl Read for Utf16Decoder<'_> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.decoded.len() {
+            if let Some(failure) = &self.failure {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, failure.clone()));
+            }
+            let more = self.fill()?;
+            if !more && self.decoded.is_empty() && self.failure.is_none() { return Ok(0); }
+        }
+        let count = buffer.len().min(self.decoded.len() - self.position);
+        buffer[..count].copy_from_slice(&self.decoded[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
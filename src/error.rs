@@ -0,0 +1,78 @@
+use std::fmt;
+use std::io;
+
+use crate::lexer::Token;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ErrorKind {
+    UnexpectedToken,
+    UnexpectedEnd,
+    NumberOutOfRange,
+    InvalidString,
+    /// The input is not UTF-8, under `InvalidUtf8::Error`.
+    InvalidUtf8,
+    /// The input is not valid in the `Encoding` it is decoded from, such as UTF-16 with an
+    /// unpaired surrogate.
+    InvalidEncoding,
+    TooDeep,
+    DuplicateKey,
+    /// A length-prefixed frame is malformed; see `read_framed`.
+    InvalidFrame,
+    /// The input is longer than `ParseOptions::max_bytes` or `max_tokens` allow, or holds a key or
+    /// string longer than `max_key_length` or `max_value_bytes` allow.
+    LimitExceeded,
+    Io,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    /// Byte offset from the start of the input.
+    pub offset: usize,
+    /// Line, column and byte offset just past the offending token, when the error is about one.
+    pub end: Option<(usize, usize, usize)>,
+}
+
+impl ParseError {
+    pub fn new(kind: ErrorKind, message: String, line: usize, column: usize, offset: usize) -> ParseError {
+        ParseError {
+            kind,
+            message,
+            line,
+            column,
+            offset,
+            end: None,
+        }
+    }
+
+    pub(crate) fn at(kind: ErrorKind, message: String, token: &Token) -> ParseError {
+        let text = &token.original_text;
+        let mut error = ParseError::new(kind, message, token.line, token.column, token.offset);
+        // Block comments can span lines; where one ends is left unknown rather than worked out.
+        if !text.contains('\n') {
+            error.end = Some((token.line, token.column + text.chars().count(), token.offset + text.len()));
+        }
+        error
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(error: io::Error) -> ParseError {
+        ParseError::new(ErrorKind::Io, error.to_string(), 0, 0, 0)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.kind == ErrorKind::Io {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}:{}: {}", self.line, self.column, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,646 @@
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::error::{ErrorKind, ParseError};
+use crate::lexer::{Comment, Lexer, Token, TokenType};
+use crate::messages::{English, Found, Messages, StringProblem};
+use crate::number::{F64NumberParser, NumberParser};
+use crate::parser::{DuplicateKeys, LeadingZeros, Mode, ParseOptions};
+use crate::pointer::escape_token;
+use crate::value::JsonValue;
+
+/// One step of a document read with `EventReader`. Containers are reported as start and end
+/// events around their contents; every object member is a `Key` followed by its value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    /// A string, number, boolean or null.
+    Value(JsonValue),
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Container {
+    Object,
+    Array,
+}
+
+/// An open object or array, with what is needed to say where in the document the reader is.
+struct Frame {
+    container: Container,
+    /// Key of the current member, escaped for a JSON Pointer, or index of the current element.
+    segment: String,
+    elements: usize,
+    /// Line and column where each key of an object was first seen; only kept when checking for duplicates.
+    keys: HashMap<String, (usize, usize)>,
+}
+
+impl Frame {
+    fn new(container: Container) -> Frame {
+        Frame { container, segment: String::new(), elements: 0, keys: HashMap::new() }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum State {
+    /// A value comes next: at the root, after a key, or after a comma in an array.
+    Value,
+    /// Just after '{', where '}' may close an empty object.
+    FirstKey,
+    /// After a comma in an object.
+    Key,
+    /// Just after '[', where ']' may close an empty array.
+    FirstElement,
+    /// After a complete value: a comma or the end of the enclosing container, or end of input at the root.
+    AfterValue,
+    Done,
+}
+
+/// Pull parser that reads a document one event at a time without building it in memory.
+pub struct EventReader {
+    lexer: Lexer,
+    options: ParseOptions,
+    number_parser: Box<dyn NumberParser>,
+    messages: Box<dyn Messages>,
+    next_token: Option<Arc<Token>>,
+    last_token: Option<Arc<Token>>,
+    comments: Vec<Comment>,
+    warnings: Vec<ParseError>,
+    stack: Vec<Frame>,
+    state: State,
+    started: bool,
+    skipping: bool
+}
+
+impl EventReader {
+    pub fn new(lexer: Lexer) -> EventReader {
+        EventReader::with_options(lexer, ParseOptions::default())
+    }
+
+    pub fn with_options(lexer: Lexer, options: ParseOptions) -> EventReader {
+        EventReader {
+            lexer: lexer
+                .with_invalid_utf8(options.invalid_utf8)
+                .with_limits(options.max_bytes, options.max_tokens)
+                .with_single_quotes(options.allow_single_quotes)
+                .with_identifiers(options.allow_unquoted_keys),
+            options,
+            number_parser: Box::new(F64NumberParser),
+            messages: Box::new(English),
+            next_token: None,
+            last_token: None,
+            comments: Vec::new(),
+            warnings: Vec::new(),
+            stack: Vec::new(),
+            state: State::Value,
+            started: false,
+            skipping: false
+        }
+    }
+
+    pub fn with_number_parser(mut self, number_parser: Box<dyn NumberParser>) -> EventReader {
+        self.number_parser = number_parser;
+        self
+    }
+
+    /// Words error messages with `messages` instead of the built-in English.
+    pub fn with_messages(mut self, messages: Box<dyn Messages>) -> EventReader {
+        self.messages = messages;
+        self
+    }
+
+    /// Gives back the lexer, to `reset` and read another input with.
+    pub fn into_lexer(self) -> Lexer {
+        self.lexer
+    }
+
+    /// Returns the next event, or `None` once the root value is complete and only whitespace follows.
+    pub fn next_event(&mut self) -> Result<Option<Event>, ParseError> {
+        if !self.started {
+            self.started = true;
+            self.advance();
+        }
+
+        let event = self.step();
+        // A read error looks like the end of input to the lexer, so report it instead of whatever that caused.
+        // Once the root is complete, what follows may be someone else's and unreadable as text.
+        let finished = self.stack.is_empty() && matches!(self.state, State::AfterValue | State::Done);
+        if let Some(mut error) = self.lexer.take_error() {
+            if error.kind == ErrorKind::InvalidUtf8 { error.message = self.messages.invalid_utf8(&error.message); }
+            if !(finished && self.options.allow_trailing_data) { return Err(error); }
+        }
+        event
+    }
+
+    /// Advances past the value that the next event would start, without decoding it. Must be called
+    /// where a value is expected: before the root, after a `Key`, or between the elements of an array.
+    /// Brackets inside the value are still checked to be balanced and well formed.
+    pub fn skip_value(&mut self) -> Result<(), ParseError> {
+        if self.state == State::FirstElement && self.check(TokenType::RightSquareBracket) {
+            return Err(self.unexpected("a value"));
+        }
+        if self.state == State::AfterValue && self.container() == Some(Container::Array) {
+            if self.match_token(TokenType::Comma).is_none() { return Err(self.unexpected("','")); }
+            self.state = State::Value;
+        }
+        if self.state != State::Value && self.state != State::FirstElement {
+            return Err(self.unexpected("a value"));
+        }
+
+        let depth = self.stack.len();
+        self.skipping = true;
+        let result = self.skip_to(depth);
+        self.skipping = false;
+        result
+    }
+
+    fn skip_to(&mut self, depth: usize) -> Result<(), ParseError> {
+        loop {
+            if self.next_event()?.is_none() { return Err(self.unexpected("a value")); }
+            if self.stack.len() == depth { return Ok(()); }
+        }
+    }
+
+    /// Builds the whole value that `event`, just returned by `next_event`, starts, reading the rest
+    /// of it. Lets a large document be read one part at a time, such as each element of an array.
+    pub fn read_value(&mut self, event: Event) -> Result<JsonValue, ParseError> {
+        match event {
+            Event::Value(value) => Ok(value),
+            Event::StartObject => {
+                let mut members = Vec::new();
+                while let Some(Event::Key(key)) = self.next_event()? {
+                    let event = self.next_event()?.ok_or_else(|| self.unexpected("a value"))?;
+                    members.push((key, self.read_value(event)?));
+                }
+                Ok(JsonValue::Object(members))
+            }
+            Event::StartArray => {
+                let mut elements = Vec::new();
+                loop {
+                    match self.next_event()? {
+                        Some(Event::EndArray) | None => return Ok(JsonValue::Array(elements)),
+                        Some(event) => elements.push(self.read_value(event)?),
+                    }
+                }
+            }
+            Event::Key(_) | Event::EndObject | Event::EndArray => Err(self.unexpected("a value")),
+        }
+    }
+
+    /// JSON Pointer to the value most recently started, or to the member whose key was just read.
+    pub fn pointer(&self) -> String {
+        self.path(self.stack.len())
+    }
+
+    /// Byte range in the input of the token most recently read.
+    pub(crate) fn last_token_span(&self) -> Option<Range<usize>> {
+        self.last_token.as_ref().map(|token| token.offset..token.offset + token.original_text.len())
+    }
+
+    fn path(&self, depth: usize) -> String {
+        self.stack[..depth].iter().map(|frame| format!("/{}", frame.segment)).collect()
+    }
+
+    fn container(&self) -> Option<Container> {
+        self.stack.last().map(|frame| frame.container)
+    }
+
+    /// Comments skipped so far; only collected when `allow_comments` is set.
+    pub fn take_comments(&mut self) -> Vec<Comment> {
+        std::mem::take(&mut self.comments)
+    }
+
+    /// Problems recovered from so far, such as values replaced under `replace_invalid`
+    /// and keys repeated under `DuplicateKeys::Warn`.
+    pub fn take_warnings(&mut self) -> Vec<ParseError> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    fn step(&mut self) -> Result<Option<Event>, ParseError> {
+        match self.state {
+            State::Value => self.value().map(Some),
+            State::FirstKey if self.check(TokenType::RightBrace) => {
+                self.match_token(TokenType::RightBrace);
+                Ok(Some(self.end_container()))
+            }
+            State::FirstKey | State::Key => {
+                let key = self.key()?;
+                let quoted = key.token_type == TokenType::String;
+                let length = if quoted { key.original_text.len() - 2 } else { key.original_text.len() };
+                if let Some(limit) = self.options.max_key_length.filter(|&limit| length > limit) {
+                    let message = self.messages.key_too_long(length, limit, &self.path(self.stack.len() - 1));
+                    return Err(ParseError::at(ErrorKind::LimitExceeded, message, &key));
+                }
+                if self.match_token(TokenType::Colon).is_none() {
+                    return Err(self.unexpected(&format!("':' after object key {}", key.original_text)));
+                }
+                self.state = State::Value;
+                // Keys are still decoded when a value may need its pointer in an error.
+                let plain = self.options.normalize_key.is_none() && self.options.duplicate_keys == DuplicateKeys::Allow && self.options.max_value_bytes.is_none();
+                if self.skipping && plain {
+                    if quoted { self.check_string(&key)?; }
+                    return Ok(Some(Event::Key(String::new())));
+                }
+                let mut name = if quoted { self.string(&key)? } else { key.original_text.clone() };
+                if let Some(normalize) = self.options.normalize_key {
+                    name = normalize(&name);
+                }
+                if self.options.duplicate_keys != DuplicateKeys::Allow {
+                    self.check_duplicate(&name, &key)?;
+                }
+                if let Some(frame) = self.stack.last_mut() {
+                    frame.segment = escape_token(&name);
+                }
+                Ok(Some(Event::Key(name)))
+            }
+            State::FirstElement if self.check(TokenType::RightSquareBracket) => {
+                self.match_token(TokenType::RightSquareBracket);
+                Ok(Some(self.end_container()))
+            }
+            State::FirstElement => self.value().map(Some),
+            State::AfterValue => match self.container() {
+                None => {
+                    self.state = State::Done;
+                    if self.options.allow_trailing_data { return Ok(None); }
+                    if self.next_token.is_some() { return Err(self.unexpected("end of input")); }
+                    Ok(None)
+                }
+                Some(Container::Object) => {
+                    if self.match_token(TokenType::Comma).is_some() {
+                        self.state = if self.options.allow_trailing_commas { State::FirstKey } else { State::Key };
+                        return self.step();
+                    }
+                    if self.match_token(TokenType::RightBrace).is_none() { return Err(self.unexpected("',' or '}'")); }
+                    Ok(Some(self.end_container()))
+                }
+                Some(Container::Array) => {
+                    if self.match_token(TokenType::Comma).is_some() {
+                        self.state = if self.options.allow_trailing_commas { State::FirstElement } else { State::Value };
+                        return self.step();
+                    }
+                    if self.match_token(TokenType::RightSquareBracket).is_none() { return Err(self.unexpected("',' or ']'")); }
+                    Ok(Some(self.end_container()))
+                }
+            },
+            State::Done => Ok(None),
+        }
+    }
+
+    fn value(&mut self) -> Result<Event, ParseError> {
+        if let Some(token) = self.next_token.as_ref().filter(|token| is_container_start(token.token_type)) {
+            if self.stack.len() >= self.options.max_depth {
+                let message = self.messages.too_deep(self.options.max_depth);
+                return Err(ParseError::at(ErrorKind::TooDeep, message, token));
+            }
+        }
+        if let Some(frame) = self.stack.last_mut().filter(|frame| frame.container == Container::Array) {
+            frame.segment = frame.elements.to_string();
+            frame.elements += 1;
+        }
+        if self.match_token(TokenType::LeftBrace).is_some() {
+            self.stack.push(Frame::new(Container::Object));
+            self.state = State::FirstKey;
+            return Ok(Event::StartObject);
+        }
+        if self.match_token(TokenType::LeftSquareBracket).is_some() {
+            self.stack.push(Frame::new(Container::Array));
+            self.state = State::FirstElement;
+            return Ok(Event::StartArray);
+        }
+
+        let value = self.scalar()?;
+        self.state = State::AfterValue;
+        Ok(Event::Value(value))
+    }
+
+    fn scalar(&mut self) -> Result<JsonValue, ParseError> {
+        if let Some(token) = self.match_token(TokenType::String) {
+            if let Some(limit) = self.options.max_value_bytes.filter(|&limit| token.original_text.len() - 2 > limit) {
+                let message = self.messages.value_too_long(token.original_text.len() - 2, limit, &self.pointer());
+                return Err(ParseError::at(ErrorKind::LimitExceeded, message, &token));
+            }
+            if self.skipping {
+                self.check_string(&token)?;
+                return Ok(JsonValue::Null);
+            }
+            return Ok(JsonValue::String(self.string(&token)?));
+        }
+        if let Some(token) = self.match_token(TokenType::Number) {
+            // Checked even when skipping, as the policy may reject the number.
+            if has_leading_zeros(&token.original_text) {
+                let Some(text) = without_leading_zeros(&token.original_text, self.options.leading_zeros) else {
+                    let message = match self.options.leading_zeros {
+                        LeadingZeros::Octal => self.messages.invalid_octal(&token.original_text),
+                        _ => self.messages.leading_zeros(&token.original_text),
+                    };
+                    return Err(ParseError::at(ErrorKind::UnexpectedToken, message, &token));
+                };
+                if self.skipping { return Ok(JsonValue::Null); }
+                return self.number(&token, &text);
+            }
+            if self.skipping { return Ok(JsonValue::Null); }
+            return self.number(&token, &token.original_text);
+        }
+        if self.match_token(TokenType::True).is_some() { return Ok(JsonValue::Bool(true)); }
+        if self.match_token(TokenType::False).is_some() { return Ok(JsonValue::Bool(false)); }
+        if self.match_token(TokenType::Null).is_some() { return Ok(JsonValue::Null); }
+        if self.options.allow_nan {
+            let number = self.next_token.as_ref().filter(|token| token.token_type == TokenType::Other).and_then(|token| non_finite(&token.original_text));
+            if let Some(number) = number {
+                self.match_token(TokenType::Other);
+                return Ok(JsonValue::Number(number));
+            }
+        }
+        if self.options.replace_invalid {
+            if let Some(token) = self.match_token(TokenType::Other) {
+                let message = self.messages.replaced(&token.original_text);
+                self.warnings.push(ParseError::at(ErrorKind::UnexpectedToken, message, &token));
+                return Ok(JsonValue::Null);
+            }
+        }
+        match &self.next_token {
+            Some(token) if token.token_type == TokenType::Other && is_bareword(&token.original_text) => Err(ParseError::at(
+                ErrorKind::UnexpectedToken,
+                self.messages.bareword(&token.original_text),
+                token
+            )),
+            Some(token) if token.token_type == TokenType::Other && is_malformed_number(&token.original_text) => Err(ParseError::at(
+                ErrorKind::UnexpectedToken,
+                self.messages.invalid_number(&token.original_text),
+                token
+            )),
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    fn end_container(&mut self) -> Event {
+        self.state = State::AfterValue;
+        match self.stack.pop().map(|frame| frame.container) {
+            Some(Container::Array) => Event::EndArray,
+            _ => Event::EndObject,
+        }
+    }
+
+    fn check_duplicate(&mut self, name: &str, key: &Token) -> Result<(), ParseError> {
+        let depth = self.stack.len();
+        let Some(frame) = self.stack.last_mut() else { return Ok(()) };
+        let Some(&(line, column)) = frame.keys.get(name) else {
+            frame.keys.insert(name.to_string(), (key.line, key.column));
+            return Ok(());
+        };
+
+        let message = self.messages.duplicate_key(name, &self.path(depth - 1), line, column);
+        let error = ParseError::at(ErrorKind::DuplicateKey, message, key);
+        if self.options.duplicate_keys == DuplicateKeys::Error { return Err(error); }
+        self.warnings.push(error);
+        Ok(())
+    }
+
+    /// Parses `text`, the token's own text or what `without_leading_zeros` made of it.
+    fn number(&mut self, token: &Token, text: &str) -> Result<JsonValue, ParseError> {
+        let number = self.number_parser.parse_number(text).map_err(|message| {
+            ParseError::at(ErrorKind::UnexpectedToken, message, token)
+        })?;
+
+        if let JsonValue::Number(n) = number {
+            if n.is_infinite() && self.options.mode == Mode::Strict {
+                return Err(ParseError::at(ErrorKind::NumberOutOfRange, self.messages.number_out_of_range(&token.original_text), token));
+            }
+        }
+        Ok(number)
+    }
+
+    fn string(&self, token: &Token) -> Result<String, ParseError> {
+        let text = &token.original_text[1..token.original_text.len() - 1];
+        unescape(text, self.options.mode == Mode::Lenient, self.options.allow_single_quotes).map_err(|(index, problem)| string_error(token, index, self.messages.invalid_string(problem)))
+    }
+
+    /// Checks a string as far as can be done without decoding it, for values being skipped.
+    fn check_string(&self, token: &Token) -> Result<(), ParseError> {
+        let text = &token.original_text[1..token.original_text.len() - 1];
+        check_escapes(text, self.options.allow_single_quotes).map_err(|(index, problem)| string_error(token, index, self.messages.invalid_string(problem)))
+    }
+
+    fn check(&self, token_type: TokenType) -> bool {
+        self.next_token.as_ref().is_some_and(|token| token.token_type == token_type)
+    }
+
+    fn match_token(&mut self, token_type: TokenType) -> Option<Arc<Token>> {
+        if !self.check(token_type) { return None; }
+
+        let token = self.next_token.take();
+        self.advance();
+        self.last_token = token.clone();
+        token
+    }
+
+    fn advance(&mut self) {
+        self.next_token = self.lexer.next_token();
+        while let Some(token) = &self.next_token {
+            if token.token_type == TokenType::Comment && self.options.allow_comments {
+                self.comments.push(Comment::from(token.as_ref()));
+            } else if !(is_byte_order_mark(token) && self.options.mode == Mode::Lenient) {
+                break;
+            }
+            self.next_token = self.lexer.next_token();
+        }
+    }
+
+    /// Takes an object key: a string, or an identifier under `allow_unquoted_keys`. Words such
+    /// as `true` are identifiers too.
+    fn key(&mut self) -> Result<Arc<Token>, ParseError> {
+        let identifier = self.next_token.as_ref()
+            .filter(|token| self.options.allow_unquoted_keys && is_identifier(&token.original_text))
+            .map(|token| token.token_type);
+        if let Some(token) = identifier.and_then(|token_type| self.match_token(token_type)) {
+            return Ok(token);
+        }
+        self.expect(TokenType::String)
+    }
+
+    fn expect(&mut self, token_type: TokenType) -> Result<Arc<Token>, ParseError> {
+        match self.match_token(token_type) {
+            Some(token) => Ok(token),
+            None => Err(self.unexpected(token_type.describe()))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        match &self.next_token {
+            Some(token) if token.token_type == TokenType::Comment => {
+                ParseError::at(ErrorKind::UnexpectedToken, self.messages.unexpected(expected, Found::Comment), token)
+            }
+            Some(token) if is_byte_order_mark(token) => {
+                ParseError::at(ErrorKind::UnexpectedToken, self.messages.unexpected(expected, Found::ByteOrderMark), token)
+            }
+            Some(token) => ParseError::at(
+                ErrorKind::UnexpectedToken,
+                self.messages.unexpected(expected, Found::Token(&token.original_text)),
+                token
+            ),
+            None => {
+                // Right after the last token, or where the input ends if it was only whitespace.
+                let (line, column, offset) = match &self.last_token {
+                    Some(token) => (token.line, token.column + token.original_text.chars().count(), token.offset + token.original_text.len()),
+                    None => self.lexer.position()
+                };
+                ParseError::new(ErrorKind::UnexpectedEnd, self.messages.unexpected(expected, Found::EndOfInput), line, column, offset)
+            }
+        }
+    }
+}
+
+/// A byte order mark between tokens, as left where files that each start with one are concatenated.
+fn is_byte_order_mark(token: &Token) -> bool {
+    token.token_type == TokenType::Other && token.original_text == "\u{feff}"
+}
+
+fn is_container_start(token_type: TokenType) -> bool {
+    token_type == TokenType::LeftBrace || token_type == TokenType::LeftSquareBracket
+}
+
+/// Whether unrecognized text looks like a word someone forgot to quote, e.g. `hello` or `en_GB`.
+pub(crate) fn is_bareword(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_alphabetic() || c == '_') && text.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Whether `text` can be an unquoted key: letters, digits, `_` and `$`, not starting with a digit.
+fn is_identifier(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$') && text.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The number `NaN`, `Infinity` or `-Infinity` stands for, under `allow_nan`.
+fn non_finite(text: &str) -> Option<f64> {
+    match text {
+        "NaN" => Some(f64::NAN),
+        "Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+/// Whether unrecognized text starts like a number, e.g. `123abc`, `1.2.3` or `-`.
+pub(crate) fn is_malformed_number(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_digit() || c == '-')
+}
+
+/// Whether the integer part of a number token starts with a zero followed by more digits, as in
+/// `0755` or `-01.5`.
+pub(crate) fn has_leading_zeros(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text).as_bytes();
+    digits.len() > 1 && digits[0] == b'0' && digits[1].is_ascii_digit()
+}
+
+/// Rewrites a number with leading zeros as plain JSON the way `policy` reads it: the same digits
+/// without the zeros, or the value of the octal digits in decimal. `None` if the policy rejects it.
+pub(crate) fn without_leading_zeros(text: &str, policy: LeadingZeros) -> Option<String> {
+    let (sign, digits) = match text.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", text),
+    };
+    match policy {
+        LeadingZeros::Error => None,
+        LeadingZeros::Decimal => {
+            let rest = digits.trim_start_matches('0');
+            // One zero stays before a fraction or exponent, or when the number is zero.
+            let rest = if rest.starts_with(|c: char| c.is_ascii_digit()) { rest } else { &digits[digits.len() - rest.len() - 1..] };
+            Some(format!("{}{}", sign, rest))
+        }
+        LeadingZeros::Octal => u64::from_str_radix(digits, 8).ok().map(|value| format!("{}{}", sign, value)),
+    }
+}
+
+/// Decodes the escapes in the text between a string's quotes. `single_quotes` also accepts `\'`.
+pub(crate) fn unescape(text: &str, lenient: bool, single_quotes: bool) -> Result<String, (usize, StringProblem)> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        if c != '\\' {
+            if (c as u32) < 0x20 { return Err((index, StringProblem::UnescapedControl(c as u32))); }
+            result.push(c);
+            continue;
+        }
+
+        match chars.next().map(|(_, escaped)| escaped) {
+            Some('"') => result.push('"'),
+            Some('\'') if single_quotes => result.push('\''),
+            Some('\\') => result.push('\\'),
+            Some('/') => result.push('/'),
+            Some('b') => result.push('\u{8}'),
+            Some('f') => result.push('\u{c}'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('u') => {
+                let high = hex_escape(&mut chars).ok_or((index, StringProblem::InvalidUnicodeEscape))?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    let mut lookahead = chars.clone();
+                    let low = match (lookahead.next(), lookahead.next()) {
+                        (Some((_, '\\')), Some((_, 'u'))) => hex_escape(&mut lookahead).filter(|low| (0xDC00..0xE000).contains(low)),
+                        _ => None
+                    };
+                    match low {
+                        Some(low) => {
+                            chars = lookahead;
+                            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                        }
+                        None => high
+                    }
+                } else {
+                    high
+                };
+
+                match char::from_u32(code) {
+                    Some(c) => result.push(c),
+                    None if lenient => result.push(char::REPLACEMENT_CHARACTER),
+                    None => return Err((index, StringProblem::UnpairedSurrogate))
+                }
+            }
+            Some(other) => return Err((index, StringProblem::InvalidEscape(other))),
+            None => return Err((index, StringProblem::UnterminatedEscape))
+        }
+    }
+
+    Ok(result)
+}
+
+/// The checks `unescape` makes that need no decoding: control characters are escaped and each
+/// escape is well formed. Surrogates in `\u` escapes are not checked to be paired.
+fn check_escapes(text: &str, single_quotes: bool) -> Result<(), (usize, StringProblem)> {
+    let bytes = text.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\\' => match bytes.get(index + 1) {
+                Some(b'"' | b'\\' | b'/' | b'b' | b'f' | b'n' | b'r' | b't') => index += 2,
+                Some(b'\'') if single_quotes => index += 2,
+                Some(b'u') if bytes.get(index + 2..index + 6).is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) => index += 6,
+                Some(b'u') => return Err((index, StringProblem::InvalidUnicodeEscape)),
+                Some(_) => return Err((index, StringProblem::InvalidEscape(text[index + 1..].chars().next().unwrap_or_default()))),
+                None => return Err((index, StringProblem::UnterminatedEscape)),
+            },
+            c if c < 0x20 => return Err((index, StringProblem::UnescapedControl(c as u32))),
+            _ => index += 1,
+        }
+    }
+    Ok(())
+}
+
+fn string_error(token: &Token, index: usize, message: String) -> ParseError {
+    let text = &token.original_text[1..];
+    let column = token.column + 1 + text[..index].chars().count();
+    ParseError::new(ErrorKind::InvalidString, message, token.line, column, token.offset + 1 + index)
+}
+
+fn hex_escape(chars: &mut impl Iterator<Item = (usize, char)>) -> Option<u32> {
+    let mut code = 0;
+    for _ in 0..4 {
+        code = code * 16 + chars.next()?.1.to_digit(16)?;
+    }
+    Some(code)
+}
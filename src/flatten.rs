@@ -0,0 +1,142 @@
+use crate::value::JsonValue;
+
+/// Turns a document into an object with one member per leaf: scalars, and empty objects and
+/// arrays, which are kept as `{}` and `[]`. Member names are joined with `separator` and array
+/// indices are written in brackets, so `{"a": {"b": [1, {}]}}` becomes
+/// `{"a.b[0]": 1, "a.b[1]": {}}`. A scalar root becomes the only member, named `""`.
+///
+/// `unflatten` reverses this, unless a member name is empty, contains `separator`, or looks
+/// like an index such as `[0]`.
+pub fn flatten(value: &JsonValue, separator: &str) -> JsonValue {
+    let mut members = Vec::new();
+    leaves(value, &mut String::new(), separator, &mut members);
+    JsonValue::Object(members)
+}
+
+fn leaves(value: &JsonValue, path: &mut String, separator: &str, members: &mut Vec<(String, JsonValue)>) {
+    let length = path.len();
+    match value {
+        JsonValue::Object(object) if !object.is_empty() => {
+            for (key, member) in object {
+                if length > 0 { path.push_str(separator); }
+                path.push_str(key);
+                leaves(member, path, separator, members);
+                path.truncate(length);
+            }
+        }
+        JsonValue::Array(elements) if !elements.is_empty() => {
+            for (index, element) in elements.iter().enumerate() {
+                path.push_str(&format!("[{}]", index));
+                leaves(element, path, separator, members);
+                path.truncate(length);
+            }
+        }
+        _ => members.push((path.clone(), value.clone())),
+    }
+}
+
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Rebuilds a document from an object written by `flatten` with the same `separator`. Missing
+/// array elements are filled with `null`. Fails if `value` is not an object, or if two member
+/// names disagree about what is at a path, as with `"a": 1` and `"a.b": 2`.
+pub fn unflatten(value: &JsonValue, separator: &str) -> Result<JsonValue, String> {
+    let JsonValue::Object(members) = value else {
+        return Err("only an object can be unflattened".to_string());
+    };
+
+    let mut root = Tree::Unset;
+    for (key, member) in members {
+        let mut current = &mut root;
+        for segment in segments(key, separator) {
+            current = match segment {
+                Segment::Key(name) => current.member(name),
+                Segment::Index(index) => current.element(index),
+            }
+            .ok_or_else(|| format!("'{}' conflicts with an earlier key", key))?;
+        }
+        if !matches!(current, Tree::Unset) {
+            return Err(format!("'{}' conflicts with an earlier key", key));
+        }
+        *current = Tree::Leaf(member.clone());
+    }
+    Ok(root.into_value())
+}
+
+/// Splits a flattened member name into the member names and array indices of its path.
+fn segments<'a>(key: &'a str, separator: &str) -> Vec<Segment<'a>> {
+    let mut segments = Vec::new();
+    let (mut rest, mut first) = (key, true);
+    while !rest.is_empty() {
+        let end = (0..=rest.len())
+            .filter(|&at| rest.is_char_boundary(at))
+            .find(|&at| (!separator.is_empty() && rest[at..].starts_with(separator)) || index(&rest[at..]).is_some())
+            .unwrap_or(rest.len());
+        // A name only comes first when the root is an object.
+        if end > 0 || !first {
+            segments.push(Segment::Key(&rest[..end]));
+        }
+        first = false;
+        rest = &rest[end..];
+        while let Some((at, length)) = index(rest) {
+            segments.push(Segment::Index(at));
+            rest = &rest[length..];
+        }
+        if !separator.is_empty() {
+            rest = rest.strip_prefix(separator).unwrap_or(rest);
+        }
+    }
+    segments
+}
+
+/// Reads an index such as `[12]` at the start of `text`, returning it and the length it takes up.
+fn index(text: &str) -> Option<(usize, usize)> {
+    let digits = text.strip_prefix('[')?;
+    let length = digits.find(|c: char| !c.is_ascii_digit())?;
+    if length == 0 || !digits[length..].starts_with(']') { return None; }
+    Some((digits[..length].parse().ok()?, length + 2))
+}
+
+/// A document being rebuilt, telling apart the places no member name has reached yet.
+enum Tree {
+    Unset,
+    Leaf(JsonValue),
+    Object(Vec<(String, Tree)>),
+    Array(Vec<Tree>),
+}
+
+impl Tree {
+    fn member(&mut self, name: &str) -> Option<&mut Tree> {
+        if matches!(self, Tree::Unset) { *self = Tree::Object(Vec::new()); }
+        let Tree::Object(members) = self else { return None };
+        let index = match members.iter().position(|(key, _)| key == name) {
+            Some(index) => index,
+            None => {
+                members.push((name.to_string(), Tree::Unset));
+                members.len() - 1
+            }
+        };
+        Some(&mut members[index].1)
+    }
+
+    fn element(&mut self, index: usize) -> Option<&mut Tree> {
+        if matches!(self, Tree::Unset) { *self = Tree::Array(Vec::new()); }
+        let Tree::Array(elements) = self else { return None };
+        while elements.len() <= index {
+            elements.push(Tree::Unset);
+        }
+        Some(&mut elements[index])
+    }
+
+    fn into_value(self) -> JsonValue {
+        match self {
+            Tree::Unset => JsonValue::Null,
+            Tree::Leaf(value) => value,
+            Tree::Object(members) => JsonValue::Object(members.into_iter().map(|(key, tree)| (key, tree.into_value())).collect()),
+            Tree::Array(elements) => JsonValue::Array(elements.into_iter().map(Tree::into_value).collect()),
+        }
+    }
+}
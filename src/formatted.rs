@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::ops::Range;
+
+use crate::error::ParseError;
+use crate::events::{Event, EventReader};
+use crate::lexer::Lexer;
+use crate::parser::ParseOptions;
+use crate::pointer::{escape_token, parse_pointer};
+use crate::value::JsonValue;
+
+/// A document kept as the text it was read from, so that values can be replaced without touching
+/// the whitespace and comments around them: after `set`, the text is the same byte for byte except
+/// where the replaced value was. New values are written compactly.
+#[derive(Debug, Clone)]
+pub struct FormattedDocument {
+    text: String,
+    options: ParseOptions,
+}
+
+impl FormattedDocument {
+    /// Checks that `text` holds a document `options` accept, and keeps it.
+    pub fn parse(text: String, options: ParseOptions) -> Result<FormattedDocument, ParseError> {
+        let document = FormattedDocument { text, options };
+        let mut reader = document.reader();
+        while reader.next_event()?.is_some() {}
+        Ok(document)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    /// The document as a value, without its formatting.
+    pub fn value(&self) -> JsonValue {
+        let mut reader = self.reader();
+        match reader.next_event() {
+            Ok(Some(event)) => reader.read_value(event).unwrap_or(JsonValue::Null),
+            _ => JsonValue::Null,
+        }
+    }
+
+    /// Byte range in the text of the value at `pointer`, from its first character to its last.
+    /// When an object has the key more than once, the first is found.
+    pub fn span(&self, pointer: &str) -> Result<Range<usize>, String> {
+        let wanted: String = parse_pointer(pointer)?.iter().map(|token| format!("/{}", escape_token(token))).collect();
+        let mut reader = self.reader();
+        let not_found = || format!("pointer not found: '{}'", pointer);
+        // The document was read whole when it was made, so it cannot fail to read now.
+        while let Some(event) = reader.next_event().map_err(|_| not_found())? {
+            // Just after a container starts, the reader's pointer already has an empty segment for
+            // what it will hold.
+            let found = match event {
+                Event::Value(_) => reader.pointer() == wanted,
+                Event::StartObject | Event::StartArray => reader.pointer().strip_suffix('/') == Some(&wanted),
+                Event::Key(_) | Event::EndObject | Event::EndArray => false,
+            };
+            if !found { continue; }
+            let start = reader.last_token_span().ok_or_else(not_found)?.start;
+            reader.read_value(event).map_err(|_| not_found())?;
+            return Ok(start..reader.last_token_span().ok_or_else(not_found)?.end);
+        }
+        Err(not_found())
+    }
+
+    /// Replaces the value at `pointer`, which must already exist, with `value`.
+    pub fn set(&mut self, pointer: &str, value: &JsonValue) -> Result<(), String> {
+        let span = self.span(pointer)?;
+        self.text.replace_range(span, &value.to_string());
+        Ok(())
+    }
+
+    fn reader(&self) -> EventReader {
+        EventReader::with_options(Lexer::new(Box::new(Cursor::new(self.text.clone()))), self.options.clone())
+    }
+}
+
+/// Parses `input` and, in the same pass, finds the span of every value in it: a map from the JSON
+/// Pointer of each value, the root's being `""`, to its byte range from its first character to its
+/// last, as `FormattedDocument::span` gives. When an object has a key more than once, the span is
+/// that of the first.
+pub fn parse_with_spans(input: &str, options: &ParseOptions) -> Result<(JsonValue, HashMap<String, Range<usize>>), ParseError> {
+    let mut reader = EventReader::with_options(Lexer::new(Box::new(Cursor::new(input.to_string()))), options.clone());
+    let mut spans = HashMap::new();
+    let value = match reader.next_event()? {
+        Some(event) => read_spanned(&mut reader, event, &mut spans)?,
+        None => JsonValue::Null,
+    };
+    // Rejects anything after the root, as parsing does.
+    reader.next_event()?;
+    Ok((value, spans))
+}
+
+/// `EventReader::read_value`, also recording the spans of the value `event` starts and of
+/// everything in it.
+fn read_spanned(reader: &mut EventReader, event: Event, spans: &mut HashMap<String, Range<usize>>) -> Result<JsonValue, ParseError> {
+    let pointer = match event {
+        Event::StartObject | Event::StartArray => reader.pointer().strip_suffix('/').unwrap_or_default().to_string(),
+        _ => reader.pointer(),
+    };
+    let start = reader.last_token_span().map_or(0, |span| span.start);
+    let value = match event {
+        Event::StartObject => {
+            let mut members = Vec::new();
+            while let Some(Event::Key(key)) = reader.next_event()? {
+                let Some(event) = reader.next_event()? else { break };
+                members.push((key, read_spanned(reader, event, spans)?));
+            }
+            JsonValue::Object(members)
+        }
+        Event::StartArray => {
+            let mut elements = Vec::new();
+            loop {
+                match reader.next_event()? {
+                    Some(Event::EndArray) | None => break,
+                    Some(event) => elements.push(read_spanned(reader, event, spans)?),
+                }
+            }
+            JsonValue::Array(elements)
+        }
+        event => reader.read_value(event)?,
+    };
+    let end = reader.last_token_span().map_or(start, |span| span.end);
+    spans.entry(pointer).or_insert(start..end);
+    Ok(value)
+}
@@ -0,0 +1,58 @@
+//! Reading values framed as `<length>\n<json>`: the byte length of the JSON text in decimal on a
+//! line of its own, then exactly that many bytes, as some wire protocols send them.
+
+use std::io::{BufRead, Cursor, Read};
+
+use crate::error::{ErrorKind, ParseError};
+use crate::lexer::Lexer;
+use crate::parser::{ParseOptions, SyntaxAnalyser};
+use crate::value::JsonValue;
+
+/// Digits in the longest length prefix accepted, enough for any `u64`.
+const MAX_DIGITS: usize = 20;
+
+/// Bytes reserved up front for a frame's text; a larger frame grows as it is read, so a bogus
+/// length cannot make it allocate more than the input holds.
+const INITIAL_CAPACITY: usize = 64 * 1024;
+
+/// Reads one framed value and leaves `reader` at the start of the next frame, or returns `None`
+/// when the input ends between frames. The line ending after the length may be `\r\n`.
+///
+/// A length that is not all digits, or text shorter than its length, is an `ErrorKind::InvalidFrame`
+/// error. Errors in the text itself are placed relative to the start of the text, not the frame.
+pub fn read_framed<R: BufRead>(reader: &mut R, options: &ParseOptions) -> Result<Option<JsonValue>, ParseError> {
+    let mut prefix = Vec::new();
+    reader.by_ref().take(MAX_DIGITS as u64 + 2).read_until(b'\n', &mut prefix)?;
+    if prefix.is_empty() { return Ok(None); }
+
+    let Some(digits) = prefix.strip_suffix(b"\n").map(|line| line.strip_suffix(b"\r").unwrap_or(line)) else {
+        let message = match prefix.len() > MAX_DIGITS {
+            true => format!("length prefix longer than {} digits", MAX_DIGITS),
+            false => "input ends inside a length prefix".to_string(),
+        };
+        return Err(frame_error(message, prefix.len()));
+    };
+    if let Some(index) = digits.iter().position(|byte| !byte.is_ascii_digit()) {
+        let message = format!("invalid length prefix '{}': expected decimal digits", String::from_utf8_lossy(digits).escape_debug());
+        return Err(frame_error(message, index));
+    }
+    let length: u64 = std::str::from_utf8(digits).ok().and_then(|digits| digits.parse().ok())
+        .ok_or_else(|| frame_error("empty or oversized length prefix".to_string(), 0))?;
+
+    let mut text = Vec::with_capacity(INITIAL_CAPACITY.min(length as usize));
+    reader.by_ref().take(length).read_to_end(&mut text)?;
+    if (text.len() as u64) < length {
+        let message = format!("input ends {} bytes into a frame of {}", text.len(), length);
+        return Err(frame_error(message, prefix.len() + text.len()));
+    }
+
+    let text = String::from_utf8(text).map_err(|error| {
+        let offset = error.utf8_error().valid_up_to();
+        ParseError::new(ErrorKind::InvalidUtf8, "invalid UTF-8 in a frame".to_string(), 1, 1, offset)
+    })?;
+    SyntaxAnalyser::with_options(Lexer::new(Box::new(Cursor::new(text))), options.clone()).parse().map(Some)
+}
+
+fn frame_error(message: String, offset: usize) -> ParseError {
+    ParseError::new(ErrorKind::InvalidFrame, message, 1, offset + 1, offset)
+}
@@ -0,0 +1,134 @@
+use crate::serializer::to_string;
+use crate::value::JsonValue;
+
+/// The characters `Generator` builds strings and keys from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Letters, digits and spaces.
+    #[default]
+    Ascii,
+    /// Text from several scripts, with characters of every UTF-8 length, emoji included.
+    Unicode,
+    /// Characters that parsers and the code behind them get wrong: quotes, backslashes, control
+    /// characters, NUL, byte order marks, line and paragraph separators, bidirectional overrides
+    /// and combining marks.
+    Hostile,
+}
+
+const ASCII: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+const UNICODE: &str = "aé ßøΩжשعक中文日本語한국🙂😀🎉𝄞";
+const HOSTILE: &str = "\"\\/\u{0}\u{1}\u{8}\t\n\r\u{1f}\u{7f}\u{a0}\u{feff}\u{2028}\u{2029}\u{202e}\u{200b}\u{301}\u{fffd}\u{10ffff}'<>&{}[]:,";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerateOptions {
+    /// Containers nested inside one another, at most.
+    pub max_depth: usize,
+    /// Members or elements of each container, at most.
+    pub max_children: usize,
+    pub alphabet: Alphabet,
+    /// Roughly how long each document is written compactly. The root is then an array of as many
+    /// values as it takes, however many `max_children` allows.
+    pub target_size: Option<usize>,
+}
+
+impl Default for GenerateOptions {
+    fn default() -> GenerateOptions {
+        GenerateOptions { max_depth: 5, max_children: 8, alphabet: Alphabet::Ascii, target_size: None }
+    }
+}
+
+/// Makes random documents for tests and load testing. The same seed and options always give
+/// the same documents, on every platform, so a seed is enough to reproduce a failure.
+pub struct Generator {
+    state: u64,
+    options: GenerateOptions,
+    alphabet: Vec<char>,
+}
+
+impl Generator {
+    pub fn new(seed: u64, options: GenerateOptions) -> Generator {
+        let characters = match options.alphabet {
+            Alphabet::Ascii => ASCII,
+            Alphabet::Unicode => UNICODE,
+            Alphabet::Hostile => HOSTILE,
+        };
+        Generator { state: seed, options, alphabet: characters.chars().collect() }
+    }
+
+    /// The next document.
+    pub fn document(&mut self) -> JsonValue {
+        let Some(target) = self.options.target_size else {
+            return self.value(0);
+        };
+        let (mut elements, mut size) = (Vec::new(), 2);
+        while size < target {
+            let element = self.value(1);
+            size += to_string(&element).len() + 1;
+            elements.push(element);
+        }
+        JsonValue::Array(elements)
+    }
+
+    fn value(&mut self, depth: usize) -> JsonValue {
+        // Containers grow less likely with depth, so documents do not all reach the limit.
+        let containers = if depth < self.options.max_depth { 6 - 5 * depth / self.options.max_depth.max(1) } else { 0 };
+        // The root is a container whenever it can be, as most documents are.
+        let choice = match depth == 0 && containers > 0 {
+            true => 6 + self.below(containers as u64),
+            false => self.below(6 + containers as u64),
+        };
+        match choice {
+            0 => JsonValue::Null,
+            1 => JsonValue::Bool(self.below(2) == 1),
+            2 | 3 => JsonValue::Number(self.number()),
+            4 | 5 => JsonValue::String(self.string(16)),
+            _ if choice % 2 == 0 => {
+                let mut members: Vec<(String, JsonValue)> = Vec::new();
+                for _ in 0..self.children(depth) {
+                    let mut key = self.string(8);
+                    // Keys are kept distinct, so that any parser reads the same document.
+                    while members.iter().any(|(name, _)| *name == key) {
+                        key.push(self.alphabet[0]);
+                    }
+                    let value = self.value(depth + 1);
+                    members.push((key, value));
+                }
+                JsonValue::Object(members)
+            }
+            _ => JsonValue::Array((0..self.children(depth)).map(|_| self.value(depth + 1)).collect()),
+        }
+    }
+
+    /// How many members or elements a container gets; the root is never empty unless it has to be.
+    fn children(&mut self, depth: usize) -> u64 {
+        let most = self.options.max_children as u64;
+        if depth == 0 && most > 0 { 1 + self.below(most) } else { self.below(most + 1) }
+    }
+
+    /// Integers, and decimals with up to three places, so they are read back exactly.
+    fn number(&mut self) -> f64 {
+        let integer = self.below(2_000_001) as f64 - 1_000_000.0;
+        match self.below(3) {
+            0 => integer / 1000.0,
+            _ => integer,
+        }
+    }
+
+    fn string(&mut self, max_length: u64) -> String {
+        let mut string = String::new();
+        for _ in 0..self.below(max_length + 1) {
+            let index = self.below(self.alphabet.len() as u64) as usize;
+            string.push(self.alphabet[index]);
+        }
+        string
+    }
+
+    /// A number below `bound`, from SplitMix64.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) % bound.max(1)
+    }
+}
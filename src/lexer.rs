@@ -0,0 +1,581 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
+use std::sync::Arc;
+
+use crate::error::{ErrorKind, ParseError};
+use crate::parser::InvalidUtf8;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum TokenType {
+    LeftBrace,
+    RightBrace,
+    String,
+    Number,
+    True,
+    False,
+    Null,
+    Colon,
+    Comma,
+    LeftSquareBracket,
+    RightSquareBracket,
+    Comment,
+    Other
+}
+
+impl TokenType {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            TokenType::LeftBrace => "'{'",
+            TokenType::RightBrace => "'}'",
+            TokenType::String => "a string",
+            TokenType::Number => "a number",
+            TokenType::True => "'true'",
+            TokenType::False => "'false'",
+            TokenType::Null => "'null'",
+            TokenType::Colon => "':'",
+            TokenType::Comma => "','",
+            TokenType::LeftSquareBracket => "'['",
+            TokenType::RightSquareBracket => "']'",
+            TokenType::Comment => "a comment",
+            TokenType::Other => "an unknown token"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub(crate) token_type: TokenType,
+    pub(crate) original_text: String,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) offset: usize,
+}
+
+impl Token {
+    fn new(token_type: TokenType, original_text: String, line: usize, column: usize, offset: usize) -> Token {
+        Token {
+            token_type,
+            original_text,
+            line,
+            column,
+            offset,
+        }
+    }
+
+    pub fn token_type(&self) -> TokenType {
+        self.token_type
+    }
+
+    /// The token as written in the input, quotes and escapes included.
+    pub fn text(&self) -> &str {
+        &self.original_text
+    }
+
+    /// Line and column where the token starts, counting characters from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Bytes from the start of the input to the token.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// Splits `input` into its tokens, comments included, without checking that they make up a
+/// document: text that is not a JSON token becomes `TokenType::Other`. Useful on its own for
+/// syntax highlighting.
+pub fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut lexer = Lexer::new(Box::new(std::io::Cursor::new(input.to_string())));
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next_token() {
+        tokens.push(token.as_ref().clone());
+    }
+    match lexer.take_error() {
+        Some(error) => Err(error),
+        None => Ok(tokens),
+    }
+}
+
+/// A `//` or `/* */` comment, with its delimiters, and where it starts in the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comment {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+    pub offset: usize,
+}
+
+impl From<&Token> for Comment {
+    fn from(token: &Token) -> Comment {
+        Comment {
+            text: token.original_text.clone(),
+            line: token.line,
+            column: token.column,
+            offset: token.offset,
+        }
+    }
+}
+
+pub struct Lexer {
+    buf_reader: Box<dyn BufRead>,
+    tokens: Vec<Arc<Token>>,
+    /// `None` until the first token is asked for, so that nothing is read before the options are set.
+    current_line: Option<String>,
+    /// The line before `current_line`, to place the end of an input without a final newline.
+    previous_line: Option<String>,
+    current_char: Option<char>,
+    current_offset: usize,
+    current_line_number: usize,
+    line_offset: usize,
+    /// Bytes at the start of the current line whose characters have been counted, and how many
+    /// characters they were, so columns cost no more than scanning on long lines.
+    counted: (usize, usize),
+    start: usize,
+    current_token: usize,
+    keywords: HashMap<String, TokenType>,
+    invalid_utf8: InvalidUtf8,
+    max_bytes: Option<usize>,
+    max_tokens: Option<usize>,
+    single_quotes: bool,
+    identifiers: bool,
+    /// Whether `read_line_counting` can still count tokens without scanning them.
+    counting: bool,
+    /// Tokens scanned so far, comments included.
+    scanned: usize,
+    error: Option<ParseError>
+}
+
+impl Lexer {
+    pub fn new(buf_reader: Box<dyn BufRead>) -> Lexer {
+        let mut map = HashMap::new();
+        map.insert("true".to_string(), TokenType::True);
+        map.insert("false".to_string(), TokenType::False);
+        map.insert("null".to_string(), TokenType::Null);
+
+        Lexer {
+            buf_reader,
+            tokens: vec![],
+            current_line: None,
+            previous_line: None,
+            current_char: None,
+            current_offset: 0,
+            current_line_number: 0,
+            line_offset: 0,
+            counted: (0, 0),
+            start: 0,
+            current_token: 0,
+            keywords: map,
+            invalid_utf8: InvalidUtf8::Error,
+            max_bytes: None,
+            max_tokens: None,
+            single_quotes: false,
+            identifiers: false,
+            counting: true,
+            scanned: 0,
+            error: None
+        }
+    }
+
+    pub fn with_invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> Lexer {
+        self.invalid_utf8 = invalid_utf8;
+        self
+    }
+
+    /// Stops reading, as at the end of the input but with an `ErrorKind::LimitExceeded` error,
+    /// once more than `max_bytes` bytes have been read or more than `max_tokens` tokens scanned.
+    /// A line is read no further than the byte limit, so memory use stays within it.
+    pub fn with_limits(mut self, max_bytes: Option<usize>, max_tokens: Option<usize>) -> Lexer {
+        self.max_bytes = max_bytes;
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Scans text in single quotes as a string, for `ParseOptions::allow_single_quotes`.
+    pub fn with_single_quotes(mut self, single_quotes: bool) -> Lexer {
+        self.single_quotes = single_quotes;
+        self
+    }
+
+    /// Scans a word such as `max_size2` or `$id` as one token, for `ParseOptions::allow_unquoted_keys`.
+    /// Otherwise a word ends at the first character that is not a letter.
+    pub fn with_identifiers(mut self, identifiers: bool) -> Lexer {
+        self.identifiers = identifiers;
+        self
+    }
+
+    /// Starts over on a new input, keeping the token buffer and keyword table already allocated,
+    /// so one lexer can read many documents in a loop.
+    pub fn reset(&mut self, buf_reader: Box<dyn BufRead>) {
+        self.buf_reader = buf_reader;
+        self.tokens.clear();
+        self.current_char = None;
+        self.current_offset = 0;
+        self.current_line_number = 0;
+        self.line_offset = 0;
+        self.counted = (0, 0);
+        self.start = 0;
+        self.current_token = 0;
+        self.scanned = 0;
+        self.counting = true;
+        self.error = None;
+        self.previous_line = None;
+        self.current_line = None;
+    }
+
+    /// Reads the line numbered `current_line_number`, or returns an empty one, which the scanner
+    /// takes as the end of input, after recording why it could not.
+    /// Lines are decoded only once all their bytes are in, so a character split across reads
+    /// of the underlying reader is never decoded in halves.
+    fn read_line(&mut self) -> String {
+        let mut bytes = Vec::new();
+        let remaining = self.max_bytes.map_or(u64::MAX, |limit| limit.saturating_sub(self.line_offset) as u64 + 1);
+        let mut reader = self.buf_reader.as_mut().take(remaining);
+        let read = match self.max_tokens {
+            Some(limit) => read_line_counting(&mut reader, &mut bytes, limit.saturating_sub(self.scanned), &mut self.counting),
+            None => reader.read_until(b'\n', &mut bytes).map(|_| None),
+        };
+        match read {
+            Ok(None) => { }
+            Ok(Some(index)) => {
+                let limit = self.max_tokens.unwrap_or_default();
+                let message = format!("more than {} tokens", limit);
+                self.error = Some(ParseError::new(ErrorKind::LimitExceeded, message, self.current_line_number + 1, index + 1, self.line_offset + index));
+                return String::new();
+            }
+            // Text the reader could not decode, such as UTF-16 with an unpaired surrogate. What it
+            // decoded before that is in `bytes`, so the error is placed just after it.
+            Err(error) if error.kind() == std::io::ErrorKind::InvalidData => {
+                let column = String::from_utf8_lossy(&bytes).chars().count() + 1;
+                self.error = Some(ParseError::new(ErrorKind::InvalidEncoding, error.to_string(), self.current_line_number + 1, column, self.line_offset + bytes.len()));
+                return String::new();
+            }
+            Err(error) => {
+                self.error = Some(error.into());
+                return String::new();
+            }
+        }
+        if let Some(limit) = self.max_bytes.filter(|&limit| self.line_offset + bytes.len() > limit) {
+            self.error = Some(too_many_bytes(limit, self.current_line_number + 1, limit - self.line_offset + 1));
+            return String::new();
+        }
+        let error = match String::from_utf8(bytes) {
+            Ok(line) => return line,
+            Err(error) => error,
+        };
+        if self.invalid_utf8 == InvalidUtf8::Replace {
+            return String::from_utf8_lossy(error.as_bytes()).into_owned();
+        }
+
+        let valid = error.utf8_error().valid_up_to();
+        let bytes = error.into_bytes();
+        let column = std::str::from_utf8(&bytes[..valid]).map_or(0, |prefix| prefix.chars().count()) + 1;
+        // Only the description: the event reader words the message around it.
+        let message = describe_invalid_utf8(&bytes[valid..]);
+        self.error = Some(ParseError::new(ErrorKind::InvalidUtf8, message, self.current_line_number + 1, column, self.line_offset + valid));
+        String::new()
+    }
+
+    fn next_character(&mut self) {
+        if let Some(line) = &self.current_line {
+            if self.current_offset >= line.len() {
+                self.line_offset += line.len();
+                self.counted = (0, 0);
+                self.current_line_number += 1;
+                let next = self.read_line();
+                self.previous_line = self.current_line.replace(next);
+                self.current_char = Some('\n');
+                self.current_offset = 0;
+            } else {
+                self.current_char = line[self.current_offset..].chars().next();
+                self.current_offset += self.current_char.map_or(1, |c| c.len_utf8());
+            }
+        } else {
+            self.current_char = None;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        if let Some(line) = &self.current_line {
+            if self.current_offset >= line.len() {
+                Some('\n')
+            } else {
+                line[self.current_offset..].chars().next()
+            }
+        } else {
+            None
+        }
+    }
+
+    /// Line, column and byte offset of the token being scanned.
+    fn start_position(&mut self) -> (usize, usize, usize) {
+        let (mut bytes, mut characters) = self.counted;
+        if self.start < bytes { (bytes, characters) = (0, 0); }
+        if let Some(line) = &self.current_line {
+            characters += line[bytes..self.start].chars().count();
+            bytes = self.start;
+        }
+        self.counted = (bytes, characters);
+        (self.current_line_number + 1, characters + 1, self.line_offset + self.start)
+    }
+
+    /// Line, column and byte offset scanning has reached, such as the end of the input.
+    pub(crate) fn position(&self) -> (usize, usize, usize) {
+        if let (true, Some(previous)) = (self.at_end(), &self.previous_line) {
+            if !previous.ends_with('\n') {
+                return (self.current_line_number, previous.chars().count() + 1, self.line_offset);
+            }
+        }
+        let characters = self.current_line.as_ref().map_or(0, |line| line[..self.current_offset.min(line.len())].chars().count());
+        (self.current_line_number + 1, characters + 1, self.line_offset + self.current_offset)
+    }
+
+    fn add_token(&mut self, token_type: TokenType) {
+        if let Some(line) = &self.current_line {
+            let text = line[self.start..self.current_offset].to_string();
+            let (line, column, offset) = self.start_position();
+            self.tokens.push(Arc::new(Token::new(token_type, text, line, column, offset)))
+        }
+    }
+
+    fn line_length(&self) -> usize {
+        self.current_line.as_ref().map_or(0, |line| line.len())
+    }
+
+    fn string(&mut self, quote: char) {
+        while self.current_offset < self.line_length() {
+            self.next_character();
+            match self.current_char {
+                Some(c) if c == quote => {
+                    self.add_token(TokenType::String);
+                    return;
+                }
+                Some('\\') if self.current_offset < self.line_length() => { self.next_character(); }
+                _ => { }
+            }
+        }
+        self.add_token(TokenType::Other);
+    }
+
+    fn comment(&mut self) {
+        match self.peek() {
+            Some('/') => {
+                while self.peek().is_some_and(|c| c != '\n' && c != '\r') {
+                    self.next_character();
+                }
+                self.add_token(TokenType::Comment);
+            }
+            Some('*') => self.block_comment(),
+            _ => self.add_token(TokenType::Other),
+        }
+    }
+
+    // Block comments may span lines, so unlike other tokens their text is collected as it is read.
+    fn block_comment(&mut self) {
+        let (line, column, offset) = self.start_position();
+        let mut text = String::from("/");
+
+        while !self.at_end() {
+            let next_line = self.current_offset >= self.line_length();
+            self.next_character();
+            if next_line { continue; }
+
+            let Some(c) = self.current_char else { break };
+            text.push(c);
+            if c == '*' && self.peek() == Some('/') {
+                self.next_character();
+                text.push('/');
+                self.tokens.push(Arc::new(Token::new(TokenType::Comment, text, line, column, offset)));
+                return;
+            }
+        }
+
+        self.tokens.push(Arc::new(Token::new(TokenType::Other, text, line, column, offset)));
+    }
+
+    fn next_num(&mut self) -> bool {
+        let mut found = false;
+        while self.peek().is_some_and(|x| x.is_ascii_digit()) {
+            self.next_character();
+            found = true;
+        }
+        found
+    }
+
+    fn number(&mut self) {
+        let mut valid = true;
+
+        if self.current_char == Some('-') {
+            valid = self.next_num();
+        } else {
+            self.next_num();
+        }
+
+        if self.peek() == Some('.') {
+            self.next_character();
+            valid &= self.next_num();
+        }
+
+        if self.peek().is_some_and(|x| x == 'e' || x == 'E') {
+            self.next_character();
+            if self.peek().is_some_and(|x| x == '+' || x == '-') {
+                self.next_character();
+            }
+            valid &= self.next_num();
+        }
+
+        // Letters, digits or dots run straight on, as in `123abc` or `1.2.3`, make the whole run
+        // one malformed token rather than a number followed by something else.
+        while self.peek().is_some_and(|x| x.is_alphanumeric() || x == '_' || x == '.') {
+            self.next_character();
+            valid = false;
+        }
+
+        self.add_token(if valid { TokenType::Number } else { TokenType::Other });
+    }
+
+    fn keyword(&mut self) {
+        let identifiers = self.identifiers;
+        while self.peek().is_some_and(|x| x.is_alphabetic() || (identifiers && is_identifier_part(x))) {
+            self.next_character();
+        }
+        if let Some(line) = &self.current_line {
+            if let Some(token) = self.keywords.get(&line[self.start..self.current_offset]) {
+                self.add_token(*token);
+            } else {
+                self.add_token(TokenType::Other);
+            }
+        } else {
+            self.add_token(TokenType::Other);
+        }
+    }
+
+    fn scan_token(&mut self) {
+        self.next_character();
+
+        if let Some(c) = self.current_char {
+            match c {
+                '{' => { self.add_token(TokenType::LeftBrace); }
+                '}' => { self.add_token(TokenType::RightBrace); }
+                ':' => { self.add_token(TokenType::Colon); }
+                ',' => { self.add_token(TokenType::Comma); }
+                '[' => { self.add_token(TokenType::LeftSquareBracket); }
+                ']' => { self.add_token(TokenType::RightSquareBracket); }
+                '"' => { self.string('"'); }
+                '\'' if self.single_quotes => { self.string('\''); }
+                ' ' | '\t' | '\n' | '\r' => { }
+                '/' => { self.comment(); }
+                '-' => { self.number(); }
+                _ if c.is_ascii_digit() => { self.number(); }
+                _ if c.is_alphabetic() => { self.keyword(); }
+                '_' | '$' if self.identifiers => { self.keyword(); }
+                _ => { self.add_token(TokenType::Other); }
+            }
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        if let Some(line) = &self.current_line {
+            line.is_empty()
+        } else {
+            false
+        }
+    }
+
+    pub(crate) fn take_error(&mut self) -> Option<ParseError> {
+        self.error.take()
+    }
+
+    /// Scans on demand, so only the tokens of the current line are ever held in memory.
+    pub(crate) fn next_token(&mut self) -> Option<Arc<Token>> {
+        if self.current_line.is_none() { self.current_line = Some(self.read_line()); }
+        if self.current_token >= self.tokens.len() {
+            self.tokens.clear();
+            self.current_token = 0;
+            while self.tokens.is_empty() && !self.at_end() {
+                self.start = self.current_offset;
+                self.scan_token();
+            }
+            if self.tokens.is_empty() { return None; }
+            self.scanned += self.tokens.len();
+            if let Some(limit) = self.max_tokens.filter(|&limit| self.scanned > limit) {
+                let token = &self.tokens[0];
+                self.error = Some(ParseError::new(ErrorKind::LimitExceeded, format!("more than {} tokens", limit), token.line, token.column, token.offset));
+                self.tokens.clear();
+                self.current_line = Some(String::new());
+                return None;
+            }
+        }
+
+        let token = self.tokens[self.current_token].clone();
+        self.current_token += 1;
+        Some(token)
+    }
+}
+
+fn is_identifier_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Bytes of a line read before `read_line_counting` looks at how many tokens it holds. Shorter
+/// lines are left to the scanner, which places the error at the exact token.
+const LONG_LINE: usize = 1 << 16;
+
+/// Reads up to and including the next newline, as `read_until` does, but gives up on a long line
+/// once it is seen to hold more than `tokens` tokens, so that a line that never ends stops being
+/// read at the token limit. Returns the index of the byte that showed it: a bracket, comma or
+/// colon outside a string. Only those are counted, and nothing after a `/` or `'` anywhere in
+/// the input, as either may start a comment or string that holds them; so the count is never
+/// more than the scanner would find.
+fn read_line_counting(reader: &mut impl BufRead, bytes: &mut Vec<u8>, tokens: usize, counting: &mut bool) -> std::io::Result<Option<usize>> {
+    let (mut count, mut in_string, mut escaped) = (0, false, false);
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() { return Ok(None); }
+        let (used, done) = match buffer.iter().position(|&byte| byte == b'\n') {
+            Some(newline) => (newline + 1, true),
+            None => (buffer.len(), false),
+        };
+        let checked = bytes.len();
+        bytes.extend_from_slice(&buffer[..used]);
+        reader.consume(used);
+
+        for (index, &byte) in bytes.iter().enumerate().skip(checked) {
+            if !*counting { break; }
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' if in_string => escaped = true,
+                b'"' => in_string = !in_string,
+                _ if in_string => { }
+                b'/' | b'\'' => *counting = false,
+                b'[' | b']' | b'{' | b'}' | b',' | b':' => {
+                    count += 1;
+                    if count > tokens && bytes.len() > LONG_LINE { return Ok(Some(index)); }
+                }
+                _ => { }
+            }
+        }
+        if done { return Ok(None); }
+    }
+}
+
+/// Columns here count bytes, as the line is not decoded.
+fn too_many_bytes(limit: usize, line: usize, column: usize) -> ParseError {
+    ParseError::new(ErrorKind::LimitExceeded, format!("more than {} bytes of input", limit), line, column, limit)
+}
+
+/// Names the problem with the bytes at the start of `bytes`, which are not valid UTF-8.
+pub(crate) fn describe_invalid_utf8(bytes: &[u8]) -> String {
+    match bytes {
+        [0xED, 0xA0..=0xBF, ..] => "a surrogate encoded directly, as in CESU-8, starting with byte 0xED".to_string(),
+        [first @ (0xC0 | 0xC1), ..] | [first @ 0xE0, 0x80..=0x9F, ..] | [first @ 0xF0, 0x80..=0x8F, ..] => {
+            format!("an overlong encoding starting with byte 0x{:02X}", first)
+        }
+        [first, ..] => format!("unexpected byte 0x{:02X}", first),
+        [] => "incomplete character".to_string(),
+    }
+}
@@ -0,0 +1,51 @@
+#[cfg(feature = "arena")]
+mod arena;
+mod checks;
+mod diff;
+mod encoding;
+mod error;
+mod events;
+mod flatten;
+mod formatted;
+mod framing;
+mod generate;
+mod lexer;
+mod messages;
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+mod mmap;
+mod number;
+mod parser;
+mod patch;
+mod pointer;
+mod push;
+mod schema;
+mod serializer;
+mod stats;
+mod value;
+
+#[cfg(feature = "arena")]
+pub use arena::{parse_in_arena, Arena, ArenaValue};
+pub use checks::{find_mixed_arrays, lint, matches_schema, LintIssue, MixedArray, SchemaMismatch};
+pub use diff::{diff, to_patch, Change};
+pub use encoding::Encoding;
+pub use error::{ErrorKind, ParseError};
+pub use events::{Event, EventReader};
+pub use flatten::{flatten, unflatten};
+pub use formatted::{parse_with_spans, FormattedDocument};
+pub use framing::read_framed;
+pub use generate::{Alphabet, GenerateOptions, Generator};
+pub use lexer::{tokenize, Comment, Lexer, Token, TokenType};
+pub use messages::{English, Found, Messages, StringProblem};
+#[cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+pub use mmap::parse_mmap;
+pub use number::{BigIntegerParser, F64NumberParser, NumberParser};
+#[cfg(feature = "unicode-normalization")]
+pub use parser::nfc_key;
+pub use parser::{first_error_context, DuplicateKeys, ErrorContext, InvalidUtf8, LeadingZeros, Mode, ParseOptions, SyntaxAnalyser, DEFAULT_MAX_DEPTH};
+pub use patch::{apply_patch, PatchError};
+pub use pointer::{escape_token, parse_pointer, PathSegment};
+pub use push::Parser;
+pub use schema::{validate_schema, SchemaError};
+pub use serializer::{to_string, to_string_with_limit, to_string_with_options, Colors, KeyOrder, NumbersAsStrings, PrettyConfig, SerializeOptions, Serializer};
+pub use stats::Stats;
+pub use value::{ArrayMerge, Entry, FromJsonValue, GetError, JsonValue};
@@ -1,299 +1,1272 @@
-use std::collections::HashMap;
+mod cli;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::fs::File;
-use std::io::{stdin, BufRead, BufReader};
+use std::io::{stderr, stdin, stdout, BufRead, BufReader, Cursor, IsTerminal, Read};
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
-enum TokenType {
-    LeftBrace,
-    RightBrace,
-    String,
-    Number,
-    True,
-    False,
-    Null,
-    Colon,
-    Comma,
-    LeftSquareBracket,
-    RightSquareBracket,
-    Other
-}
-
-struct Token {
-    token_type: TokenType,
-    original_text: String,
-}
-
-impl Token {
-    fn new(token_type: TokenType, original_text: String) -> Token {
-        Token {
-            token_type,
-            original_text,
-        }
-    }
-}
-
-struct Lexer {
-    buf_reader: Box<dyn BufRead>,
-    tokens: Vec<Rc<Token>>,
-    current_line: Option<String>,
-    current_char: Option<char>,
-    current_offset: usize,
-    current_line_number: usize,
-    start: usize,
-    current_token: usize,
-    keywords: HashMap<String, TokenType>
-}
-
-impl Lexer {
-    fn new(mut buf_reader: Box<dyn BufRead>) -> Lexer {
-        let line = &mut "".to_string();
-        buf_reader.read_line(line).expect("Failed to read first line");
-
-        let mut map = HashMap::new();
-        map.insert("true".to_string(), TokenType::True);
-        map.insert("false".to_string(), TokenType::False);
-        map.insert("null".to_string(), TokenType::Null);
-
-        Lexer {
-            buf_reader,
-            tokens: vec![],
-            current_line: Some(line.clone()),
-            current_char: None,
-            current_offset: 0,
-            current_line_number: 0,
-            start: 0,
-            current_token: 0,
-            keywords: map
-        }
-    }
-
-    fn next_character(&mut self) {
-        if let Some(line) = &self.current_line {
-            if self.current_offset >= line.chars().count() {
-                let mut new_line = String::new();
-                self.buf_reader.read_line(&mut new_line).expect("Failed to read line");
-                self.current_line = Some(new_line.clone());
-                self.current_char = Some('\n');
-                self.current_line_number += 1;
-                self.current_offset = 0;
-            } else {
-                self.current_char = line.chars().nth(self.current_offset);
-                self.current_offset += 1;
+use cli::args::{self, Args, ColorChoice, Command, DiffArgs, DiffFormat, Edit, ExploreArgs, Format, GenerateArgs, Formatting, Input, Listing, MergeArgs, Missing, PatchArgs, Query, StatsFormat, Summary};
+use cli::{diagnostic, explore, follow, help, preview, progress, unified, walk, watch, write};
+use cli::diagnostic::Severity;
+use cli::output::{Level, Output};
+use cli::recent::{Recent, Tap};
+use json_parser_rs::{
+    apply_patch, diff, escape_token, find_mixed_arrays, flatten, lint, to_patch, to_string_with_options, unflatten, validate_schema, Colors, Encoding, ErrorKind, Event, EventReader, FormattedDocument, Generator, JsonValue, KeyOrder, Lexer, Mode, ParseError, ParseOptions, PrettyConfig,
+    SerializeOptions, Stats, SyntaxAnalyser,
+};
+
+/// How --progress names standard input.
+const STDIN: &str = "standard input";
+
+/// An opened input. Files are reopened for each pass over them. Standard input can only be read
+/// once, so it is streamed to the parser, keeping only the lines an error may need to show,
+/// unless the command needs all of its text after parsing it. Either reads as UTF-8.
+enum Source {
+    /// Standard input read in full, already decoded.
+    Stdin(Rc<[u8]>),
+    /// Standard input, decoded, until the parser takes it, and what the parser has read of it.
+    Stream(RefCell<Option<Box<dyn BufRead>>>, Rc<RefCell<Recent>>),
+    File(String, Encoding),
+}
+
+impl Source {
+    /// With `whole`, standard input is read up front, no further than one byte past `max_bytes`,
+    /// which is enough for the parser to report it as too long. Otherwise the parser's own limits
+    /// stop it as it streams.
+    fn open(input: &Input, max_bytes: Option<usize>, encoding: Encoding, whole: bool) -> Result<Source, ParseError> {
+        match input {
+            Input::Stdin if whole => {
+                let mut buffer = Vec::new();
+                let raw = progress::Meter::new(stdin(), STDIN, None).take(max_bytes.map_or(u64::MAX, |limit| limit as u64 + 1));
+                encoding.decode(Box::new(raw)).read_to_end(&mut buffer)?;
+                Ok(Source::Stdin(buffer.into()))
             }
-        } else {
-            self.current_char = None;
+            Input::Stdin => {
+                let recent = Rc::new(RefCell::new(Recent::new()));
+                let stream = BufReader::new(encoding.decode(Box::new(progress::Meter::new(stdin(), STDIN, None))));
+                Ok(Source::Stream(RefCell::new(Some(Box::new(Tap::new(stream, recent.clone())))), recent))
+            }
+            Input::File(path) => Ok(Source::File(path.clone(), encoding)),
         }
     }
 
-    fn peek(&mut self) -> Option<char> {
-        if let Some(line) = &self.current_line {
-            if self.current_offset >= line.chars().count() {
-                Some('\n')
-            } else {
-                line.chars().nth(self.current_offset)
+    fn reader(&self) -> Result<Box<dyn BufRead>, ParseError> {
+        let reader: Box<dyn BufRead> = match self {
+            Source::Stdin(buffer) => Box::new(Cursor::new(buffer.clone())),
+            Source::Stream(stream, _) => return stream.take().ok_or_else(|| std::io::Error::other("standard input has already been read").into()),
+            Source::File(path, Encoding::Utf8) => Box::new(BufReader::new(File::open(path)?)),
+            Source::File(path, encoding) => Box::new(BufReader::new(encoding.decode(Box::new(File::open(path)?)))),
+        };
+        if !progress::enabled() { return Ok(reader); }
+        let name = match self {
+            Source::Stdin(_) | Source::Stream(..) => STDIN,
+            Source::File(path, _) => path,
+        };
+        Ok(Box::new(progress::Meter::new(reader, name, self.size())))
+    }
+
+    /// For a stream, what has been read of it so far.
+    fn size(&self) -> Option<u64> {
+        match self {
+            Source::Stdin(buffer) => Some(buffer.len() as u64),
+            Source::Stream(_, recent) => Some(recent.borrow().bytes()),
+            Source::File(path, _) => std::fs::metadata(path).ok().map(|metadata| metadata.len()),
+        }
+    }
+
+    /// Why the input failed to parse if it is UTF-16 that was read as UTF-8.
+    fn utf16_error(&self) -> Option<ParseError> {
+        if let Source::Stream(_, recent) = self { return encoding_error(Encoding::detect(recent.borrow().start())); }
+        let mut start = Vec::new();
+        self.reader().ok()?.take(4).read_to_end(&mut start).ok()?;
+        encoding_error(Encoding::detect(&start))
+    }
+
+    fn contents(&self) -> Result<Vec<u8>, ParseError> {
+        let mut contents = Vec::new();
+        self.reader()?.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+
+    fn line(&self, number: usize) -> Option<String> {
+        if let Source::Stream(_, recent) = self { return recent.borrow().line(number); }
+        let mut reader = self.reader().ok()?;
+        let mut line = Vec::new();
+        for index in 1..=number {
+            line.clear();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                // The line an input stops decoding on is shown up to where it stops.
+                Err(_) if index == number && !line.is_empty() => break,
+                Err(_) => return None,
             }
-        } else {
-            None
         }
+        Some(String::from_utf8_lossy(&line).into_owned())
     }
+}
 
-    fn add_token(&mut self, token_type: TokenType) {
-        if let Some(line) = &self.current_line {
-            self.tokens.push(Rc::new(Token::new(token_type, line[self.start..self.current_offset].to_string())))
-        } else {
-            panic!("Tried to add token but the current line is None");
+/// The error for an input in `encoding`, when it is UTF-16, given without `--encoding`.
+fn encoding_error(encoding: Encoding) -> Option<ParseError> {
+    if encoding == Encoding::Utf8 { return None; }
+    let message = format!("input appears to be {}; re-encode as UTF-8 or pass --encoding {}", encoding.name().to_uppercase(), encoding.name());
+    Some(ParseError::new(ErrorKind::InvalidUtf8, message, 1, 1, 0))
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum ExitCode {
+    Valid = 0,
+    Invalid = 1,
+    Usage = 2,
+    Io = 3,
+    NotFound = 4,
+    /// An input broke a `--max-bytes`, `--max-tokens`, `--max-key-length` or `--max-value-bytes` limit.
+    TooLarge = 5,
+    /// A `--length` pointer resolved to a number, boolean or null.
+    NoLength = 6,
+}
+
+impl ExitCode {
+    fn severity(self) -> u8 {
+        match self {
+            ExitCode::Valid => 0,
+            ExitCode::NotFound => 1,
+            ExitCode::NoLength => 2,
+            ExitCode::Invalid => 3,
+            ExitCode::TooLarge => 4,
+            ExitCode::Usage => 5,
+            ExitCode::Io => 6,
         }
     }
 
-    fn next_num(&mut self) {
-        while let Some(_) = self.current_char {
-            if self.peek().is_some_and(|x| !x.is_numeric()) { break; }
-            self.next_character();
+    /// Combines the outcomes of two inputs so that the most serious one wins.
+    fn worst(self, other: ExitCode) -> ExitCode {
+        if other.severity() > self.severity() { other } else { self }
+    }
+
+    /// How the summary counts an input that ended with this code.
+    fn status(self) -> &'static str {
+        match self {
+            ExitCode::Valid => "ok",
+            ExitCode::Invalid => "invalid",
+            ExitCode::NotFound => "not-found",
+            ExitCode::NoLength => "no-length",
+            ExitCode::TooLarge => "too-large",
+            ExitCode::Io => "io-error",
+            ExitCode::Usage => "usage-error",
+        }
+    }
+}
+
+/// Parses a source, also returning any warnings about input the parser recovered from.
+fn validate(source: &Source, options: &ParseOptions) -> (Result<JsonValue, ParseError>, Vec<ParseError>) {
+    let lexer = match source.reader() {
+        Ok(reader) => Lexer::new(reader),
+        Err(error) => return (Err(error), Vec::new()),
+    };
+    let mut syntax_analyser = SyntaxAnalyser::with_options(lexer, options.clone());
+    let result = syntax_analyser.parse();
+    (result, syntax_analyser.take_warnings())
+}
+
+/// Checks that a source is well formed without building its value or decoding its strings.
+fn check_structure(source: &Source, options: &ParseOptions) -> (Result<(), ParseError>, Vec<ParseError>) {
+    let lexer = match source.reader() {
+        Ok(reader) => Lexer::new(reader),
+        Err(error) => return (Err(error), Vec::new()),
+    };
+    let mut reader = EventReader::with_options(lexer, options.clone());
+    let result = reader.skip_value().and_then(|()| reader.next_event()).map(|_| ());
+    (result, reader.take_warnings())
+}
+
+fn check(args: &Args, options: &ParseOptions, schema: Option<&JsonValue>, output: &Output, input: &Input) -> ExitCode {
+    let name = args.name(input);
+    // Linting, --preserve-formatting and --check read the text again once it has been parsed.
+    let whole = args.lint || args.preserve_formatting || args.check;
+    let source = match Source::open(input, options.max_bytes, args.global.encoding, whole) {
+        Ok(source) => source,
+        Err(error) => {
+            output.diagnostic(Severity::Error, name, &error, None);
+            return ExitCode::Io;
         }
+    };
+
+    let started = Instant::now();
+    if args.depth.is_some() || args.sample.is_some() {
+        let depth = args.depth.unwrap_or(usize::MAX);
+        let (result, warnings) = read_preview(&source, options, depth, args.sample, args.max_string_len);
+        report_timing(output, name, &source, started);
+        report_warnings(output, name, &source, &warnings);
+        return match result {
+            Ok(node) => {
+                output.result(&(preview::render(&node, &serialize_options(&args.formatting, false)) + "\n"));
+                if !output.is_quiet() {
+                    let option = if args.depth.is_some() { "--depth" } else { "--sample" };
+                    output.problem(Severity::Warning, name, None, "not-json", &format!("output printed with '{}' leaves values out and is not valid JSON", option));
+                }
+                ExitCode::Valid
+            }
+            Err(error) => report_error(args, output, name, &source, &error),
+        };
+    }
+    if let Some(listing) = &args.listing {
+        let (result, warnings) = list(args, output, &source, options, listing);
+        report_timing(output, name, &source, started);
+        report_warnings(output, name, &source, &warnings);
+        return match result {
+            Ok(None) => ExitCode::Valid,
+            Ok(Some(message)) => {
+                output.problem(Severity::Error, name, None, "pointer-not-found", &message);
+                ExitCode::NotFound
+            }
+            Err(error) => report_error(args, output, name, &source, &error),
+        };
+    }
+    if let Some(format) = args.stats {
+        let (result, warnings) = collect_stats(&source, options);
+        report_timing(output, name, &source, started);
+        report_warnings(output, name, &source, &warnings);
+        return match result {
+            Ok(stats) => {
+                output.result(&format_stats(name, &stats, format));
+                ExitCode::Valid
+            }
+            Err(error) => report_error(args, output, name, &source, &error),
+        };
+    }
+
+    if args.validate_utf8_only {
+        let (result, warnings) = check_structure(&source, options);
+        report_timing(output, name, &source, started);
+        report_warnings(output, name, &source, &warnings);
+        return match result {
+            Ok(()) => {
+                output.result(&format!("{}: ok\n", name));
+                ExitCode::Valid
+            }
+            Err(error) => report_error(args, output, name, &source, &error),
+        };
     }
 
-    fn number(&mut self) {
-        self.next_num();
+    let (result, warnings) = validate(&source, options);
+    report_timing(output, name, &source, started);
+    report_warnings(output, name, &source, &warnings);
 
-        if let Some(dot) = self.peek() {
-            if dot == '.' {
-                self.next_character();
-                self.next_character();
-                if let Some(n) = self.current_char {
-                    if n.is_numeric() {
-                        self.next_num();
-                    }
+    if let (Ok(_), true) = (&result, args.lint) {
+        match source.contents() {
+            Ok(contents) => {
+                for issue in lint(&String::from_utf8_lossy(&contents)) {
+                    output.problem(Severity::Warning, name, Some(issue.line), issue.code, &issue.message);
                 }
             }
+            Err(error) => output.diagnostic(Severity::Error, name, &error, None),
+        }
+    }
+    if let (Ok(value), true) = (&result, args.homogeneous_arrays) {
+        for array in find_mixed_arrays(value) {
+            let pointer = if array.pointer.is_empty() { "the root" } else { &array.pointer };
+            output.problem(Severity::Warning, name, None, "mixed-array", &format!("array at {} mixes element types: {}", pointer, array.types.join(", ")));
+        }
+    }
+
+    if let (Ok(value), Some(schema)) = (&result, schema) {
+        if !check_schema(output, name, None, value, schema) { return ExitCode::Invalid; }
+    }
+
+    match result {
+        Ok(value) if !args.gets.is_empty() => get(args, output, name, &value),
+        Ok(value) if !args.selections.is_empty() => select(args, output, name, None, &value, &serialize_options(&args.formatting, output.colors_results())),
+        Ok(value) if args.rewrites() => rewrite(args, options, output, input, &source, name, value),
+        Ok(_) => {
+            output.result(&format!("{}: ok\n", name));
+            ExitCode::Valid
+        }
+        Err(error) => report_error(args, output, name, &source, &error),
+    }
+}
+
+/// Reports each way `value` fails the schema, returning whether it passed.
+fn check_schema(output: &Output, name: &str, line: Option<usize>, value: &JsonValue, schema: &JsonValue) -> bool {
+    let errors = validate_schema(value, schema);
+    for error in &errors {
+        output.problem(Severity::Error, name, line, "schema", &error.to_string());
+    }
+    errors.is_empty()
+}
+
+fn load_schema(path: &str, options: &ParseOptions, output: &Output) -> Result<JsonValue, ExitCode> {
+    let source = Source::File(path.to_string(), Encoding::Utf8);
+    match validate(&source, options).0 {
+        Ok(schema) => Ok(schema),
+        Err(error) if error.kind == ErrorKind::Io => {
+            output.diagnostic(Severity::Error, path, &error, None);
+            Err(ExitCode::Io)
+        }
+        Err(error) => {
+            output.diagnostic(Severity::Error, path, &error, source.line(error.line).as_deref());
+            Err(ExitCode::Usage)
+        }
+    }
+}
+
+fn report_timing(output: &Output, name: &str, source: &Source, started: Instant) {
+    let size = source.size().map_or("unknown size".to_string(), |size| format!("{} bytes", size));
+    output.verbose(&format!("{}: {}, validated in {:.2?}\n", name, size, started.elapsed()));
+}
+
+fn report_warnings(output: &Output, name: &str, source: &Source, warnings: &[ParseError]) {
+    for warning in warnings {
+        output.diagnostic(Severity::Warning, name, warning, source.line(warning.line).as_deref());
+    }
+}
+
+fn report_error(args: &Args, output: &Output, name: &str, source: &Source, error: &ParseError) -> ExitCode {
+    if error.kind == ErrorKind::Io {
+        output.diagnostic(Severity::Error, name, error, None);
+        return ExitCode::Io;
+    }
+    // The line may be as long as the limit, so it is not shown.
+    if error.kind == ErrorKind::LimitExceeded {
+        output.diagnostic(Severity::Error, name, error, None);
+        return ExitCode::TooLarge;
+    }
+    if let Some(error) = source.utf16_error() {
+        output.diagnostic(Severity::Error, name, &error, None);
+        return ExitCode::Invalid;
+    }
+
+    output.diagnostic(Severity::Error, name, error, source.line(error.line).as_deref());
+    if args.count_lines && !output.errors_as_json() {
+        output.error(&diagnostic::summary(error));
+    }
+    ExitCode::Invalid
+}
+
+fn collect_stats(source: &Source, options: &ParseOptions) -> (Result<Stats, ParseError>, Vec<ParseError>) {
+    let lexer = match source.reader() {
+        Ok(reader) => Lexer::new(reader),
+        Err(error) => return (Err(error), Vec::new()),
+    };
+    let mut reader = EventReader::with_options(lexer, options.clone());
+    let result = Stats::collect(&mut reader);
+    (result, reader.take_warnings())
+}
+
+fn format_stats(name: &str, stats: &Stats, format: StatsFormat) -> String {
+    let counts = [
+        ("objects", stats.objects),
+        ("arrays", stats.arrays),
+        ("strings", stats.strings),
+        ("numbers", stats.numbers),
+        ("booleans", stats.booleans),
+        ("nulls", stats.nulls),
+        ("max_depth", stats.max_depth),
+        ("longest_array", stats.longest_array),
+        ("distinct_keys", stats.distinct_keys),
+        ("string_bytes", stats.string_bytes),
+    ];
+
+    match format {
+        StatsFormat::Text => {
+            let mut text = format!("{}:\n", name);
+            for (label, count) in counts {
+                text.push_str(&format!("  {:<15}{}\n", label.replace('_', " "), count));
+            }
+            text
+        }
+        StatsFormat::Json => {
+            let mut members = vec![("file".to_string(), JsonValue::String(name.to_string()))];
+            members.extend(counts.iter().map(|(label, count)| (label.to_string(), JsonValue::Number(*count as f64))));
+            JsonValue::Object(members).to_string() + "\n"
         }
+    }
+}
 
-        self.add_token(TokenType::Number);
+fn get(args: &Args, output: &Output, name: &str, document: &JsonValue) -> ExitCode {
+    let mut code = ExitCode::Valid;
+    for query in &args.gets {
+        let Some(value) = document.pointer(query.pointer()) else {
+            output.problem(Severity::Error, name, None, "pointer-not-found", &format!("pointer not found: '{}'", query.pointer()));
+            code = code.worst(ExitCode::NotFound);
+            continue;
+        };
+        match (query, value) {
+            (Query::Type(_), value) => output.result(&format!("{}\n", value.type_name())),
+            (Query::Length(_), JsonValue::Object(members)) => output.result(&format!("{}\n", members.len())),
+            (Query::Length(_), JsonValue::Array(elements)) => output.result(&format!("{}\n", elements.len())),
+            (Query::Length(_), JsonValue::String(string)) => output.result(&format!("{}\n", string.chars().count())),
+            (Query::Length(pointer), value) => {
+                output.problem(Severity::Error, name, None, "no-length", &format!("value at '{}' is {}, which has no length", pointer, value));
+                code = code.worst(ExitCode::NoLength);
+            }
+            (Query::Value(_), JsonValue::String(string)) if !args.json => output.result(&format!("{}\n", string)),
+            (Query::Value(_), value) => output.result(&format!("{}\n", value)),
+        }
     }
+    code
+}
 
-    fn keyword(&mut self) {
-        while let Some(_) = self.current_char {
-            if self.peek().is_some_and(|x| !x.is_alphabetic()) { break; }
-            self.next_character();
+/// Prints the object `--select` builds from a document. With `--missing=error`, nothing is
+/// printed unless every pointer resolves.
+fn select(args: &Args, output: &Output, name: &str, line: Option<usize>, document: &JsonValue, options: &SerializeOptions) -> ExitCode {
+    let mut members = Vec::new();
+    let mut code = ExitCode::Valid;
+    for (key, pointer) in &args.selections {
+        match (document.pointer(pointer), args.missing) {
+            (Some(value), _) => members.push((key.clone(), value.clone())),
+            (None, Missing::Null) => members.push((key.clone(), JsonValue::Null)),
+            (None, Missing::Error) => {
+                output.problem(Severity::Error, name, line, "pointer-not-found", &format!("pointer not found: '{}'", pointer));
+                code = ExitCode::NotFound;
+            }
         }
-        if let Some(line) = &self.current_line {
-            if let Some(token) = self.keywords.get(&line[self.start..self.current_offset].to_string()) {
-                self.add_token(token.clone());
-            } else {
-                self.add_token(TokenType::Other);
+    }
+    if code == ExitCode::Valid {
+        output.result(&(to_string_with_options(&JsonValue::Object(members), options) + "\n"));
+    }
+    code
+}
+
+/// Reads a source for `--depth` or `--sample`, keeping only what will be printed.
+fn read_preview(source: &Source, options: &ParseOptions, depth: usize, sample: Option<usize>, max_string_len: Option<usize>) -> (Result<preview::Node, ParseError>, Vec<ParseError>) {
+    let lexer = match source.reader() {
+        Ok(reader) => Lexer::new(reader),
+        Err(error) => return (Err(error), Vec::new()),
+    };
+    let mut reader = EventReader::with_options(lexer, options.clone());
+    let result = preview::read(&mut reader, depth, sample, max_string_len);
+    (result, reader.take_warnings())
+}
+
+/// Prints what `listing` asks for as the reader's events go by, without building the document.
+/// Succeeds with a message when `--keys` names something other than an object.
+fn list(args: &Args, output: &Output, source: &Source, options: &ParseOptions, listing: &Listing) -> (Result<Option<String>, ParseError>, Vec<ParseError>) {
+    let lexer = match source.reader() {
+        Ok(reader) => Lexer::new(reader),
+        Err(error) => return (Err(error), Vec::new()),
+    };
+    let mut reader = EventReader::with_options(lexer, options.clone());
+    let mut listed = Vec::new();
+    let mut print = |text: &str| match args.json {
+        true => listed.push(JsonValue::String(text.to_string())),
+        false => output.result(&(listing_line(text) + "\n")),
+    };
+
+    // Each open container's pointer and how many values it holds so far; `target` is what --keys found.
+    let mut open: Vec<(String, usize, bool)> = Vec::new();
+    let mut key = String::new();
+    let mut target = None;
+    let result = loop {
+        let event = match reader.next_event() {
+            Ok(Some(event)) => event,
+            Ok(None) => break Ok(()),
+            Err(error) => break Err(error),
+        };
+        let pointer = match (&event, open.last_mut()) {
+            (Event::Key(name), Some((pointer, _, _))) => {
+                if matches!(listing, Listing::Keys(wanted) if wanted == pointer) { print(name); }
+                key = name.clone();
+                continue;
             }
-        } else {
-            self.add_token(TokenType::Other);
-        }
-    }
-
-    fn scan_token(&mut self) {
-        self.next_character();
-
-        if let Some(c) = self.current_char {
-            match c {
-                '{' => { self.add_token(TokenType::LeftBrace); }
-                '}' => { self.add_token(TokenType::RightBrace); }
-                ':' => { self.add_token(TokenType::Colon); }
-                ',' => { self.add_token(TokenType::Comma); }
-                '[' => { self.add_token(TokenType::LeftSquareBracket); }
-                ']' => { self.add_token(TokenType::RightSquareBracket); }
-                '"' => {
-                    self.next_character();
-                    while let Some(ch) = self.current_char {
-                        if ch == '"' { break; }
-                        self.next_character();
-                    }
-                    self.add_token(TokenType::String)
-                }
-                '\n' | ' ' => { }
-                _ => {
-                    if c.is_numeric() {
-                        self.number();
-                    } else if c.is_alphabetic() {
-                        self.keyword();
-                    } else {
-                        self.add_token(TokenType::Other);
-                    }
+            (Event::EndObject | Event::EndArray, _) => {
+                let (pointer, count, _) = open.pop().unwrap_or_default();
+                if count == 0 && !pointer.is_empty() && *listing == (Listing::Paths { leaves: true }) { print(&pointer); }
+                continue;
+            }
+            (_, None) => String::new(),
+            (_, Some((parent, count, true))) => format!("{}/{}", parent, count),
+            (_, Some((parent, _, false))) => format!("{}/{}", parent, escape_token(&key)),
+        };
+        if let Some((_, count, _)) = open.last_mut() { *count += 1; }
+
+        if matches!(listing, Listing::Keys(wanted) if *wanted == pointer) {
+            target = Some(match &event {
+                Event::StartObject => "object",
+                Event::StartArray => "array",
+                Event::Value(value) => value.type_name(),
+                _ => unreachable!("keys and container ends are handled above"),
+            });
+        }
+        let container = matches!(event, Event::StartObject | Event::StartArray);
+        if matches!(listing, Listing::Paths { leaves } if !pointer.is_empty() && (!*leaves || !container)) {
+            print(&pointer);
+        }
+        if container { open.push((pointer, 0, matches!(event, Event::StartArray))); }
+    };
+    let warnings = reader.take_warnings();
+    if let Err(error) = result {
+        return (Err(error), warnings);
+    }
+
+    if args.json {
+        output.result(&(JsonValue::Array(listed).to_string() + "\n"));
+    }
+    let missing = match (listing, target) {
+        (Listing::Keys(pointer), None) => Some(format!("pointer not found: '{}'", pointer)),
+        (Listing::Keys(pointer), Some(found)) if found != "object" => Some(format!("value at '{}' is not an object but {}", pointer, found)),
+        _ => None,
+    };
+    (Ok(missing), warnings)
+}
+
+/// A key or pointer as one line of `--keys` or `--paths` output: as it is, unless it could be
+/// mistaken for more than one line or for a JSON string, in which case it is written as one.
+fn listing_line(text: &str) -> String {
+    if text.starts_with('"') || text.contains(|c: char| c.is_control()) {
+        return JsonValue::String(text.to_string()).to_string();
+    }
+    text.to_string()
+}
+
+/// Applies `--set`, `--delete`, `--flatten` and `--unflatten` in order. A failure comes with the
+/// exit code and the diagnostic code to report it with.
+fn apply_edits(args: &Args, document: &mut JsonValue) -> Result<(), (ExitCode, &'static str, String)> {
+    let not_found = |message| (ExitCode::NotFound, "pointer-not-found", message);
+    for edit in &args.edits {
+        match edit {
+            Edit::Set(pointer, value) => document.set_pointer(pointer, value.clone(), args.create_missing).map_err(not_found)?,
+            Edit::Delete(pointer) => {
+                if document.remove_pointer(pointer).is_none() && !args.ignore_missing {
+                    return Err(not_found(format!("pointer not found: '{}'", pointer)));
                 }
             }
-        } else {
-            panic!("Next character is none :o");
+            Edit::Flatten => *document = flatten(document, &args.separator),
+            Edit::Unflatten => *document = unflatten(document, &args.separator).map_err(|message| (ExitCode::Invalid, "not-flat", message))?,
+        }
+    }
+    Ok(())
+}
+
+/// Applies the `--set` edits of `--preserve-formatting` to the text of a document.
+fn edit_text(args: &Args, options: &ParseOptions, source: &Source) -> Result<String, (ExitCode, &'static str, String)> {
+    let unreadable = |error: ParseError| (ExitCode::Io, "io", error.message);
+    let text = String::from_utf8_lossy(&source.contents().map_err(unreadable)?).into_owned();
+    // The document has been parsed already, so this only fails if the input changed since.
+    let mut document = FormattedDocument::parse(text, options.clone()).map_err(|error| (ExitCode::Invalid, diagnostic::code(error.kind), error.message))?;
+    for edit in &args.edits {
+        if let Edit::Set(pointer, value) = edit {
+            document.set(pointer, value).map_err(|message| (ExitCode::NotFound, "pointer-not-found", message))?;
         }
     }
+    Ok(document.into_string())
+}
 
-    fn at_end(&self) -> bool {
-        if let Some(line) = &self.current_line {
-            line.is_empty()
-        } else {
-            false
+fn serialize_options(formatting: &Formatting, color: bool) -> SerializeOptions {
+    if formatting.format == Some(Format::Canonical) {
+        return SerializeOptions { colors: if color { Some(Colors::default()) } else { None }, ..SerializeOptions::canonical() };
+    }
+    SerializeOptions {
+        colors: if color { Some(Colors::default()) } else { None },
+        escape_unicode: formatting.escape_unicode,
+        collapse_whitespace: formatting.collapse_whitespace,
+        key_order: if formatting.sort_keys { KeyOrder::Sorted } else { KeyOrder::Preserve },
+        pretty: match formatting.format {
+            Some(Format::Compact) => None,
+            _ => Some(match &formatting.indent {
+                Some(indent) => PrettyConfig { indent: indent.clone() },
+                None => PrettyConfig::default(),
+            }),
+        },
+        ..SerializeOptions::default()
+    }
+}
+
+fn rewrite(args: &Args, options: &ParseOptions, output: &Output, input: &Input, source: &Source, name: &str, mut document: JsonValue) -> ExitCode {
+    let text = if args.preserve_formatting {
+        edit_text(args, options, source)
+    } else {
+        apply_edits(args, &mut document).map(|()| {
+            let color = output.colors_results() && !args.write && !args.check;
+            to_string_with_options(&document, &serialize_options(&args.formatting, color)) + "\n"
+        })
+    };
+    let text = match text {
+        Ok(text) => text,
+        Err((code, problem, message)) => {
+            output.problem(Severity::Error, name, None, problem, &message);
+            return code;
+        }
+    };
+    if args.check { return check_unchanged(args, output, source, name, &text); }
+
+    match input {
+        Input::File(path) if args.write => write_back(output, path, &text),
+        _ => {
+            output.result(&text);
+            ExitCode::Valid
+        }
+    }
+}
+
+/// Reports whether printing `text` in place of the input would change it, for --check.
+fn check_unchanged(args: &Args, output: &Output, source: &Source, name: &str, text: &str) -> ExitCode {
+    let original = match source.contents() {
+        Ok(original) => original,
+        Err(error) => {
+            output.diagnostic(Severity::Error, name, &error, None);
+            return ExitCode::Io;
+        }
+    };
+    if original == text.as_bytes() {
+        output.result(&format!("{}: ok\n", name));
+        return ExitCode::Valid;
+    }
+
+    output.result(&format!("{}: would change\n", name));
+    if args.check_diff {
+        output.result(&unified::diff(name, &format!("{} (formatted)", name), &String::from_utf8_lossy(&original), text));
+    }
+    ExitCode::Invalid
+}
+
+fn write_back(output: &Output, path: &str, text: &str) -> ExitCode {
+    match write::replace(path, text.as_bytes()) {
+        Ok(true) => ExitCode::Valid,
+        Ok(false) => {
+            output.verbose(&format!("{}: unchanged, not written\n", path));
+            ExitCode::Valid
+        }
+        Err(error) => {
+            output.problem(Severity::Error, path, None, "io", &error.to_string());
+            ExitCode::Io
+        }
+    }
+}
+
+/// Moves a problem found in the record on line `number`, starting `start` bytes into the input,
+/// from where it is in the record to where it is in the input.
+fn place_in_record(error: &mut ParseError, number: usize, start: usize) {
+    error.line = number;
+    error.offset += start;
+    if let Some((line, _, offset)) = &mut error.end {
+        *line = number;
+        *offset += start;
+    }
+}
+
+/// Opens an input to be read through once, without holding standard input in memory as `Source` does.
+fn open_stream(input: &Input, encoding: Encoding) -> Result<Box<dyn BufRead>, ParseError> {
+    let (raw, name, size): (Box<dyn Read>, &str, _) = match input {
+        Input::Stdin => (Box::new(stdin().lock()), STDIN, None),
+        Input::File(path) => (Box::new(File::open(path)?), path, std::fs::metadata(path).ok().map(|metadata| metadata.len())),
+    };
+    let stream = BufReader::new(encoding.decode(raw));
+    if !progress::enabled() { return Ok(Box::new(stream)); }
+    Ok(Box::new(progress::Meter::new(stream, name, size)))
+}
+
+/// Checks newline-delimited JSON one line at a time, so memory use does not grow with the input.
+/// Every record is checked even after a failure; positions in diagnostics are those of the whole input.
+/// With `--follow`, the records read after a file is truncated or replaced are numbered on from
+/// those before.
+fn check_ndjson(args: &Args, options: &ParseOptions, schema: Option<&JsonValue>, output: &Output, input: &Input) -> ExitCode {
+    let name = args.name(input);
+    let opened = match input {
+        Input::File(path) if args.follow => {
+            let follower = follow::Follower::open(path).map_err(ParseError::from);
+            follower.map(|follower| Box::new(BufReader::new(args.global.encoding.decode(Box::new(follower)))) as Box<dyn BufRead>)
+        }
+        _ => open_stream(input, args.global.encoding),
+    };
+    let mut reader = match opened {
+        Ok(reader) => reader,
+        Err(error) => {
+            output.diagnostic(Severity::Error, name, &error, None);
+            return ExitCode::Io;
+        }
+    };
+    // Every record of UTF-16 would fail, and none would say why.
+    if let Some(error) = reader.fill_buf().ok().and_then(|start| encoding_error(Encoding::detect(start))) {
+        output.diagnostic(Severity::Error, name, &error, None);
+        return ExitCode::Invalid;
+    }
+
+    let started = Instant::now();
+    let array_options = serialize_options(&args.formatting, output.colors_results());
+    let serialize_options = SerializeOptions { pretty: None, ..array_options.clone() };
+    let mut array = args.from_ndjson.then(|| ArrayPrinter::new(&array_options));
+    let mut code = ExitCode::Valid;
+    let mut bytes = Vec::new();
+    let (mut number, mut offset, mut records) = (0, 0, 0);
+
+    loop {
+        bytes.clear();
+        let remaining = options.max_bytes.map_or(u64::MAX, |limit| limit.saturating_sub(offset) as u64 + 1);
+        let length = match reader.as_mut().take(remaining).read_until(b'\n', &mut bytes) {
+            Ok(0) => break,
+            Ok(length) => length,
+            Err(error) => {
+                output.diagnostic(Severity::Error, name, &error.into(), None);
+                return code.worst(ExitCode::Io);
+            }
+        };
+        // A record still being written when following stopped is not judged.
+        if args.follow && !bytes.ends_with(b"\n") { break; }
+        number += 1;
+        let start = offset;
+        offset += length;
+        if let Some(limit) = options.max_bytes.filter(|&limit| offset > limit) {
+            let error = ParseError::new(ErrorKind::LimitExceeded, format!("more than {} bytes of input", limit), number, limit - start + 1, limit);
+            output.diagnostic(Severity::Error, name, &error, None);
+            return code.worst(ExitCode::TooLarge);
+        }
+
+        let line = String::from_utf8_lossy(&bytes);
+        if line.trim().is_empty() { continue; }
+        records += 1;
+
+        // The lexer decodes the record, so bytes that are not UTF-8 follow --invalid-utf8.
+        let mut syntax_analyser = SyntaxAnalyser::with_options(Lexer::new(Box::new(Cursor::new(bytes.clone()))), options.clone());
+        let (result, warnings) = (syntax_analyser.parse(), syntax_analyser.take_warnings());
+        for mut warning in warnings {
+            place_in_record(&mut warning, number, start);
+            output.diagnostic(Severity::Warning, name, &warning, Some(&line));
+        }
+        let mut document = match result {
+            Ok(document) => document,
+            Err(mut error) if error.kind == ErrorKind::LimitExceeded => {
+                place_in_record(&mut error, number, start);
+                output.diagnostic(Severity::Error, name, &error, None);
+                code = code.worst(ExitCode::TooLarge);
+                continue;
+            }
+            Err(mut error) => {
+                place_in_record(&mut error, number, start);
+                output.diagnostic(Severity::Error, name, &error, Some(&line));
+                code = code.worst(ExitCode::Invalid);
+                continue;
+            }
+        };
+
+        if let Some(schema) = schema {
+            if !check_schema(output, name, Some(number), &document, schema) {
+                code = code.worst(ExitCode::Invalid);
+                continue;
+            }
+        }
+
+        if !args.gets.is_empty() {
+            code = code.worst(get(args, output, name, &document));
+        } else if !args.selections.is_empty() {
+            code = code.worst(select(args, output, name, Some(number), &document, &serialize_options));
+        } else if args.rewrites() || args.from_ndjson {
+            match apply_edits(args, &mut document) {
+                Ok(()) if args.from_ndjson => array.as_mut().map_or((), |array| array.print(output, document)),
+                Ok(()) => output.result(&(to_string_with_options(&document, &serialize_options) + "\n")),
+                Err((failure, problem, message)) => {
+                    output.problem(Severity::Error, name, Some(number), problem, &message);
+                    code = code.worst(failure);
+                }
+            }
         }
     }
 
-    fn scan_tokens(&mut self) {
-        while !self.at_end() {
-            self.start = self.current_offset;
-            self.scan_token();
+    if let Some(array) = array {
+        array.finish(output);
+    }
+    output.verbose(&format!("{}: {} records in {} bytes, validated in {:.2?}\n", name, records, offset, started.elapsed()));
+    if code == ExitCode::Valid && args.gets.is_empty() && args.selections.is_empty() && !args.rewrites() && !args.from_ndjson {
+        output.result(&format!("{}: ok\n", name));
+    }
+    code
+}
+
+/// Prints each element of a document's root array as one line of newline-delimited JSON, reading
+/// and printing them one at a time so that memory use does not grow with the array.
+fn split_array(args: &Args, options: &ParseOptions, output: &Output, input: &Input) -> ExitCode {
+    let name = args.name(input);
+    let mut reader = match open_stream(input, args.global.encoding) {
+        Ok(stream) => EventReader::with_options(Lexer::new(stream), options.clone()),
+        Err(error) => {
+            output.diagnostic(Severity::Error, name, &error, None);
+            return ExitCode::Io;
         }
+    };
+    let serialize_options = SerializeOptions { pretty: None, ..serialize_options(&args.formatting, output.colors_results()) };
+
+    let result = print_elements(&mut reader, output, &serialize_options);
+
+    // Standard input has been read through by now, so only a file can show the offending line.
+    let source = match input {
+        Input::File(path) => Source::File(path.clone(), args.global.encoding),
+        Input::Stdin => Source::Stdin(Rc::from([])),
+    };
+    report_warnings(output, name, &source, &reader.take_warnings());
+    match result {
+        Ok(None) => ExitCode::Valid,
+        Ok(Some(found)) => {
+            output.problem(Severity::Error, name, None, "not-an-array", &format!("the root is not an array but {}", found));
+            ExitCode::Invalid
+        }
+        Err(error) => report_error(args, output, name, &source, &error),
     }
+}
 
-    fn next_token(&mut self) -> Option<Rc<Token>> {
-        if self.current_token >= self.tokens.len() { return None; }
+/// Prints the elements of the root array, one per line. When the root is something else, reads
+/// through it and succeeds with its type instead.
+fn print_elements(reader: &mut EventReader, output: &Output, options: &SerializeOptions) -> Result<Option<&'static str>, ParseError> {
+    let found = match reader.next_event()? {
+        Some(Event::StartArray) => None,
+        Some(Event::StartObject) => Some("object"),
+        Some(Event::Value(value)) => Some(value.type_name()),
+        _ => return Ok(None),
+    };
+    if found.is_some() {
+        while reader.next_event()?.is_some() {}
+        return Ok(found);
+    }
 
-        let token = self.tokens[self.current_token].clone();
-        self.current_token += 1;
-        Some(token)
+    while let Some(event) = reader.next_event()? {
+        if event == Event::EndArray { break; }
+        output.result(&(to_string_with_options(&reader.read_value(event)?, options) + "\n"));
     }
+    // Rejects anything after the root, as parsing does.
+    reader.next_event()?;
+    Ok(None)
 }
 
-struct SyntaxAnalyser {
-    lexer: Lexer,
-    next_token: Option<Rc<Token>>
+/// Prints documents one at a time as the elements of an array, laid out just as
+/// `to_string_with_options` lays out a whole array, so that --from-ndjson need not hold them all.
+/// The brackets and separators are cut from arrays of nulls written with the same options.
+struct ArrayPrinter<'a> {
+    options: &'a SerializeOptions,
+    open: String,
+    separator: String,
+    close: String,
+    printed: usize,
 }
 
-impl SyntaxAnalyser {
-    fn new (lexer: Lexer) -> SyntaxAnalyser {
-        SyntaxAnalyser {
-            lexer,
-            next_token: None
+impl ArrayPrinter<'_> {
+    fn new(options: &SerializeOptions) -> ArrayPrinter<'_> {
+        let null = to_string_with_options(&JsonValue::Null, options);
+        let one = to_string_with_options(&JsonValue::Array(vec![JsonValue::Null]), options);
+        let two = to_string_with_options(&JsonValue::Array(vec![JsonValue::Null; 2]), options);
+        // The brackets are only punctuation and whitespace, so the first null is the element.
+        let at = one.find(&null).unwrap_or_default();
+        let (open, close) = (one[..at].to_string(), one[at + null.len()..].to_string());
+        let separator = two[at + null.len()..two.len() - close.len() - null.len()].to_string();
+        ArrayPrinter { options, open, separator, close, printed: 0 }
+    }
+
+    fn print(&mut self, output: &Output, document: JsonValue) {
+        let text = to_string_with_options(&JsonValue::Array(vec![document]), self.options);
+        let element = &text[self.open.len()..text.len() - self.close.len()];
+        output.result(&format!("{}{}", if self.printed == 0 { &self.open } else { &self.separator }, element));
+        self.printed += 1;
+    }
+
+    fn finish(self, output: &Output) {
+        match self.printed {
+            0 => output.result(&(to_string_with_options(&JsonValue::Array(Vec::new()), self.options) + "\n")),
+            _ => output.result(&(self.close + "\n")),
         }
     }
+}
 
-    fn parse(&mut self) -> bool {
-        self.lexer.scan_tokens();
-        self.next_token = self.lexer.next_token();
-        self.object()
+/// Reads and parses a whole document, reporting why if it cannot.
+fn load_document(input: &Input, options: &ParseOptions, encoding: Encoding, output: &Output) -> Result<JsonValue, ExitCode> {
+    let name = match input {
+        Input::Stdin => "<stdin>",
+        Input::File(path) => path,
+    };
+    let source = Source::open(input, options.max_bytes, encoding, false).map_err(|error| {
+        output.diagnostic(Severity::Error, name, &error, None);
+        ExitCode::Io
+    })?;
+
+    match validate(&source, options).0 {
+        Ok(document) => Ok(document),
+        Err(error) if error.kind == ErrorKind::Io => {
+            output.diagnostic(Severity::Error, name, &error, None);
+            Err(ExitCode::Io)
+        }
+        Err(error) if error.kind == ErrorKind::LimitExceeded => {
+            output.diagnostic(Severity::Error, name, &error, None);
+            Err(ExitCode::TooLarge)
+        }
+        Err(error) => {
+            match source.utf16_error() {
+                Some(error) => output.diagnostic(Severity::Error, name, &error, None),
+                None => output.diagnostic(Severity::Error, name, &error, source.line(error.line).as_deref()),
+            }
+            Err(ExitCode::Invalid)
+        }
     }
+}
 
-    fn object(&mut self) -> bool {
-        if !self.match_token(TokenType::LeftBrace) { return false; }
+fn run_diff(args: &DiffArgs, output: &Output) -> ExitCode {
+    let options = args.global.parse_options();
+    let (old, new) = match (load_document(&args.old, &options, args.global.encoding, output), load_document(&args.new, &options, args.global.encoding, output)) {
+        (Ok(old), Ok(new)) => (old, new),
+        (old, new) => return old.err().unwrap_or(ExitCode::Valid).worst(new.err().unwrap_or(ExitCode::Valid)),
+    };
 
-        loop {
-            if self.match_token(TokenType::String) {
-                if !self.match_token(TokenType::Colon) { return false; }
-                if !self.value() { return false; }
+    let changes = diff(&old, &new);
+    match args.format {
+        DiffFormat::Text => {
+            for change in &changes {
+                output.result(&format!("{}\n", change));
             }
+        }
+        DiffFormat::Patch => {
+            let options = SerializeOptions { pretty: Some(PrettyConfig::default()), ..SerializeOptions::default() };
+            output.result(&(to_string_with_options(&to_patch(&changes), &options) + "\n"));
+        }
+    }
+    if changes.is_empty() { ExitCode::Valid } else { ExitCode::Invalid }
+}
 
-            if !self.match_token(TokenType::Comma) { break; }
+fn run_merge(args: &MergeArgs, output: &Output) -> ExitCode {
+    let options = args.global.parse_options();
+
+    let mut code = ExitCode::Valid;
+    let mut documents = Vec::new();
+    for input in &args.inputs {
+        match load_document(input, &options, args.global.encoding, output) {
+            Ok(document) => documents.push(document),
+            Err(error) => code = code.worst(error),
         }
+    }
+    if code != ExitCode::Valid { return code; }
+
+    let mut documents = documents.into_iter();
+    let mut merged = documents.next().unwrap_or(JsonValue::Null);
+    for overlay in documents {
+        merged.merge_with(&overlay, args.null_deletes, args.arrays);
+    }
+    output.result(&(to_string_with_options(&merged, &serialize_options(&args.formatting, output.colors_results())) + "\n"));
+    ExitCode::Valid
+}
+
+fn run_patch(args: &PatchArgs, output: &Output) -> ExitCode {
+    let options = args.global.parse_options();
+    let (mut document, patch) = match (load_document(&args.document, &options, args.global.encoding, output), load_document(&args.patch, &options, args.global.encoding, output)) {
+        (Ok(document), Ok(patch)) => (document, patch),
+        (document, patch) => return document.err().unwrap_or(ExitCode::Valid).worst(patch.err().unwrap_or(ExitCode::Valid)),
+    };
+
+    let name = match &args.patch {
+        Input::Stdin => "<stdin>",
+        Input::File(path) => path,
+    };
+    if let Err(error) = apply_patch(&mut document, &patch) {
+        output.problem(Severity::Error, name, None, "patch", &error.to_string());
+        return ExitCode::Invalid;
+    }
+    if args.dry_run { return ExitCode::Valid; }
+
+    let color = output.colors_results() && !args.write;
+    let text = to_string_with_options(&document, &serialize_options(&args.formatting, color)) + "\n";
+    match &args.document {
+        Input::File(path) if args.write => write_back(output, path, &text),
+        _ => {
+            output.result(&text);
+            ExitCode::Valid
+        }
+    }
+}
+
+fn run_explore(args: &ExploreArgs, output: &Output) -> ExitCode {
+    let document = match load_document(&args.input, &args.global.parse_options(), args.global.encoding, output) {
+        Ok(document) => document,
+        Err(code) => return code,
+    };
+    let terminal = stdin().is_terminal();
+    let options = serialize_options(&args.formatting, output.colors_results());
+    explore::explore(&document, &mut stdin().lock(), terminal, args.depth, &options, output);
+    ExitCode::Valid
+}
 
-        if !self.match_token(TokenType::RightBrace) { return false; }
-        true
+fn run_generate(args: &GenerateArgs, output: &Output) -> ExitCode {
+    let seed = args.seed.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64));
+    output.verbose(&format!("seed: {}\n", seed));
+    let options = serialize_options(&args.formatting, output.colors_results());
+    let mut generator = Generator::new(seed, args.options.clone());
+    for _ in 0..args.count {
+        output.result(&(to_string_with_options(&generator.document(), &options) + "\n"));
     }
+    ExitCode::Valid
+}
 
-    fn value(&mut self) -> bool {
-        if self.match_token(TokenType::String) { return true; }
-        if self.match_token(TokenType::Number) { return true; }
-        if self.match_token(TokenType::True) { return true; }
-        if self.match_token(TokenType::False) { return true; }
-        if self.match_token(TokenType::Null) { return true; }
-        if self.object() { return true; }
-        if self.array() { return true; }
-        false
+fn use_color(choice: ColorChoice, terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => terminal && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()),
     }
+}
 
-    fn array(&mut self) -> bool {
-        if !self.match_token(TokenType::LeftSquareBracket) { return false; }
+fn run(args: &Args, output: &Output) -> ExitCode {
+    let options = args.global.parse_options();
+    let mode = match options.mode {
+        Mode::Strict => "strict",
+        Mode::Lenient => "lenient",
+    };
+    output.verbose(&format!("options: mode {}, max depth {}\n", mode, options.max_depth));
+    if args.follow || args.watch { follow::catch_interrupt(); }
+    // Redrawing a line only makes sense on a terminal; elsewhere it would be noise in a log.
+    if args.progress && stderr().is_terminal() && !output.is_quiet() && !output.errors_as_json() { progress::enable(); }
+
+    let schema = match args.schema.as_deref().map(|path| load_schema(path, &options, output)).transpose() {
+        Ok(schema) => schema,
+        Err(code) => return code,
+    };
 
-        if self.value() && self.match_token(TokenType::Comma) {
-            loop {
-                if !self.value() { return false; }
-                if !self.match_token(TokenType::Comma) { break; }
+    let started = Instant::now();
+    let mut checked = Vec::new();
+    let mut inputs = args.inputs.clone();
+    if let Some(list) = &args.files_from {
+        match read_file_list(list, args.null_separated) {
+            Ok(paths) => inputs.extend(paths.into_iter().map(Input::File)),
+            Err(error) => {
+                output.problem(Severity::Error, args.name(list), None, "io", &error.to_string());
+                return ExitCode::Io;
             }
         }
+    }
+    if args.recursive {
+        let expanded = walk::expand(&inputs, &args.extensions, args.hidden);
+        for path in &expanded.skipped {
+            output.verbose(&format!("{}: skipped, looks binary\n", path));
+        }
+        for (path, error) in &expanded.errors {
+            output.problem(Severity::Error, path, None, "io", &error.to_string());
+            checked.push((path.clone(), ExitCode::Io, Duration::ZERO));
+        }
+        inputs = expanded.inputs;
+    }
 
-        if !self.match_token(TokenType::RightSquareBracket) { return false; }
-        true
+    let check_input = |output: &Output, input: &Input| {
+        let ndjson = args.ndjson || (args.recursive && matches!(input, Input::File(path) if path.ends_with(".ndjson")));
+        if args.to_ndjson {
+            split_array(args, &options, output, input)
+        } else if ndjson {
+            check_ndjson(args, &options, schema.as_ref(), output, input)
+        } else {
+            check(args, &options, schema.as_ref(), output, input)
+        }
+    };
+    if args.watch { return watch_files(args, output, check_input); }
+    // With --fail-fast, a directory that could not be read has already failed the run.
+    let inputs = if args.fail_fast && !checked.is_empty() { &[][..] } else { &inputs[..] };
+    // Following lasts until Ctrl-C, so each file needs a thread of its own, and what it finds
+    // is printed at once rather than after the files before it, which never finish.
+    let jobs = match args.follow {
+        true => inputs.len(),
+        false => args.jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, usize::from)),
+    };
+    if jobs > 1 && inputs.len() > 1 {
+        let results = check_in_parallel(inputs, jobs, args.fail_fast, args.follow, output, check_input);
+        checked.extend(inputs.iter().zip(results).map(|(input, (code, elapsed))| (args.name(input).to_string(), code, elapsed)));
+    } else {
+        for input in inputs {
+            let started = Instant::now();
+            let code = check_input(output, input);
+            checked.push((args.name(input).to_string(), code, started.elapsed()));
+            if args.fail_fast && code != ExitCode::Valid { break; }
+        }
     }
 
-    fn match_token(&mut self, token_type: TokenType) -> bool {
-        match &self.next_token {
-            None => { false }
-            Some(token) => {
-                if token.token_type == token_type {
-                    self.next_token = self.lexer.next_token();
-                    true
-                } else {
-                    false
-                }
+    let code = checked.iter().fold(ExitCode::Valid, |code, (_, checked, _)| code.worst(*checked));
+    report_summary(args, output, &checked, code, started.elapsed());
+    code
+}
+
+/// Prints the --summary of a run: by default a line of counts after more than one input.
+/// `checked` holds the name, outcome and time taken of each input in order.
+fn report_summary(args: &Args, output: &Output, checked: &[(String, ExitCode, Duration)], code: ExitCode, elapsed: Duration) {
+    let statuses = [ExitCode::Valid, ExitCode::Invalid, ExitCode::NotFound, ExitCode::NoLength, ExitCode::TooLarge, ExitCode::Io, ExitCode::Usage];
+    let count = |status: ExitCode| checked.iter().filter(|(_, code, _)| *code == status).count();
+    match args.summary {
+        Summary::Auto if checked.len() < 2 || output.is_quiet() || output.errors_as_json() => {}
+        Summary::Auto | Summary::Text => {
+            let counts: Vec<String> = statuses.iter()
+                .filter(|&&status| status == ExitCode::Valid || count(status) > 0)
+                .map(|&status| format!("{} {}", count(status), status.status()))
+                .collect();
+            let files = if checked.len() == 1 { "file" } else { "files" };
+            output.error(&format!("checked {} {}: {} ({:.2}s)\n", checked.len(), files, counts.join(", "), elapsed.as_secs_f64()));
+        }
+        Summary::Json => {
+            let mut members = vec![("files".to_string(), JsonValue::Number(checked.len() as f64))];
+            members.extend(statuses.iter().map(|&status| (status.status().replace('-', "_"), JsonValue::Number(count(status) as f64))));
+            members.push(("exit_code".to_string(), JsonValue::Number(code as i32 as f64)));
+            members.push(("seconds".to_string(), JsonValue::Number(seconds(elapsed))));
+            if args.global.level == Level::Verbose {
+                let inputs = checked.iter().map(|(name, code, elapsed)| JsonValue::Object(vec![
+                    ("file".to_string(), JsonValue::String(name.clone())),
+                    ("status".to_string(), JsonValue::String(code.status().to_string())),
+                    ("seconds".to_string(), JsonValue::Number(seconds(*elapsed))),
+                ]));
+                members.push(("inputs".to_string(), JsonValue::Array(inputs.collect())));
             }
+            output.error(&(JsonValue::Object(members).to_string() + "\n"));
         }
+        Summary::None => {}
     }
 }
 
-fn main() -> std::io::Result<()>  {
-    let args: Vec<String> = std::env::args().collect();
+/// A duration in seconds, to the microsecond, which is as precise as timing a check can be.
+fn seconds(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1e6).round() / 1e6
+}
 
-    let buffer: Box<dyn BufRead> = if args.len() == 1 {
-        Box::new(BufReader::new(stdin()))
-    } else {
-        let file = File::open(&args[1])?;
-        Box::new(BufReader::new(file))
+/// Checks each file again whenever it changes, until Ctrl-C, preceding its report with a line
+/// saying when and what happened. The exit status is decided by the last check of each file
+/// still there.
+fn watch_files(args: &Args, output: &Output, check_input: impl Fn(&Output, &Input) -> ExitCode) -> ExitCode {
+    let mut watcher = watch::Watcher::new(&args.inputs, &args.extensions, args.hidden);
+    let mut codes = BTreeMap::new();
+    loop {
+        let events = watcher.next();
+        if events.is_empty() { break; }
+        for event in events {
+            let (path, what) = match event {
+                watch::Event::Found(path) => (path, "found"),
+                watch::Event::Changed(path) => (path, "changed"),
+                watch::Event::Removed(path) => {
+                    output.result(&format!("[{}] {}: removed\n", clock(), path));
+                    codes.remove(&path);
+                    continue;
+                }
+            };
+            output.result(&format!("[{}] {}: {}\n", clock(), path, what));
+            let code = check_input(output, &Input::File(path.clone()));
+            codes.insert(path, code);
+        }
+    }
+    codes.into_values().fold(ExitCode::Valid, ExitCode::worst)
+}
+
+/// The time of day in UTC, as `hh:mm:ss`.
+fn clock() -> String {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()) % 86400;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+/// Checks the inputs on `jobs` threads. What each prints is held back and printed whole once
+/// everything before it has been, so the output is the same as checking them one at a time.
+/// With `streaming` it is printed as it comes instead, interleaved. With `fail_fast`, no input is started once one has failed; those already started are finished.
+/// Returns the outcome of each input reported, and how long it took, in input order.
+fn check_in_parallel(inputs: &[Input], jobs: usize, fail_fast: bool, streaming: bool, output: &Output, check_input: impl Fn(&Output, &Input) -> ExitCode + Sync) -> Vec<(ExitCode, Duration)> {
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.min(inputs.len()) {
+            let (next, failed, check_input, sender) = (&next, &failed, &check_input, sender.clone());
+            scope.spawn(move || {
+                // Inputs are taken in order, so those started are always all the ones before some index.
+                while !failed.load(Ordering::SeqCst) {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(input) = inputs.get(index) else { return };
+                    let recording = (!streaming).then(|| output.recording());
+                    let started = Instant::now();
+                    let code = check_input(recording.as_ref().unwrap_or(output), input);
+                    let elapsed = started.elapsed();
+                    if fail_fast && code != ExitCode::Valid { failed.store(true, Ordering::SeqCst); }
+                    let messages = recording.map_or_else(Vec::new, Output::into_recorded);
+                    if sender.send((index, (code, elapsed), messages)).is_err() { return; }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut results = Vec::new();
+        let mut finished = BTreeMap::new();
+        let mut printed = 0;
+        for (index, result, messages) in receiver {
+            finished.insert(index, (result, messages));
+            while let Some((result, messages)) = finished.remove(&printed) {
+                output.replay(messages);
+                results.push(result);
+                printed += 1;
+            }
+        }
+        results
+    })
+}
+
+/// The paths named in a --files-from list, skipping empty entries.
+fn read_file_list(list: &Input, null_separated: bool) -> std::io::Result<Vec<String>> {
+    let mut contents = Vec::new();
+    match list {
+        Input::Stdin => stdin().read_to_end(&mut contents)?,
+        Input::File(path) => File::open(path)?.read_to_end(&mut contents)?,
     };
+    let separator = if null_separated { b'\0' } else { b'\n' };
+    Ok(contents.split(|&byte| byte == separator)
+        .map(|path| if null_separated { path } else { path.strip_suffix(b"\r").unwrap_or(path) })
+        .filter(|path| !path.is_empty())
+        .map(|path| String::from_utf8_lossy(path).into_owned())
+        .collect())
+}
 
-    let lexer = Lexer::new(buffer);
-    let mut syntax_analyser = SyntaxAnalyser::new(lexer);
+fn usage_error(message: &str) -> ! {
+    Output::new(Level::Normal).error(&format!("error: {}\n{}\nTry 'json-parser-rs --help' for more information.\n", message, help::USAGE));
+    std::process::exit(ExitCode::Usage as i32);
+}
 
-    let valid = syntax_analyser.parse();
-    std::process::exit(!valid as i32);
-}
\ No newline at end of file
+fn main() {
+    let command = match args::parse(std::env::args().skip(1)) {
+        Ok(Command::Help(text)) => {
+            Output::new(Level::Normal).result(&text);
+            return;
+        }
+        Ok(Command::Version) => {
+            Output::new(Level::Normal).result(&format!("json-parser-rs {}\n", env!("CARGO_PKG_VERSION")));
+            return;
+        }
+        Ok(command) => command,
+        Err(message) => usage_error(&message),
+    };
+    let Some(global) = command.global() else { return };
+
+    if let Some(path) = &global.output {
+        if !global.force && command.files().iter().any(|file| write::same_file(file, path)) {
+            usage_error(&format!("'--output' would overwrite the input '{}'; use '--write' to edit files in place, or add '--force'", path));
+        }
+    }
+    let color_results = use_color(global.color, global.output.is_none() && stdout().is_terminal());
+    let output = Output::new(global.level)
+        .with_color(color_results, use_color(global.color, stderr().is_terminal()))
+        .with_error_format(global.error_format);
+    let output = match &global.output {
+        Some(path) => match write::Pending::create(path) {
+            Ok(file) => output.writing_to(file),
+            Err(error) => {
+                output.error(&format!("{}: error: {}\n", path, error));
+                std::process::exit(ExitCode::Io as i32);
+            }
+        },
+        None => output,
+    };
+
+    let code = match &command {
+        Command::Run(args) => run(args, &output),
+        Command::Diff(args) => run_diff(args, &output),
+        Command::Merge(args) => run_merge(args, &output),
+        Command::Patch(args) => run_patch(args, &output),
+        Command::Explore(args) => run_explore(args, &output),
+        Command::Generate(args) => run_generate(args, &output),
+        Command::Help(_) | Command::Version => ExitCode::Valid,
+    };
+
+    let code = match (&global.output, output.into_file()) {
+        (Some(path), Some(file)) => match file.commit() {
+            Ok(()) => code,
+            Err(error) => {
+                Output::new(Level::Normal).error(&format!("{}: error: {}\n", path, error));
+                code.worst(ExitCode::Io)
+            }
+        },
+        _ => code,
+    };
+    std::process::exit(code as i32);
+}
@@ -0,0 +1,121 @@
+/// What the parser came across where it expected something else.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Found<'a> {
+    /// The text of a token, e.g. `}` or `tru`.
+    Token(&'a str),
+    EndOfInput,
+    ByteOrderMark,
+    /// A comment, when comments are not allowed.
+    Comment,
+}
+
+/// What is wrong inside a string literal.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum StringProblem {
+    /// A character below U+0020 written as is; it holds the code point.
+    UnescapedControl(u32),
+    /// A backslash followed by a character that does not start an escape.
+    InvalidEscape(char),
+    /// `\u` not followed by four hexadecimal digits.
+    InvalidUnicodeEscape,
+    /// A `\u` escape of a lone surrogate, rejected unless parsing leniently.
+    UnpairedSurrogate,
+    /// A backslash at the very end of the string.
+    UnterminatedEscape,
+}
+
+/// Words the messages of parse errors and warnings, one method per kind of problem, so that they
+/// can be translated or restyled without touching the rest of the error. Every method defaults to
+/// the built-in English text, so an implementation only needs the ones it changes.
+///
+/// Errors from reading the input and from a `NumberParser` keep the text they come with.
+pub trait Messages {
+    /// `expected` is an English description of what would have been accepted, e.g. `"a value"`.
+    fn unexpected(&self, expected: &str, found: Found) -> String {
+        match found {
+            Found::Token(text) => format!("expected {}, found '{}'", expected, text),
+            Found::EndOfInput => format!("expected {}, found end of input", expected),
+            Found::ByteOrderMark => format!("expected {}, found a byte order mark (U+FEFF)", expected),
+            Found::Comment => "comments are not allowed".to_string(),
+        }
+    }
+
+    /// A word where a value belongs, most likely a string missing its quotes.
+    fn bareword(&self, text: &str) -> String {
+        format!("unexpected bareword '{}'; string values must be quoted", text)
+    }
+
+    /// Text that starts like a number but is not one, e.g. `123abc`.
+    fn invalid_number(&self, text: &str) -> String {
+        format!("invalid number '{}'", text)
+    }
+
+    /// A number such as `0755`, whose integer part starts with a zero, under `LeadingZeros::Error`.
+    fn leading_zeros(&self, text: &str) -> String {
+        format!("invalid number '{}': leading zeros are not allowed", text)
+    }
+
+    /// A number with leading zeros that `LeadingZeros::Octal` cannot read, e.g. `089` or `0755.5`.
+    fn invalid_octal(&self, text: &str) -> String {
+        format!("invalid octal number '{}'", text)
+    }
+
+    fn number_out_of_range(&self, _text: &str) -> String {
+        "number out of range".to_string()
+    }
+
+    /// The warning for unrecognizable text read as `null` under `replace_invalid`.
+    fn replaced(&self, text: &str) -> String {
+        format!("replaced '{}' with null", text)
+    }
+
+    fn too_deep(&self, max_depth: usize) -> String {
+        format!("nesting exceeds the maximum depth of {}", max_depth)
+    }
+
+    /// A key longer than `ParseOptions::max_key_length`; `object` is as for `duplicate_key`.
+    fn key_too_long(&self, bytes: usize, limit: usize, object: &str) -> String {
+        match object {
+            "" => format!("key of {} bytes in the root object is longer than the limit of {}", bytes, limit),
+            _ => format!("key of {} bytes in the object at {} is longer than the limit of {}", bytes, object, limit),
+        }
+    }
+
+    /// A string longer than `ParseOptions::max_value_bytes`; `pointer` is its JSON Pointer.
+    fn value_too_long(&self, bytes: usize, limit: usize, pointer: &str) -> String {
+        match pointer {
+            "" => format!("string of {} bytes at the root is longer than the limit of {}", bytes, limit),
+            _ => format!("string of {} bytes at {} is longer than the limit of {}", bytes, pointer, limit),
+        }
+    }
+
+    /// `object` is the JSON Pointer of the object holding the key, empty for the root.
+    fn duplicate_key(&self, key: &str, object: &str, first_line: usize, first_column: usize) -> String {
+        let key = crate::JsonValue::String(key.to_string());
+        match object {
+            "" => format!("duplicate key {} in the root object (first at line {}, column {})", key, first_line, first_column),
+            _ => format!("duplicate key {} in the object at {} (first at line {}, column {})", key, object, first_line, first_column),
+        }
+    }
+
+    fn invalid_string(&self, problem: StringProblem) -> String {
+        match problem {
+            StringProblem::UnescapedControl(code) => format!("control character U+{:04X} must be escaped", code),
+            StringProblem::InvalidEscape(c) => format!("invalid escape '\\{}'", c),
+            StringProblem::InvalidUnicodeEscape => "invalid unicode escape".to_string(),
+            StringProblem::UnpairedSurrogate => "unpaired surrogate in unicode escape".to_string(),
+            StringProblem::UnterminatedEscape => "unterminated escape".to_string(),
+        }
+    }
+
+    /// `description` names the bad bytes in English, e.g. "an overlong encoding starting with byte 0xC0".
+    fn invalid_utf8(&self, description: &str) -> String {
+        format!("invalid UTF-8: {}", description)
+    }
+}
+
+/// The built-in messages.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct English;
+
+impl Messages for English {}
@@ -0,0 +1,96 @@
+//! Parsing files through a memory map rather than buffered reads.
+//!
+//! # Safety
+//!
+//! A mapping shows the file as it is on disk, not as it was when it was opened. If another
+//! process modifies the file while it is being parsed, the parser may see a mix of old and new
+//! contents; if the file is truncated, reading past its new end raises `SIGBUS` and the process
+//! dies. Only map files nothing else will change for the duration of the parse.
+
+use std::ffi::c_void;
+use std::fs::File;
+use std::io::{self, BufRead, Read};
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+use crate::error::ParseError;
+use crate::lexer::Lexer;
+use crate::parser::SyntaxAnalyser;
+use crate::value::JsonValue;
+
+const PROT_READ: c_int = 1;
+const MAP_PRIVATE: c_int = 2;
+
+// `offset` is C's `off_t`, which is 64 bits on every 64-bit Unix; the module is only built there.
+extern "C" {
+    fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+/// A read-only private mapping of a whole file, read from start to end.
+struct Mapping {
+    address: *mut c_void,
+    length: usize,
+    position: usize,
+}
+
+impl Mapping {
+    fn open(path: &Path) -> io::Result<Mapping> {
+        let file = File::open(path)?;
+        let length = usize::try_from(file.metadata()?.len()).map_err(|_| io::Error::other("file is too large to map"))?;
+        // Mapping nothing is an error, but there is nothing to read either.
+        if length == 0 { return Ok(Mapping { address: std::ptr::null_mut(), length, position: 0 }); }
+
+        // SAFETY: a fresh mapping chosen by the kernel aliases no Rust memory, and it stays valid
+        // after the file is closed.
+        let address = unsafe { mmap(std::ptr::null_mut(), length, PROT_READ, MAP_PRIVATE, file.as_raw_fd(), 0) };
+        if address as isize == -1 { return Err(io::Error::last_os_error()); }
+        Ok(Mapping { address, length, position: 0 })
+    }
+
+    fn contents(&self) -> &[u8] {
+        if self.length == 0 { return &[]; }
+        // SAFETY: the mapping is `length` bytes, readable, and lives until `self` is dropped.
+        // See the module documentation for what happens if the file changes underneath it.
+        unsafe { std::slice::from_raw_parts(self.address as *const u8, self.length) }
+    }
+}
+
+impl Read for Mapping {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = self.fill_buf()?.read(buf)?;
+        self.consume(count);
+        Ok(count)
+    }
+}
+
+impl BufRead for Mapping {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        let position = self.position;
+        Ok(&self.contents()[position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.length);
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        if self.length == 0 { return; }
+        // SAFETY: unmapped exactly once, with no borrows of `contents` outstanding.
+        unsafe { munmap(self.address, self.length) };
+    }
+}
+
+/// Parses a file by memory-mapping it, which suits very large inputs. The file is not copied
+/// through a read buffer, but the lexer still copies each line into a `String` as it scans it,
+/// so a file that is one long line is held twice while it is parsed. The result is the same as
+/// parsing it through a `BufReader`.
+///
+/// Read the module documentation before using this on files other processes may modify.
+pub fn parse_mmap(path: impl AsRef<Path>) -> Result<JsonValue, ParseError> {
+    let mapping = Mapping::open(path.as_ref())?;
+    SyntaxAnalyser::new(Lexer::new(Box::new(mapping))).parse()
+}
@@ -0,0 +1,39 @@
+use crate::serializer::MAX_SAFE_INTEGER;
+use crate::value::JsonValue;
+
+/// Turns the text of a number token into a value. Implement this to parse numbers
+/// into something other than `f64`, e.g. to keep big integers exact.
+pub trait NumberParser {
+    fn parse_number(&mut self, text: &str) -> Result<JsonValue, String>;
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct F64NumberParser;
+
+impl NumberParser for F64NumberParser {
+    fn parse_number(&mut self, text: &str) -> Result<JsonValue, String> {
+        text.parse()
+            .map(JsonValue::Number)
+            .map_err(|_| format!("invalid number '{}'", text))
+    }
+}
+
+/// Parses like `F64NumberParser`, but keeps integers too large for an `f64` to hold exactly as
+/// their digits in a `JsonValue::String`, so that the digits survive being written back out.
+///
+/// Such a value is a string from then on, and nothing marks it as having been a number: it is
+/// written back quoted, and `get_required::<f64>` or `matches_schema` see a string where the
+/// input had a number. Use it where the digits matter more than the type, e.g. for identifiers.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct BigIntegerParser;
+
+impl NumberParser for BigIntegerParser {
+    fn parse_number(&mut self, text: &str) -> Result<JsonValue, String> {
+        let value = F64NumberParser.parse_number(text)?;
+        let integer = !text.contains(['.', 'e', 'E']);
+        match value {
+            JsonValue::Number(number) if integer && number.abs() > MAX_SAFE_INTEGER => Ok(JsonValue::String(text.to_string())),
+            value => Ok(value),
+        }
+    }
+}
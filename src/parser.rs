@@ -0,0 +1,240 @@
+use std::io::Cursor;
+
+use crate::error::ParseError;
+use crate::events::{Event, EventReader};
+use crate::lexer::{tokenize, Comment, Lexer, Token};
+use crate::messages::Messages;
+use crate::number::NumberParser;
+use crate::value::JsonValue;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum Mode {
+    #[default]
+    Strict,
+    /// Accepts input that strict JSON rejects, e.g. numbers that overflow to infinity, and
+    /// ignores a byte order mark between tokens as whitespace.
+    Lenient,
+}
+
+/// What to do when an object has the same key more than once. JSON allows it, but the meaning
+/// is unclear: this parser keeps every member, and lookups find the first. Keys are compared
+/// once their escapes are decoded, so `"a\u0062"` repeats `"ab"`.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum DuplicateKeys {
+    #[default]
+    Allow,
+    /// Record a warning, available from `take_warnings`.
+    Warn,
+    Error,
+}
+
+/// What to do with input bytes that are not valid UTF-8, such as surrogates encoded directly
+/// (CESU-8) or overlong encodings. Unlike `\u` escapes, these never reach the string decoder.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum InvalidUtf8 {
+    /// Fail with `ErrorKind::InvalidUtf8` at the first bad byte.
+    #[default]
+    Error,
+    /// Decode each bad sequence as U+FFFD, as `String::from_utf8_lossy` does.
+    Replace,
+}
+
+/// What to do with a number whose integer part has leading zeros, such as `0755`, which RFC 8259
+/// does not allow. Some older producers, following C, mean them as octal; others pad decimals.
+/// Nothing in the text says which, and the wrong choice silently changes the value: `0755` is
+/// 755 as decimal but 493 as octal. Only choose `Decimal` or `Octal` knowing what wrote the input.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum LeadingZeros {
+    #[default]
+    Error,
+    /// Read the digits as decimal, ignoring the zeros.
+    Decimal,
+    /// Read the digits as octal. A fraction, an exponent or the digits 8 and 9 are errors.
+    Octal,
+}
+
+/// Nesting allowed by `ParseOptions::default()`, far beyond hand-written documents but
+/// low enough that recursive code walking the result cannot run out of stack.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Composes a key to Unicode NFC, for `ParseOptions::normalize_key`, so that "caf\u{e9}" and
+/// "cafe\u{301}" are the same key.
+#[cfg(feature = "unicode-normalization")]
+pub fn nfc_key(key: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    key.nfc().collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    pub mode: Mode,
+    /// Skip `//` and `/* */` comments instead of rejecting them.
+    pub allow_comments: bool,
+    /// Accept a comma after the last member of an object or element of an array.
+    pub allow_trailing_commas: bool,
+    /// Accept strings in single quotes, as in JSON5: `'say "hi"'` needs no escapes, and `\'`
+    /// is accepted in strings of either kind.
+    pub allow_single_quotes: bool,
+    /// Accept object keys without quotes when they are identifiers, as in JSON5: letters, digits,
+    /// `_` and `$`, not starting with a digit, such as `max_size` or `$id`.
+    pub allow_unquoted_keys: bool,
+    /// Accept `NaN`, `Infinity` and `-Infinity` as numbers, as JavaScript writes them. JSON has
+    /// no form for them, so the serializer writes them as `null`.
+    pub allow_nan: bool,
+    /// Read unrecognizable text where a value belongs, such as a stray bareword, as `null`
+    /// and record a warning instead of failing.
+    pub replace_invalid: bool,
+    /// Stop after the first complete value, ignoring whatever follows it, rather than
+    /// requiring the input to hold exactly one value.
+    pub allow_trailing_data: bool,
+    /// Containers allowed inside one another before parsing fails.
+    pub max_depth: usize,
+    pub duplicate_keys: DuplicateKeys,
+    pub invalid_utf8: InvalidUtf8,
+    pub leading_zeros: LeadingZeros,
+    /// Applied to every object key as it is read, so keys that are equal after normalization
+    /// are stored, looked up and compared for duplicates identically. `nfc_key`, with the
+    /// `unicode-normalization` feature, composes keys to NFC; without it the crate carries no
+    /// Unicode tables, and a function composing just the characters expected will do.
+    pub normalize_key: Option<fn(&str) -> String>,
+    /// Bytes of input read before parsing fails, for input that cannot be trusted to be small.
+    /// Checked as the input is read, so no more than this is held in memory.
+    pub max_bytes: Option<usize>,
+    /// Tokens, comments included, read before parsing fails. Also checked as a long line is read,
+    /// so a line that never ends stops being read.
+    pub max_tokens: Option<usize>,
+    /// Bytes an object key may take, counted as written in the input, escapes included.
+    pub max_key_length: Option<usize>,
+    /// Bytes a string value may take, counted as for `max_key_length`.
+    pub max_value_bytes: Option<usize>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            mode: Mode::default(),
+            allow_comments: false,
+            allow_trailing_commas: false,
+            allow_single_quotes: false,
+            allow_unquoted_keys: false,
+            allow_nan: false,
+            replace_invalid: false,
+            allow_trailing_data: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            duplicate_keys: DuplicateKeys::Allow,
+            invalid_utf8: InvalidUtf8::Error,
+            leading_zeros: LeadingZeros::Error,
+            normalize_key: None,
+            max_bytes: None,
+            max_tokens: None,
+            max_key_length: None,
+            max_value_bytes: None
+        }
+    }
+}
+
+pub struct SyntaxAnalyser {
+    reader: EventReader
+}
+
+impl SyntaxAnalyser {
+    pub fn new(lexer: Lexer) -> SyntaxAnalyser {
+        SyntaxAnalyser::with_options(lexer, ParseOptions::default())
+    }
+
+    pub fn with_options(lexer: Lexer, options: ParseOptions) -> SyntaxAnalyser {
+        SyntaxAnalyser { reader: EventReader::with_options(lexer, options) }
+    }
+
+    pub fn with_number_parser(self, number_parser: Box<dyn NumberParser>) -> SyntaxAnalyser {
+        SyntaxAnalyser { reader: self.reader.with_number_parser(number_parser) }
+    }
+
+    /// Words error messages with `messages` instead of the built-in English.
+    pub fn with_messages(self, messages: Box<dyn Messages>) -> SyntaxAnalyser {
+        SyntaxAnalyser { reader: self.reader.with_messages(messages) }
+    }
+
+    /// Gives back the lexer, to `reset` and read another input with.
+    pub fn into_lexer(self) -> Lexer {
+        self.reader.into_lexer()
+    }
+
+    /// Builds the document from the reader's events. Open containers are kept on an explicit
+    /// stack, each with the key it will be stored under in its parent.
+    pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
+        let mut stack: Vec<(JsonValue, Option<String>)> = Vec::new();
+        let mut key = None;
+
+        loop {
+            let value = match self.reader.next_event()? {
+                Some(Event::StartObject) => {
+                    stack.push((JsonValue::Object(Vec::new()), key.take()));
+                    continue;
+                }
+                Some(Event::StartArray) => {
+                    stack.push((JsonValue::Array(Vec::new()), key.take()));
+                    continue;
+                }
+                Some(Event::Key(name)) => {
+                    key = Some(name);
+                    continue;
+                }
+                Some(Event::EndObject) | Some(Event::EndArray) => match stack.pop() {
+                    Some((container, parent_key)) => {
+                        key = parent_key;
+                        container
+                    }
+                    None => JsonValue::Null,
+                },
+                Some(Event::Value(value)) => value,
+                None => return Ok(JsonValue::Null),
+            };
+
+            match stack.last_mut() {
+                Some((JsonValue::Object(members), _)) => members.push((key.take().unwrap_or_default(), value)),
+                Some((JsonValue::Array(elements), _)) => elements.push(value),
+                _ => {
+                    // The root is complete; this also rejects anything after it.
+                    self.reader.next_event()?;
+                    return Ok(value);
+                }
+            }
+        }
+    }
+
+    /// Problems that `replace_invalid` let the last parse recover from.
+    pub fn take_warnings(&mut self) -> Vec<ParseError> {
+        self.reader.take_warnings()
+    }
+
+    /// Parses like `parse`, and also returns every comment in the input in source order.
+    /// Comments are only accepted when `allow_comments` is set.
+    pub fn parse_with_comments(&mut self) -> Result<(JsonValue, Vec<Comment>), ParseError> {
+        let value = self.parse()?;
+        Ok((value, self.reader.take_comments()))
+    }
+}
+
+/// Where parsing failed and the tokens around it, from `first_error_context`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorContext {
+    pub error: ParseError,
+    /// Up to the requested number of tokens that end before the error, in order.
+    pub before: Vec<Token>,
+    /// Up to the requested number of tokens from the one the error is in or at onwards.
+    pub after: Vec<Token>,
+}
+
+/// Parses `input` and, if it fails, returns the error with as many as `radius` tokens on each
+/// side of it, for tools that show more than a single line of context. Returns `None` when the
+/// input parses.
+pub fn first_error_context(input: &str, options: &ParseOptions, radius: usize) -> Option<ErrorContext> {
+    let lexer = Lexer::new(Box::new(Cursor::new(input.to_string())));
+    let error = SyntaxAnalyser::with_options(lexer, options.clone()).parse().err()?;
+    let tokens = tokenize(input).unwrap_or_default();
+    let split = tokens.partition_point(|token| token.offset() + token.text().len() <= error.offset);
+    let before = tokens[split.saturating_sub(radius)..split].to_vec();
+    let after = tokens[split..].iter().take(radius).cloned().collect();
+    Some(ErrorContext { error, before, after })
+}
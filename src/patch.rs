@@ -0,0 +1,118 @@
+use std::fmt;
+
+use crate::diff::diff;
+use crate::pointer::{array_index, parse_pointer};
+use crate::value::{prefix, JsonValue};
+
+/// Why an operation of a JSON Patch could not be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchError {
+    /// Position of the operation in the patch, from 0.
+    pub index: usize,
+    /// The operation's `op`, or an empty string if it has none.
+    pub op: String,
+    pub message: String,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.op.is_empty() { return write!(f, "operation {}: {}", self.index, self.message); }
+        write!(f, "operation {} ({}): {}", self.index, self.op, self.message)
+    }
+}
+
+/// Applies an RFC 6902 JSON Patch to `document`. Either every operation applies or, if one
+/// fails, `document` is left as it was and the error says which operation and why.
+pub fn apply_patch(document: &mut JsonValue, patch: &JsonValue) -> Result<(), PatchError> {
+    let JsonValue::Array(operations) = patch else {
+        return Err(PatchError { index: 0, op: String::new(), message: format!("a patch must be an array, found {}", patch.type_name()) });
+    };
+
+    let mut patched = document.clone();
+    for (index, operation) in operations.iter().enumerate() {
+        let op = match operation.pointer("/op") {
+            Some(JsonValue::String(op)) => op.as_str(),
+            _ => "",
+        };
+        apply(&mut patched, operation, op).map_err(|message| PatchError { index, op: op.to_string(), message })?;
+    }
+    *document = patched;
+    Ok(())
+}
+
+fn apply(document: &mut JsonValue, operation: &JsonValue, op: &str) -> Result<(), String> {
+    if !matches!(operation, JsonValue::Object(_)) {
+        return Err(format!("an operation must be an object, found {}", operation.type_name()));
+    }
+    let member = |name: &str| operation.pointer(&format!("/{}", name)).ok_or_else(|| format!("missing member '{}'", name));
+    let pointer = |name: &str| match member(name)? {
+        JsonValue::String(pointer) => parse_pointer(pointer).map(|_| pointer.as_str()),
+        other => Err(format!("'{}' must be a string, found {}", name, other.type_name())),
+    };
+
+    match op {
+        "add" => add(document, pointer("path")?, member("value")?.clone()),
+        "remove" => remove(document, pointer("path")?).map(|_| ()),
+        "replace" => {
+            let path = pointer("path")?;
+            let target = document.pointer_mut(path).ok_or_else(|| format!("path not found: '{}'", path))?;
+            *target = member("value")?.clone();
+            Ok(())
+        }
+        "move" => {
+            let (from, path) = (pointer("from")?, pointer("path")?);
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(format!("cannot move '{}' into itself", from));
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        "copy" => {
+            let (from, path) = (pointer("from")?, pointer("path")?);
+            let value = document.pointer(from).ok_or_else(|| format!("path not found: '{}'", from))?.clone();
+            add(document, path, value)
+        }
+        "test" => {
+            let path = pointer("path")?;
+            let actual = document.pointer(path).ok_or_else(|| format!("path not found: '{}'", path))?;
+            let expected = member("value")?;
+            if !diff(actual, expected).is_empty() {
+                return Err(format!("test failed: '{}' is {}, not {}", path, actual, expected));
+            }
+            Ok(())
+        }
+        "" => Err("missing member 'op'".to_string()),
+        other => Err(format!("unknown operation '{}'", other)),
+    }
+}
+
+/// Adds a member to an object, or inserts an element into an array, unlike `set_pointer` which replaces it.
+fn add(document: &mut JsonValue, path: &str, value: JsonValue) -> Result<(), String> {
+    let mut tokens = parse_pointer(path)?;
+    let Some(last) = tokens.pop() else {
+        *document = value;
+        return Ok(());
+    };
+
+    match document.pointer_mut(&prefix(&tokens)) {
+        Some(JsonValue::Object(members)) => match members.iter_mut().find(|(key, _)| *key == last) {
+            Some((_, existing)) => *existing = value,
+            None => members.push((last, value)),
+        },
+        Some(JsonValue::Array(elements)) => {
+            let index = if last == "-" { Some(elements.len()) } else { array_index(&last) };
+            match index {
+                Some(index) if index <= elements.len() => elements.insert(index, value),
+                _ => return Err(format!("array index out of range: '{}'", path)),
+            }
+        }
+        Some(_) => return Err(format!("cannot add a member to a scalar: '{}'", path)),
+        None => return Err(format!("path not found: '{}'", prefix(&tokens))),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut JsonValue, path: &str) -> Result<JsonValue, String> {
+    if path.is_empty() { return Ok(std::mem::replace(document, JsonValue::Null)); }
+    document.remove_pointer(path).ok_or_else(|| format!("path not found: '{}'", path))
+}
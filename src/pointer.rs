@@ -0,0 +1,68 @@
+/// One step of a path into a document, for lookups built in code rather than written as a
+/// JSON Pointer. `"users".into()` and `0.into()` make a key and an index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A member of an object.
+    Key(String),
+    /// An element of an array.
+    Index(usize),
+}
+
+impl From<&str> for PathSegment {
+    fn from(key: &str) -> PathSegment {
+        PathSegment::Key(key.to_string())
+    }
+}
+
+impl From<String> for PathSegment {
+    fn from(key: String) -> PathSegment {
+        PathSegment::Key(key)
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> PathSegment {
+        PathSegment::Index(index)
+    }
+}
+
+/// Splits an RFC 6901 JSON Pointer into its reference tokens, undoing the `~0`/`~1` escapes.
+/// The empty pointer refers to the whole document and has no tokens.
+pub fn parse_pointer(pointer: &str) -> Result<Vec<String>, String> {
+    if pointer.is_empty() { return Ok(Vec::new()); }
+
+    let Some(rest) = pointer.strip_prefix('/') else {
+        return Err(format!("invalid JSON pointer '{}': must be empty or start with '/'", pointer));
+    };
+
+    rest.split('/').map(|token| unescape_token(token).ok_or_else(|| {
+        format!("invalid JSON pointer '{}': '~' must be followed by '0' or '1'", pointer)
+    })).collect()
+}
+
+fn unescape_token(token: &str) -> Option<String> {
+    let mut result = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next()? {
+                '0' => result.push('~'),
+                '1' => result.push('/'),
+                _ => return None,
+            },
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+pub fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Parses an array index token. RFC 6901 does not allow signs or leading zeros.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) { return None; }
+    if !token.bytes().all(|b| b.is_ascii_digit()) { return None; }
+    token.parse().ok()
+}
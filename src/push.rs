@@ -0,0 +1,457 @@
+//! Parsing input pushed in as it arrives, such as from a socket, rather than pulled from a reader.
+
+use crate::error::{ErrorKind, ParseError};
+use crate::events::{has_leading_zeros, is_bareword, is_malformed_number, unescape, without_leading_zeros, Event};
+use crate::lexer::{describe_invalid_utf8, TokenType};
+use crate::messages::{English, Found, Messages};
+use crate::parser::{LeadingZeros, Mode, ParseOptions};
+use crate::value::JsonValue;
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Container {
+    Object,
+    Array,
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum State {
+    /// A value comes next: at the root, after a colon, or after a comma in an array.
+    Value,
+    /// Just after '{', where '}' may close an empty object.
+    FirstKey,
+    /// After a comma in an object.
+    Key,
+    /// After an object key.
+    Colon,
+    /// Just after '[', where ']' may close an empty array.
+    FirstElement,
+    /// After a complete value: a comma or the end of the enclosing container, or only whitespace at the root.
+    AfterValue,
+}
+
+/// The kind of token whose bytes are being collected, which may go on into the next chunk.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+enum Scan {
+    Between,
+    String { escaped: bool },
+    Number,
+    Word,
+    /// A `/`, which the next byte makes a comment or leaves a token of its own.
+    Slash,
+    /// A comment, scanned as the lexer does so it can be reported whole; `star` is whether the
+    /// last byte of a block comment was `*`.
+    Comment { block: bool, star: bool },
+}
+
+/// Line, column and byte offset, as in `ParseError`.
+type Position = (usize, usize, usize);
+
+/// Push parser: the counterpart of `EventReader` for callers that are handed bytes rather than
+/// able to read them. Each `feed` returns the events its chunk completes; a token or character
+/// split between chunks is held back until the rest of it arrives.
+///
+/// Of `ParseOptions`, only `mode`, `allow_trailing_commas`, `leading_zeros` and `max_depth`
+/// are supported, and `with_options` refuses any other set to something but its default.
+/// Comments and anything after the root value are always errors.
+pub struct Parser {
+    options: ParseOptions,
+    messages: Box<dyn Messages>,
+    stack: Vec<Container>,
+    state: State,
+    scan: Scan,
+    /// Bytes of the token being scanned, not yet checked to be UTF-8.
+    token: Vec<u8>,
+    /// Where the next byte goes, and where the token being scanned started.
+    position: Position,
+    start: Position,
+    /// Just past the last complete token, where a document cut short is reported to end.
+    last_end: Option<Position>,
+    /// Text of the object key waiting for its colon, for the error if none comes.
+    key: String,
+    events: Vec<Event>,
+    /// The first error, returned again by every later call.
+    error: Option<ParseError>,
+}
+
+impl Default for Parser {
+    fn default() -> Parser {
+        Parser::new()
+    }
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser::with_supported_options(ParseOptions::default())
+    }
+
+    /// Fails, naming them, if any options are set that the push parser does not support.
+    pub fn with_options(options: ParseOptions) -> Result<Parser, String> {
+        let default = ParseOptions::default();
+        let unsupported: Vec<&str> = [
+            ("allow_comments", options.allow_comments),
+            ("allow_single_quotes", options.allow_single_quotes),
+            ("allow_unquoted_keys", options.allow_unquoted_keys),
+            ("allow_nan", options.allow_nan),
+            ("replace_invalid", options.replace_invalid),
+            ("allow_trailing_data", options.allow_trailing_data),
+            ("duplicate_keys", options.duplicate_keys != default.duplicate_keys),
+            ("invalid_utf8", options.invalid_utf8 != default.invalid_utf8),
+            ("normalize_key", options.normalize_key.is_some()),
+            ("max_bytes", options.max_bytes.is_some()),
+            ("max_tokens", options.max_tokens.is_some()),
+            ("max_key_length", options.max_key_length.is_some()),
+            ("max_value_bytes", options.max_value_bytes.is_some()),
+        ].into_iter().filter(|&(_, set)| set).map(|(name, _)| name).collect();
+        if !unsupported.is_empty() {
+            return Err(format!("the push parser does not support {}", unsupported.join(", ")));
+        }
+        Ok(Parser::with_supported_options(options))
+    }
+
+    fn with_supported_options(options: ParseOptions) -> Parser {
+        Parser {
+            options,
+            messages: Box::new(English),
+            stack: Vec::new(),
+            state: State::Value,
+            scan: Scan::Between,
+            token: Vec::new(),
+            position: (1, 1, 0),
+            start: (1, 1, 0),
+            last_end: None,
+            key: String::new(),
+            events: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Words error messages with `messages` instead of the built-in English.
+    pub fn with_messages(mut self, messages: Box<dyn Messages>) -> Parser {
+        self.messages = messages;
+        self
+    }
+
+    /// Parses the next chunk of input and returns the events it completes. Once an error has
+    /// been returned, every later call returns it again.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Event>, ParseError> {
+        if let Some(error) = &self.error { return Err(error.clone()); }
+        for &byte in chunk {
+            if let Err(error) = self.byte(byte) {
+                self.error = Some(error.clone());
+                return Err(error);
+            }
+        }
+        Ok(std::mem::take(&mut self.events))
+    }
+
+    /// Ends the input, returning the events of a token that only its end completes, such as a
+    /// number at the root, or an error if the document is incomplete.
+    pub fn finish(mut self) -> Result<Vec<Event>, ParseError> {
+        if let Some(error) = self.error { return Err(error); }
+        match self.scan {
+            Scan::Between => {}
+            Scan::Number => self.end_token(TokenType::Number)?,
+            Scan::Word | Scan::Slash => self.end_token(TokenType::Other)?,
+            // Unterminated, so not a string or comment; the pull parser reports them the same way.
+            Scan::String { .. } | Scan::Comment { block: true, .. } => self.end_token(TokenType::Other)?,
+            Scan::Comment { block: false, .. } => return Err(self.comment()),
+        }
+        if self.stack.is_empty() && self.state == State::AfterValue { return Ok(self.events); }
+
+        let expected = self.expected();
+        let (line, column, offset) = self.last_end.unwrap_or(self.position);
+        Err(ParseError::new(ErrorKind::UnexpectedEnd, self.messages.unexpected(&expected, Found::EndOfInput), line, column, offset))
+    }
+
+    fn byte(&mut self, byte: u8) -> Result<(), ParseError> {
+        match self.scan {
+            Scan::String { escaped } => {
+                self.take(byte);
+                self.scan = match byte {
+                    b'"' if !escaped => return self.end_token(TokenType::String),
+                    b'\\' => Scan::String { escaped: !escaped },
+                    _ => Scan::String { escaped: false },
+                };
+                return Ok(());
+            }
+            Scan::Number | Scan::Word if self.continues(byte) => {
+                self.take(byte);
+                return Ok(());
+            }
+            Scan::Slash if byte == b'/' || byte == b'*' => {
+                self.take(byte);
+                self.scan = Scan::Comment { block: byte == b'*', star: byte == b'*' };
+                return Ok(());
+            }
+            Scan::Comment { block: false, .. } if byte != b'\n' && byte != b'\r' => {
+                self.take(byte);
+                return Ok(());
+            }
+            Scan::Comment { block: true, star } => {
+                self.take(byte);
+                if star && byte == b'/' { return Err(self.comment()); }
+                self.scan = Scan::Comment { block: true, star: byte == b'*' };
+                return Ok(());
+            }
+            Scan::Comment { block: false, .. } => return Err(self.comment()),
+            Scan::Number => self.end_token(TokenType::Number)?,
+            Scan::Word | Scan::Slash => self.end_token(TokenType::Other)?,
+            Scan::Between => {}
+        }
+
+        self.start = self.position;
+        let token_type = match byte {
+            b' ' | b'\t' | b'\n' | b'\r' => {
+                self.advance(byte);
+                return Ok(());
+            }
+            b'{' => TokenType::LeftBrace,
+            b'}' => TokenType::RightBrace,
+            b'[' => TokenType::LeftSquareBracket,
+            b']' => TokenType::RightSquareBracket,
+            b':' => TokenType::Colon,
+            b',' => TokenType::Comma,
+            b'"' => {
+                self.scan = Scan::String { escaped: false };
+                TokenType::String
+            }
+            b'/' => {
+                self.scan = Scan::Slash;
+                TokenType::Other
+            }
+            b'-' | b'0'..=b'9' => {
+                self.scan = Scan::Number;
+                TokenType::Number
+            }
+            _ if continues_word(byte) => {
+                self.scan = Scan::Word;
+                TokenType::Other
+            }
+            _ => TokenType::Other,
+        };
+        self.take(byte);
+        if self.scan != Scan::Between { return Ok(()); }
+        self.end_token(token_type)
+    }
+
+    /// Whether `byte` carries on the number or word being scanned.
+    fn continues(&self, byte: u8) -> bool {
+        match self.scan {
+            Scan::Number => continues_number(&self.token, byte),
+            _ => continues_word(byte),
+        }
+    }
+
+    fn take(&mut self, byte: u8) {
+        self.token.push(byte);
+        self.advance(byte);
+    }
+
+    fn advance(&mut self, byte: u8) {
+        let (line, column, offset) = &mut self.position;
+        *offset += 1;
+        if byte == b'\n' {
+            *line += 1;
+            *column = 1;
+        } else if byte & 0xC0 != 0x80 {
+            // Continuation bytes belong to the character their lead byte already counted.
+            *column += 1;
+        }
+    }
+
+    /// Checks the scanned token is UTF-8 and hands it to the grammar. Number and word tokens
+    /// are only classified here, once all of them is in.
+    fn end_token(&mut self, token_type: TokenType) -> Result<(), ParseError> {
+        self.scan = Scan::Between;
+        self.last_end = Some(self.position);
+        let text = match String::from_utf8(std::mem::take(&mut self.token)) {
+            Ok(text) => text,
+            Err(error) => {
+                let valid = error.utf8_error().valid_up_to();
+                let bytes = error.into_bytes();
+                let column = self.start.1 + std::str::from_utf8(&bytes[..valid]).map_or(0, |prefix| prefix.chars().count());
+                let message = self.messages.invalid_utf8(&describe_invalid_utf8(&bytes[valid..]));
+                return Err(ParseError::new(ErrorKind::InvalidUtf8, message, self.start.0, column, self.start.2 + valid));
+            }
+        };
+        let token_type = match token_type {
+            TokenType::Number if !is_number(&text) => TokenType::Other,
+            TokenType::Other if text == "true" => TokenType::True,
+            TokenType::Other if text == "false" => TokenType::False,
+            TokenType::Other if text == "null" => TokenType::Null,
+            // Lenient parsing skips byte order marks between tokens, as the pull parser does.
+            TokenType::Other if text == "\u{feff}" && self.options.mode == Mode::Lenient => return Ok(()),
+            token_type => token_type,
+        };
+        self.token(token_type, text)
+    }
+
+    fn token(&mut self, token_type: TokenType, text: String) -> Result<(), ParseError> {
+        match (self.state, token_type) {
+            (State::FirstElement, TokenType::RightSquareBracket) | (State::FirstKey, TokenType::RightBrace) => {
+                self.end_container();
+                Ok(())
+            }
+            (State::Value | State::FirstElement, _) => self.value(token_type, text),
+            (State::FirstKey | State::Key, TokenType::String) => {
+                let key = self.string(&text)?;
+                self.events.push(Event::Key(key));
+                self.key = text;
+                self.state = State::Colon;
+                Ok(())
+            }
+            (State::FirstKey | State::Key, _) => Err(self.unexpected(TokenType::String.describe(), &text)),
+            (State::Colon, TokenType::Colon) => {
+                self.state = State::Value;
+                Ok(())
+            }
+            (State::Colon, _) => Err(self.unexpected(&format!("':' after object key {}", self.key), &text)),
+            (State::AfterValue, _) => match (self.stack.last(), token_type) {
+                (None, _) => Err(self.unexpected("end of input", &text)),
+                (Some(Container::Object), TokenType::Comma) => {
+                    self.state = if self.options.allow_trailing_commas { State::FirstKey } else { State::Key };
+                    Ok(())
+                }
+                (Some(Container::Array), TokenType::Comma) => {
+                    self.state = if self.options.allow_trailing_commas { State::FirstElement } else { State::Value };
+                    Ok(())
+                }
+                (Some(Container::Object), TokenType::RightBrace) | (Some(Container::Array), TokenType::RightSquareBracket) => {
+                    self.end_container();
+                    Ok(())
+                }
+                (Some(Container::Object), _) => Err(self.unexpected("',' or '}'", &text)),
+                (Some(Container::Array), _) => Err(self.unexpected("',' or ']'", &text)),
+            },
+        }
+    }
+
+    fn value(&mut self, token_type: TokenType, text: String) -> Result<(), ParseError> {
+        let (container, state, event) = match token_type {
+            TokenType::LeftBrace => (Container::Object, State::FirstKey, Event::StartObject),
+            TokenType::LeftSquareBracket => (Container::Array, State::FirstElement, Event::StartArray),
+            _ => {
+                let value = self.scalar(token_type, &text)?;
+                self.events.push(Event::Value(value));
+                self.state = State::AfterValue;
+                return Ok(());
+            }
+        };
+        if self.stack.len() >= self.options.max_depth {
+            return Err(self.error_at(ErrorKind::TooDeep, self.messages.too_deep(self.options.max_depth)));
+        }
+        self.stack.push(container);
+        self.state = state;
+        self.events.push(event);
+        Ok(())
+    }
+
+    fn scalar(&self, token_type: TokenType, text: &str) -> Result<JsonValue, ParseError> {
+        match token_type {
+            TokenType::String => Ok(JsonValue::String(self.string(text)?)),
+            TokenType::Number if has_leading_zeros(text) => match without_leading_zeros(text, self.options.leading_zeros) {
+                Some(rewritten) => self.scalar(TokenType::Number, &rewritten),
+                None if self.options.leading_zeros == LeadingZeros::Octal => Err(self.error_at(ErrorKind::UnexpectedToken, self.messages.invalid_octal(text))),
+                None => Err(self.error_at(ErrorKind::UnexpectedToken, self.messages.leading_zeros(text))),
+            },
+            TokenType::Number => {
+                let number: f64 = text.parse().map_err(|_| self.error_at(ErrorKind::UnexpectedToken, self.messages.invalid_number(text)))?;
+                if number.is_infinite() && self.options.mode == Mode::Strict {
+                    return Err(self.error_at(ErrorKind::NumberOutOfRange, self.messages.number_out_of_range(text)));
+                }
+                Ok(JsonValue::Number(number))
+            }
+            TokenType::True => Ok(JsonValue::Bool(true)),
+            TokenType::False => Ok(JsonValue::Bool(false)),
+            TokenType::Null => Ok(JsonValue::Null),
+            TokenType::Other if is_bareword(text) => Err(self.error_at(ErrorKind::UnexpectedToken, self.messages.bareword(text))),
+            TokenType::Other if is_malformed_number(text) => Err(self.error_at(ErrorKind::UnexpectedToken, self.messages.invalid_number(text))),
+            _ => Err(self.unexpected("a value", text)),
+        }
+    }
+
+    fn string(&self, text: &str) -> Result<String, ParseError> {
+        let inner = &text[1..text.len() - 1];
+        unescape(inner, self.options.mode == Mode::Lenient, false).map_err(|(index, problem)| {
+            let (line, column, offset) = self.start;
+            let column = column + 1 + inner[..index].chars().count();
+            ParseError::new(ErrorKind::InvalidString, self.messages.invalid_string(problem), line, column, offset + 1 + index)
+        })
+    }
+
+    fn end_container(&mut self) {
+        self.state = State::AfterValue;
+        let event = match self.stack.pop() {
+            Some(Container::Array) => Event::EndArray,
+            _ => Event::EndObject,
+        };
+        self.events.push(event);
+    }
+
+    /// What the grammar would have taken in place of the token just scanned.
+    fn expected(&self) -> String {
+        match (self.state, self.stack.last()) {
+            (State::Value | State::FirstElement, _) => "a value".to_string(),
+            (State::FirstKey | State::Key, _) => TokenType::String.describe().to_string(),
+            (State::Colon, _) => format!("':' after object key {}", self.key),
+            (State::AfterValue, None) => "end of input".to_string(),
+            (State::AfterValue, Some(Container::Object)) => "',' or '}'".to_string(),
+            (State::AfterValue, _) => "',' or ']'".to_string(),
+        }
+    }
+
+    /// Comments are never allowed, so one is reported as soon as it ends.
+    fn comment(&self) -> ParseError {
+        self.error_at(ErrorKind::UnexpectedToken, self.messages.unexpected(&self.expected(), Found::Comment))
+    }
+
+    fn unexpected(&self, expected: &str, text: &str) -> ParseError {
+        let found = if text == "\u{feff}" { Found::ByteOrderMark } else { Found::Token(text) };
+        self.error_at(ErrorKind::UnexpectedToken, self.messages.unexpected(expected, found))
+    }
+
+    /// An error about the token just scanned.
+    fn error_at(&self, kind: ErrorKind, message: String) -> ParseError {
+        let (line, column, offset) = self.start;
+        let mut error = ParseError::new(kind, message, line, column, offset);
+        // As in the pull parser, the end of a token spanning lines is left unknown.
+        if self.position.0 == line {
+            error.end = Some(self.position);
+        }
+        error
+    }
+}
+
+/// Whether `byte` carries on the number token `token`, as the lexer scans them: digits, a sign
+/// right after the exponent, and letters, digits, dots or underscores run on, as in `123abc`,
+/// which make the whole run one malformed token.
+fn continues_number(token: &[u8], byte: u8) -> bool {
+    match byte {
+        b'+' | b'-' => matches!(token.last(), Some(b'e' | b'E')),
+        _ => byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'_',
+    }
+}
+
+/// Letters of a keyword or bareword. Any byte of a multibyte character counts, so that text
+/// outside strings is reported whole rather than a byte at a time.
+fn continues_word(byte: u8) -> bool {
+    byte.is_ascii_alphabetic() || byte >= 0x80
+}
+
+/// Whether `text` is a number as the lexer accepts them: `-?[0-9]+(\.[0-9]+)?([eE][+-]?[0-9]+)?`.
+fn is_number(text: &str) -> bool {
+    fn digits(text: &str) -> Option<&str> {
+        let rest = text.trim_start_matches(|c: char| c.is_ascii_digit());
+        if rest.len() == text.len() { None } else { Some(rest) }
+    }
+    let Some(mut rest) = digits(text.strip_prefix('-').unwrap_or(text)) else { return false };
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let Some(after) = digits(fraction) else { return false };
+        rest = after;
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let Some(after) = digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent)) else { return false };
+        rest = after;
+    }
+    rest.is_empty()
+}
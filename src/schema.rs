@@ -0,0 +1,138 @@
+use std::fmt;
+
+use crate::pointer::escape_token;
+use crate::value::JsonValue;
+
+/// One way in which a value fails a JSON Schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaError {
+    /// JSON Pointer to the offending part of the value.
+    pub pointer: String,
+    /// The schema keyword that failed, e.g. `required`.
+    pub keyword: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pointer = if self.pointer.is_empty() { "the root" } else { &self.pointer };
+        write!(f, "{} at {}: {}", self.keyword, pointer, self.message)
+    }
+}
+
+/// Validates `value` against a JSON Schema, returning every failure found (none if it is valid).
+///
+/// Only a subset of the specification is implemented: `type`, `required`, `properties`, `items`
+/// (a single schema for every element, or an array of schemas for the leading elements), `enum`,
+/// `minimum`/`maximum` and `minLength`/`maxLength`, plus the `true`/`false` schemas. Other keywords
+/// are ignored, so a schema using them accepts more than it should rather than less.
+pub fn validate_schema(value: &JsonValue, schema: &JsonValue) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    check(value, schema, &mut String::new(), &mut errors);
+    errors
+}
+
+fn check(value: &JsonValue, schema: &JsonValue, pointer: &mut String, errors: &mut Vec<SchemaError>) {
+    let mut fail = |keyword, message| errors.push(SchemaError { pointer: pointer.clone(), keyword, message });
+
+    let keywords = match schema {
+        JsonValue::Bool(true) => return,
+        JsonValue::Bool(false) => return fail("false", "no value is allowed here".to_string()),
+        JsonValue::Object(keywords) => keywords,
+        _ => return fail("schema", format!("invalid schema: expected an object or a boolean, found {}", schema.type_name())),
+    };
+    let keyword = |name: &str| keywords.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+
+    if let Some(expected) = keyword("type") {
+        let names: Vec<&str> = match expected {
+            JsonValue::String(name) => vec![name],
+            JsonValue::Array(names) => names.iter().filter_map(|name| match name {
+                JsonValue::String(name) => Some(name.as_str()),
+                _ => None,
+            }).collect(),
+            _ => Vec::new(),
+        };
+        if !names.iter().any(|name| has_type(value, name)) {
+            fail("type", format!("expected {}, found {}", names.join(" or "), value.type_name()));
+        }
+    }
+
+    if let Some(JsonValue::Array(allowed)) = keyword("enum") {
+        if !allowed.iter().any(|candidate| equal(candidate, value)) {
+            let allowed: Vec<String> = allowed.iter().map(|candidate| candidate.to_string()).collect();
+            fail("enum", format!("{} is not one of {}", value, allowed.join(", ")));
+        }
+    }
+
+    if let JsonValue::Number(number) = value {
+        if let Some(JsonValue::Number(minimum)) = keyword("minimum") {
+            if number < minimum { fail("minimum", format!("{} is less than the minimum of {}", value, JsonValue::Number(*minimum))); }
+        }
+        if let Some(JsonValue::Number(maximum)) = keyword("maximum") {
+            if number > maximum { fail("maximum", format!("{} is greater than the maximum of {}", value, JsonValue::Number(*maximum))); }
+        }
+    }
+
+    if let JsonValue::String(string) = value {
+        let length = string.chars().count();
+        if let Some(JsonValue::Number(minimum)) = keyword("minLength") {
+            if (length as f64) < *minimum { fail("minLength", format!("length {} is less than the minimum of {}", length, minimum)); }
+        }
+        if let Some(JsonValue::Number(maximum)) = keyword("maxLength") {
+            if (length as f64) > *maximum { fail("maxLength", format!("length {} is greater than the maximum of {}", length, maximum)); }
+        }
+    }
+
+    let length = pointer.len();
+    if let JsonValue::Object(members) = value {
+        if let Some(JsonValue::Array(required)) = keyword("required") {
+            for name in required {
+                let JsonValue::String(name) = name else { continue };
+                if !members.iter().any(|(key, _)| key == name) {
+                    fail("required", format!("missing required member '{}'", name));
+                }
+            }
+        }
+
+        if let Some(JsonValue::Object(properties)) = keyword("properties") {
+            for (key, member) in members {
+                let Some((_, property)) = properties.iter().find(|(name, _)| name == key) else { continue };
+                pointer.push('/');
+                pointer.push_str(&escape_token(key));
+                check(member, property, pointer, errors);
+                pointer.truncate(length);
+            }
+        }
+    }
+
+    if let JsonValue::Array(elements) = value {
+        let items: Vec<&JsonValue> = match keyword("items") {
+            Some(JsonValue::Array(items)) => items.iter().collect(),
+            Some(items) => vec![items; elements.len()],
+            None => Vec::new(),
+        };
+        for (index, (element, item)) in elements.iter().zip(items).enumerate() {
+            pointer.push_str(&format!("/{}", index));
+            check(element, item, pointer, errors);
+            pointer.truncate(length);
+        }
+    }
+}
+
+fn has_type(value: &JsonValue, name: &str) -> bool {
+    match (name, value) {
+        ("integer", JsonValue::Number(number)) => number.fract() == 0.0,
+        (name, value) => value.type_name() == name,
+    }
+}
+
+/// Equality as JSON Schema defines it, under which the order of object members does not matter.
+pub(crate) fn equal(a: &JsonValue, b: &JsonValue) -> bool {
+    match (a, b) {
+        (JsonValue::Object(a), JsonValue::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, value)| b.iter().any(|(other, other_value)| key == other && equal(value, other_value)))
+        }
+        (JsonValue::Array(a), JsonValue::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equal(a, b)),
+        (a, b) => a == b,
+    }
+}
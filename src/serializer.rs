@@ -0,0 +1,302 @@
+use std::cmp::Ordering;
+
+use crate::value::JsonValue;
+
+#[derive(Debug, Copy, Clone, Default)]
+pub enum KeyOrder {
+    /// Keys are written in the order they were inserted (or appeared in the source).
+    #[default]
+    Preserve,
+    Sorted,
+    Custom(fn(&str, &str) -> Ordering),
+}
+
+#[derive(Debug, Clone)]
+pub struct PrettyConfig {
+    /// Written once per nesting level at the start of each line.
+    pub indent: String,
+}
+
+impl Default for PrettyConfig {
+    fn default() -> PrettyConfig {
+        PrettyConfig { indent: "  ".to_string() }
+    }
+}
+
+/// Escape sequences written before each kind of token when highlighting output; each token is
+/// followed by a reset. The default is a terminal palette in the style of `jq`.
+#[derive(Debug, Clone)]
+pub struct Colors {
+    pub key: String,
+    pub string: String,
+    pub number: String,
+    /// `true`, `false` and `null`.
+    pub literal: String,
+    pub punctuation: String,
+}
+
+impl Default for Colors {
+    fn default() -> Colors {
+        Colors {
+            key: "\x1b[34;1m".to_string(),
+            string: "\x1b[32m".to_string(),
+            number: "\x1b[36m".to_string(),
+            literal: "\x1b[35m".to_string(),
+            punctuation: "\x1b[1m".to_string(),
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// Which numbers to write as strings, for consumers such as JavaScript that read every number
+/// as a double and would silently round large integers.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum NumbersAsStrings {
+    #[default]
+    Never,
+    /// Integers beyond ±(2^53 - 1), the largest a double holds exactly alongside its neighbours.
+    UnsafeIntegers,
+    All,
+}
+
+/// `Number.MAX_SAFE_INTEGER` in JavaScript.
+pub(crate) const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+#[derive(Debug, Clone, Default)]
+pub struct SerializeOptions {
+    pub key_order: KeyOrder,
+    /// Print one member or element per line; compact output when `None`.
+    pub pretty: Option<PrettyConfig>,
+    /// Highlight tokens for display in a terminal; plain output when `None`.
+    pub colors: Option<Colors>,
+    /// Write every non-ASCII character as a `\uXXXX` escape (a surrogate pair outside the
+    /// Basic Multilingual Plane), for consumers that cannot handle UTF-8.
+    pub escape_unicode: bool,
+    pub numbers_as_strings: NumbersAsStrings,
+    /// Write each run of whitespace in a string value as a single space, as HTML displays text.
+    /// Lossy, for text pipelines; keys are written as they are.
+    pub collapse_whitespace: bool,
+    /// Write negative zero as `0` rather than `-0`, as RFC 8785 requires.
+    pub unsigned_zero: bool,
+}
+
+impl SerializeOptions {
+    /// The canonical form of RFC 8785 (JCS), in which equal documents serialize to identical
+    /// bytes: compact, with keys sorted by their UTF-16 code units and negative zero written as
+    /// `0`. Other numbers and strings are already written the way it prescribes.
+    pub fn canonical() -> SerializeOptions {
+        SerializeOptions { key_order: KeyOrder::Custom(compare_utf16), unsigned_zero: true, ..SerializeOptions::default() }
+    }
+}
+
+fn compare_utf16(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+pub struct Serializer<'a> {
+    options: &'a SerializeOptions,
+    output: String,
+    depth: usize,
+    limit: usize,
+}
+
+impl<'a> Serializer<'a> {
+    pub fn new(options: &'a SerializeOptions) -> Serializer<'a> {
+        Serializer {
+            options,
+            output: String::new(),
+            depth: 0,
+            limit: usize::MAX,
+        }
+    }
+
+    /// Stops serializing soon after the output grows past `limit` bytes, rather than writing all of a large value.
+    pub fn with_limit(mut self, limit: usize) -> Serializer<'a> {
+        self.limit = limit;
+        self
+    }
+
+    pub fn serialize(mut self, value: &JsonValue) -> String {
+        self.value(value);
+        self.output
+    }
+
+    fn value(&mut self, value: &JsonValue) {
+        if self.output.len() > self.limit { return; }
+        match value {
+            JsonValue::Object(members) => self.object(members),
+            JsonValue::Array(elements) => self.array(elements),
+            JsonValue::String(string) if self.options.collapse_whitespace => self.string(&collapse_whitespace(string), |colors| &colors.string),
+            JsonValue::String(string) => self.string(string, |colors| &colors.string),
+            JsonValue::Number(number) if *number == 0.0 && self.options.unsigned_zero => self.token("0", |colors| &colors.number),
+            JsonValue::Number(number) if self.quotes(*number) => self.string(&format_number(*number), |colors| &colors.number),
+            JsonValue::Number(number) => self.token(&format_number(*number), |colors| &colors.number),
+            JsonValue::Bool(true) => self.token("true", |colors| &colors.literal),
+            JsonValue::Bool(false) => self.token("false", |colors| &colors.literal),
+            JsonValue::Null => self.token("null", |colors| &colors.literal),
+        }
+    }
+
+    fn quotes(&self, number: f64) -> bool {
+        match self.options.numbers_as_strings {
+            NumbersAsStrings::Never => false,
+            NumbersAsStrings::UnsafeIntegers => number.fract() == 0.0 && number.abs() > MAX_SAFE_INTEGER,
+            NumbersAsStrings::All => true,
+        }
+    }
+
+    fn object(&mut self, members: &[(String, JsonValue)]) {
+        let mut ordered: Vec<&(String, JsonValue)> = members.iter().collect();
+        match self.options.key_order {
+            KeyOrder::Preserve => { }
+            KeyOrder::Sorted => ordered.sort_by(|a, b| a.0.cmp(&b.0)),
+            KeyOrder::Custom(compare) => ordered.sort_by(|a, b| compare(&a.0, &b.0)),
+        }
+
+        self.punctuation("{");
+        if ordered.is_empty() {
+            self.punctuation("}");
+            return;
+        }
+
+        self.depth += 1;
+        for (index, (key, value)) in ordered.into_iter().enumerate() {
+            if self.output.len() > self.limit { return; }
+            if index > 0 { self.punctuation(","); }
+            self.newline();
+            self.string(key, |colors| &colors.key);
+            self.punctuation(":");
+            if self.options.pretty.is_some() { self.output.push(' '); }
+            self.value(value);
+        }
+        self.depth -= 1;
+        self.newline();
+        self.punctuation("}");
+    }
+
+    fn array(&mut self, elements: &[JsonValue]) {
+        self.punctuation("[");
+        if elements.is_empty() {
+            self.punctuation("]");
+            return;
+        }
+
+        self.depth += 1;
+        for (index, element) in elements.iter().enumerate() {
+            if self.output.len() > self.limit { return; }
+            if index > 0 { self.punctuation(","); }
+            self.newline();
+            self.value(element);
+        }
+        self.depth -= 1;
+        self.newline();
+        self.punctuation("]");
+    }
+
+    fn newline(&mut self) {
+        if let Some(pretty) = &self.options.pretty {
+            self.output.push('\n');
+            for _ in 0..self.depth {
+                self.output.push_str(&pretty.indent);
+            }
+        }
+    }
+
+    fn punctuation(&mut self, text: &str) {
+        self.token(text, |colors| &colors.punctuation);
+    }
+
+    /// Writes a token, surrounded by the color `color` picks when highlighting.
+    fn token(&mut self, text: &str, color: fn(&Colors) -> &String) {
+        self.start_color(color);
+        self.output.push_str(text);
+        self.end_color();
+    }
+
+    fn start_color(&mut self, color: fn(&Colors) -> &String) {
+        if let Some(colors) = &self.options.colors {
+            self.output.push_str(color(colors));
+        }
+    }
+
+    fn end_color(&mut self) {
+        if self.options.colors.is_some() {
+            self.output.push_str(RESET);
+        }
+    }
+
+    fn string(&mut self, string: &str, color: fn(&Colors) -> &String) {
+        self.start_color(color);
+        self.output.push('"');
+        for c in string.chars() {
+            match c {
+                '"' => self.output.push_str("\\\""),
+                '\\' => self.output.push_str("\\\\"),
+                '\n' => self.output.push_str("\\n"),
+                '\r' => self.output.push_str("\\r"),
+                '\t' => self.output.push_str("\\t"),
+                '\u{8}' => self.output.push_str("\\b"),
+                '\u{c}' => self.output.push_str("\\f"),
+                c if (c as u32) < 0x20 => self.output.push_str(&format!("\\u{:04x}", c as u32)),
+                c if !c.is_ascii() && self.options.escape_unicode => {
+                    for unit in c.encode_utf16(&mut [0; 2]) {
+                        self.output.push_str(&format!("\\u{:04x}", unit));
+                    }
+                }
+                c => self.output.push(c),
+            }
+        }
+        self.output.push('"');
+        self.end_color();
+    }
+}
+
+fn collapse_whitespace(string: &str) -> String {
+    let mut collapsed = String::with_capacity(string.len());
+    for c in string.chars() {
+        if !c.is_whitespace() {
+            collapsed.push(c);
+        } else if !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+    }
+    collapsed
+}
+
+/// Formats a number the way JavaScript does: plain decimals for everyday magnitudes,
+/// exponent notation outside `1e-6..1e21`. Non-finite numbers have no JSON form and become `null`.
+/// Unlike JavaScript, negative zero keeps its sign, which JSON can express as `-0`.
+fn format_number(number: f64) -> String {
+    if !number.is_finite() { return "null".to_string(); }
+    if number == 0.0 { return if number.is_sign_negative() { "-0" } else { "0" }.to_string(); }
+
+    let magnitude = number.abs();
+    if (1e-6..1e21).contains(&magnitude) {
+        format!("{}", number)
+    } else {
+        format!("{:e}", number).replace("e", "e+").replace("e+-", "e-")
+    }
+}
+
+pub fn to_string(value: &JsonValue) -> String {
+    to_string_with_options(value, &SerializeOptions::default())
+}
+
+pub fn to_string_with_options(value: &JsonValue, options: &SerializeOptions) -> String {
+    Serializer::new(options).serialize(value)
+}
+
+/// Serializes at most `limit` bytes of `value`, for previews in logs and messages. Also returns
+/// whether the output was cut short, in which case it is generally not valid JSON.
+pub fn to_string_with_limit(value: &JsonValue, limit: usize) -> (String, bool) {
+    let mut output = Serializer::new(&SerializeOptions::default()).with_limit(limit).serialize(value);
+    if output.len() <= limit { return (output, false); }
+
+    let mut end = limit;
+    while !output.is_char_boundary(end) {
+        end -= 1;
+    }
+    output.truncate(end);
+    (output, true)
+}
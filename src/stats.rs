@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+
+use crate::error::ParseError;
+use crate::events::{Event, EventReader};
+use crate::value::JsonValue;
+
+/// Counts describing the shape of a document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    /// Containers enclosing the most deeply nested value; 0 for a scalar document.
+    pub max_depth: usize,
+    /// Element count of the largest array.
+    pub longest_array: usize,
+    /// Object keys counted once however many objects use them.
+    pub distinct_keys: usize,
+    /// UTF-8 length of all string values once unescaped; keys are not included.
+    pub string_bytes: usize,
+}
+
+impl Stats {
+    /// Reads the rest of the document from `reader`, counting as it goes rather than building it.
+    pub fn collect(reader: &mut EventReader) -> Result<Stats, ParseError> {
+        let mut stats = Stats::default();
+        let mut keys = HashSet::new();
+        // Element counts of the open arrays, or `None` for open objects.
+        let mut open: Vec<Option<usize>> = Vec::new();
+
+        while let Some(event) = reader.next_event()? {
+            if let (Event::Value(_) | Event::StartObject | Event::StartArray, Some(Some(count))) = (&event, open.last_mut()) {
+                *count += 1;
+            }
+
+            match event {
+                Event::StartObject => {
+                    stats.objects += 1;
+                    open.push(None);
+                    stats.max_depth = stats.max_depth.max(open.len());
+                }
+                Event::StartArray => {
+                    stats.arrays += 1;
+                    open.push(Some(0));
+                    stats.max_depth = stats.max_depth.max(open.len());
+                }
+                Event::EndObject => { open.pop(); }
+                Event::EndArray => {
+                    if let Some(Some(count)) = open.pop() {
+                        stats.longest_array = stats.longest_array.max(count);
+                    }
+                }
+                Event::Key(key) => { keys.insert(key); }
+                Event::Value(JsonValue::String(string)) => {
+                    stats.strings += 1;
+                    stats.string_bytes += string.len();
+                }
+                Event::Value(JsonValue::Number(_)) => stats.numbers += 1,
+                Event::Value(JsonValue::Bool(_)) => stats.booleans += 1,
+                Event::Value(_) => stats.nulls += 1,
+            }
+        }
+
+        stats.distinct_keys = keys.len();
+        Ok(stats)
+    }
+}
@@ -0,0 +1,495 @@
+use std::fmt;
+use std::io::Cursor;
+use std::str::FromStr;
+
+use crate::error::ParseError;
+use crate::lexer::Lexer;
+use crate::parser::SyntaxAnalyser;
+use crate::pointer::{array_index, escape_token, parse_pointer, PathSegment};
+use crate::schema::equal;
+use crate::serializer;
+
+/// How `JsonValue::merge_with` combines an array in a patch with an array already there.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayMerge {
+    /// The patch's array replaces the other, as RFC 7386 has it.
+    #[default]
+    Replace,
+    /// The patch's elements are appended.
+    Concat,
+    /// The patch's elements are appended, except those equal to one already there; the order of
+    /// object members does not matter.
+    Union,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Object(Vec<(String, JsonValue)>),
+    Array(Vec<JsonValue>),
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Null,
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serializer::to_string(self))
+    }
+}
+
+impl FromStr for JsonValue {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<JsonValue, ParseError> {
+        SyntaxAnalyser::new(Lexer::new(Box::new(Cursor::new(s.to_string())))).parse()
+    }
+}
+
+impl JsonValue {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            JsonValue::Object(_) => "object",
+            JsonValue::Array(_) => "array",
+            JsonValue::String(_) => "string",
+            JsonValue::Number(_) => "number",
+            JsonValue::Bool(_) => "boolean",
+            JsonValue::Null => "null",
+        }
+    }
+
+    /// Whether the value is an object without members, an array without elements or an empty
+    /// string. Numbers, booleans and `null` are never empty, `0` and `false` included.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            JsonValue::Object(members) => members.is_empty(),
+            JsonValue::Array(elements) => elements.is_empty(),
+            JsonValue::String(string) => string.is_empty(),
+            JsonValue::Number(_) | JsonValue::Bool(_) | JsonValue::Null => false,
+        }
+    }
+
+    /// Consumes an object into its members, in document order, without copying them.
+    pub fn into_object(self) -> Option<Vec<(String, JsonValue)>> {
+        match self {
+            JsonValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+
+    /// Consumes an array into its elements without copying them.
+    pub fn into_array(self) -> Option<Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn into_string(self) -> Option<String> {
+        match self {
+            JsonValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// The member named `key`, or `None` if there is none or this is not an object. When the key
+    /// appears more than once, the first is found.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// The string member named `key`. A missing member, `null` and a value of another type all
+    /// give `None`; use `get_or_null` or `get_required` to tell them apart.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get_or_null(key).ok().flatten()
+    }
+
+    /// The member named `key` as a `T`, or `None` if it is `null`. Fails if there is no such
+    /// member, or if it is neither `null` nor a `T`.
+    pub fn get_or_null<'a, T: FromJsonValue<'a>>(&'a self, key: &str) -> Result<Option<T>, GetError> {
+        match self.get(key) {
+            None => Err(GetError::Missing(key.to_string())),
+            Some(JsonValue::Null) => Ok(None),
+            Some(value) => typed(key, value).map(Some),
+        }
+    }
+
+    /// The member named `key` as a `T`. Fails if there is no such member, if it is `null`, or if
+    /// it is another type, with a different `GetError` for each.
+    pub fn get_required<'a, T: FromJsonValue<'a>>(&'a self, key: &str) -> Result<T, GetError> {
+        match self.get(key) {
+            None => Err(GetError::Missing(key.to_string())),
+            Some(JsonValue::Null) => Err(GetError::Null(key.to_string())),
+            Some(value) => typed(key, value),
+        }
+    }
+
+    /// Moves the value out, leaving `null` in its place; useful for taking a member out of a
+    /// document held by reference, e.g. `document.pointer_mut("/items")`.
+    pub fn take(&mut self) -> JsonValue {
+        std::mem::replace(self, JsonValue::Null)
+    }
+
+    /// The value as a number, leniently: a number as it is, or a string holding a finite
+    /// number, such as `"42"`, `" 3.14 "` or `"1e3"`, with surrounding whitespace ignored.
+    /// Booleans, `null`, other strings, arrays and objects give `None`. For data that may have
+    /// been written with the wrong types; `get_required::<f64>` is the strict alternative.
+    pub fn coerce_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(number) => Some(*number),
+            JsonValue::String(string) => string.trim().parse::<f64>().ok().filter(|number| number.is_finite()),
+            _ => None,
+        }
+    }
+
+    /// The value as an integer, leniently: whatever `coerce_f64` accepts, as long as it has no
+    /// fractional part and fits, so `"42"` and `4.2e1` give 42 but `"4.5"` gives `None`.
+    pub fn coerce_i64(&self) -> Option<i64> {
+        i64::from_value(&JsonValue::Number(self.coerce_f64()?))
+    }
+
+    /// The value as a boolean, leniently: a boolean as it is, or the string `"true"` or
+    /// `"false"` in any case, with surrounding whitespace ignored. Numbers are not booleans.
+    pub fn coerce_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(boolean) => Some(*boolean),
+            JsonValue::String(string) if string.trim().eq_ignore_ascii_case("true") => Some(true),
+            JsonValue::String(string) if string.trim().eq_ignore_ascii_case("false") => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The value as a string, leniently: a string as it is, a number as the serializer writes
+    /// it (`3` rather than `3.0`), and a boolean as `"true"` or `"false"`. `null`, arrays and
+    /// objects give `None`.
+    pub fn coerce_string(&self) -> Option<String> {
+        match self {
+            JsonValue::String(string) => Some(string.clone()),
+            JsonValue::Number(_) => Some(serializer::to_string(self)),
+            JsonValue::Bool(boolean) => Some(boolean.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Looks up a value by RFC 6901 JSON Pointer, e.g. `/users/0/name`.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        let mut current = self;
+        for token in parse_pointer(pointer).ok()? {
+            current = match current {
+                JsonValue::Object(members) => members.iter().find(|(key, _)| *key == token).map(|(_, value)| value)?,
+                JsonValue::Array(elements) => elements.get(array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Looks up a value by a path of keys and indexes, e.g. `&["users".into(), 0.into()]`. Unlike a
+    /// pointer, nothing is parsed or unescaped, and a key never matches an array element.
+    pub fn get_path(&self, path: &[PathSegment]) -> Option<&JsonValue> {
+        let mut current = self;
+        for segment in path {
+            current = match (current, segment) {
+                (JsonValue::Object(members), PathSegment::Key(name)) => members.iter().find(|(key, _)| key == name).map(|(_, value)| value)?,
+                (JsonValue::Array(elements), PathSegment::Index(index)) => elements.get(*index)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        let mut current = self;
+        for token in parse_pointer(pointer).ok()? {
+            current = match current {
+                JsonValue::Object(members) => members.iter_mut().find(|(key, _)| *key == token).map(|(_, value)| value)?,
+                JsonValue::Array(elements) => elements.get_mut(array_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(current)
+    }
+
+    /// Assigns `value` at `pointer`, replacing what is there. Object members are added if missing,
+    /// and an index one past the end (or `-`) appends to an array. With `create_missing`, absent
+    /// parent members are created as empty objects instead of being an error.
+    pub fn set_pointer(&mut self, pointer: &str, value: JsonValue, create_missing: bool) -> Result<(), String> {
+        let mut tokens = parse_pointer(pointer)?;
+        let Some(last) = tokens.pop() else {
+            *self = value;
+            return Ok(());
+        };
+
+        let mut current = self;
+        for (depth, token) in tokens.iter().enumerate() {
+            let found = match current {
+                JsonValue::Object(members) => match members.iter().position(|(key, _)| key == token) {
+                    Some(index) => Some(&mut members[index].1),
+                    None if create_missing => {
+                        members.push((token.clone(), JsonValue::Object(Vec::new())));
+                        members.last_mut().map(|(_, value)| value)
+                    }
+                    None => None,
+                },
+                JsonValue::Array(elements) => array_index(token).and_then(|index| elements.get_mut(index)),
+                _ => None,
+            };
+            current = found.ok_or_else(|| format!("path not found: '{}'", prefix(&tokens[..=depth])))?;
+        }
+
+        match current {
+            JsonValue::Object(members) => match members.iter_mut().find(|(key, _)| *key == last) {
+                Some((_, existing)) => *existing = value,
+                None => members.push((last, value)),
+            },
+            JsonValue::Array(elements) => {
+                let index = if last == "-" { Some(elements.len()) } else { array_index(&last) };
+                match index {
+                    Some(index) if index < elements.len() => elements[index] = value,
+                    Some(index) if index == elements.len() => elements.push(value),
+                    _ => return Err(format!("array index out of range: '{}'", pointer)),
+                }
+            }
+            _ => return Err(format!("cannot set a member of a scalar: '{}'", pointer)),
+        }
+        Ok(())
+    }
+
+    /// Applies `patch` as an RFC 7386 JSON Merge Patch: objects are merged member by member at
+    /// every level, and anything else in the patch replaces what is here. A `null` member removes
+    /// the member of the same name or, when `null_deletes` is false, sets it to `null`.
+    pub fn merge(&mut self, patch: &JsonValue, null_deletes: bool) {
+        self.merge_with(patch, null_deletes, ArrayMerge::Replace);
+    }
+
+    /// The same as `merge`, except that wherever an array in the patch meets an array here, the
+    /// two are combined as `arrays` says.
+    pub fn merge_with(&mut self, patch: &JsonValue, null_deletes: bool, arrays: ArrayMerge) {
+        if let (JsonValue::Array(elements), JsonValue::Array(additions)) = (&mut *self, patch) {
+            if arrays != ArrayMerge::Replace {
+                for addition in additions {
+                    if arrays == ArrayMerge::Union && elements.iter().any(|element| equal(element, addition)) { continue; }
+                    elements.push(addition.clone());
+                }
+                return;
+            }
+        }
+        let JsonValue::Object(changes) = patch else {
+            *self = patch.clone();
+            return;
+        };
+        if !matches!(self, JsonValue::Object(_)) {
+            *self = JsonValue::Object(Vec::new());
+        }
+        let JsonValue::Object(members) = self else { return };
+
+        for (key, change) in changes {
+            let position = members.iter().position(|(name, _)| name == key);
+            match (position, change) {
+                (Some(index), JsonValue::Null) if null_deletes => { members.remove(index); }
+                (None, JsonValue::Null) if null_deletes => { }
+                (Some(index), change) => members[index].1.merge_with(change, null_deletes, arrays),
+                (None, change) => {
+                    let mut member = JsonValue::Null;
+                    member.merge_with(change, null_deletes, arrays);
+                    members.push((key.clone(), member));
+                }
+            }
+        }
+    }
+
+    /// Removes the value at `pointer` and returns it, or `None` if the pointer does not resolve.
+    /// Later elements of an array shift down to fill the gap. The root itself cannot be removed.
+    pub fn remove_pointer(&mut self, pointer: &str) -> Option<JsonValue> {
+        let mut tokens = parse_pointer(pointer).ok()?;
+        let last = tokens.pop()?;
+        let parent = self.pointer_mut(&prefix(&tokens))?;
+
+        match parent {
+            JsonValue::Object(members) => {
+                let index = members.iter().position(|(key, _)| *key == last)?;
+                Some(members.remove(index).1)
+            }
+            JsonValue::Array(elements) => {
+                let index = array_index(&last).filter(|&index| index < elements.len())?;
+                Some(elements.remove(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Keeps only the members of an object, or elements of an array, for which `keep` returns
+    /// true, in their order, like `Vec::retain`. `keep` is given the key of each member, and
+    /// `None` for each element. Only direct children are looked at; other values are unchanged.
+    pub fn retain<F: FnMut(Option<&str>, &JsonValue) -> bool>(&mut self, mut keep: F) {
+        match self {
+            JsonValue::Object(members) => members.retain(|(key, value)| keep(Some(key), value)),
+            JsonValue::Array(elements) => elements.retain(|element| keep(None, element)),
+            _ => {}
+        }
+    }
+
+    /// Gets the member `key` of an object for in-place manipulation, like `HashMap::entry`.
+    /// `Null` is first turned into an empty object, which makes building documents from
+    /// scratch convenient; any other non-object value returns `None`.
+    pub fn entry(&mut self, key: &str) -> Option<Entry<'_>> {
+        if *self == JsonValue::Null {
+            *self = JsonValue::Object(Vec::new());
+        }
+
+        match self {
+            JsonValue::Object(members) => {
+                let index = members.iter().position(|(name, _)| name == key);
+                Some(Entry { members, key: key.to_string(), index })
+            }
+            _ => None,
+        }
+    }
+}
+
+pub struct Entry<'a> {
+    members: &'a mut Vec<(String, JsonValue)>,
+    key: String,
+    index: Option<usize>,
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn or_insert(self, default: JsonValue) -> &'a mut JsonValue {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> JsonValue>(self, default: F) -> &'a mut JsonValue {
+        let index = match self.index {
+            Some(index) => index,
+            None => {
+                self.members.push((self.key, default()));
+                self.members.len() - 1
+            }
+        };
+        &mut self.members[index].1
+    }
+
+    /// Inserts `null` if the key is missing.
+    pub fn or_default(self) -> &'a mut JsonValue {
+        self.or_insert(JsonValue::Null)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut JsonValue)>(self, f: F) -> Entry<'a> {
+        if let Some(index) = self.index {
+            f(&mut self.members[index].1);
+        }
+        self
+    }
+}
+
+/// A type that `JsonValue::get_or_null` and `JsonValue::get_required` can read a member as.
+pub trait FromJsonValue<'a>: Sized {
+    /// What the type is called in a `GetError`, such as `"string"`.
+    const TYPE_NAME: &'static str;
+
+    fn from_value(value: &'a JsonValue) -> Option<Self>;
+}
+
+impl<'a> FromJsonValue<'a> for &'a str {
+    const TYPE_NAME: &'static str = "string";
+
+    fn from_value(value: &'a JsonValue) -> Option<&'a str> {
+        match value {
+            JsonValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+}
+
+impl FromJsonValue<'_> for f64 {
+    const TYPE_NAME: &'static str = "number";
+
+    fn from_value(value: &JsonValue) -> Option<f64> {
+        match value {
+            JsonValue::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+/// Only numbers with no fractional part that fit are integers.
+impl FromJsonValue<'_> for i64 {
+    const TYPE_NAME: &'static str = "integer";
+
+    fn from_value(value: &JsonValue) -> Option<i64> {
+        match value {
+            JsonValue::Number(number) if number.fract() == 0.0 && number.abs() < 2f64.powi(63) => Some(*number as i64),
+            _ => None,
+        }
+    }
+}
+
+impl FromJsonValue<'_> for bool {
+    const TYPE_NAME: &'static str = "boolean";
+
+    fn from_value(value: &JsonValue) -> Option<bool> {
+        match value {
+            JsonValue::Bool(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> FromJsonValue<'a> for &'a [JsonValue] {
+    const TYPE_NAME: &'static str = "array";
+
+    fn from_value(value: &'a JsonValue) -> Option<&'a [JsonValue]> {
+        match value {
+            JsonValue::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> FromJsonValue<'a> for &'a [(String, JsonValue)] {
+    const TYPE_NAME: &'static str = "object";
+
+    fn from_value(value: &'a JsonValue) -> Option<&'a [(String, JsonValue)]> {
+        match value {
+            JsonValue::Object(members) => Some(members),
+            _ => None,
+        }
+    }
+}
+
+/// Why `JsonValue::get_or_null` or `JsonValue::get_required` gave no value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GetError {
+    /// There is no member with the key, or the value is not an object.
+    Missing(String),
+    /// The member is `null`; only `get_required` fails this way.
+    Null(String),
+    WrongType { key: String, expected: &'static str, found: &'static str },
+}
+
+impl fmt::Display for GetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GetError::Missing(key) => write!(f, "missing key '{}'", key),
+            GetError::Null(key) => write!(f, "key '{}' is null", key),
+            GetError::WrongType { key, expected, found } => write!(f, "key '{}' is {} where {} was expected", key, found, expected),
+        }
+    }
+}
+
+impl std::error::Error for GetError {}
+
+fn typed<'a, T: FromJsonValue<'a>>(key: &str, value: &'a JsonValue) -> Result<T, GetError> {
+    T::from_value(value).ok_or_else(|| GetError::WrongType { key: key.to_string(), expected: T::TYPE_NAME, found: value.type_name() })
+}
+
+pub(crate) fn prefix(tokens: &[String]) -> String {
+    tokens.iter().map(|token| format!("/{}", escape_token(token))).collect()
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "arena")]
+
+use std::io::Cursor;
+
+use json_parser_rs::{parse_in_arena, to_string, Arena, GenerateOptions, Generator, JsonValue, Lexer, ParseOptions, SyntaxAnalyser};
+
+#[test]
+fn arena_documents_match_parsed_values() {
+    let mut arena = Arena::new();
+    for seed in 0..100 {
+        let text = to_string(&Generator::new(seed, GenerateOptions::default()).document());
+        let expected = SyntaxAnalyser::new(Lexer::new(Box::new(Cursor::new(text.clone())))).parse().unwrap();
+        let value = parse_in_arena(&mut arena, &text, &ParseOptions::default()).unwrap();
+        assert_eq!(value.to_value(), expected, "seed {}", seed);
+    }
+}
+
+#[test]
+fn arena_values_are_read_in_place() {
+    let mut arena = Arena::with_capacity(16, 64);
+    let root = parse_in_arena(&mut arena, r#"{"name": "x", "tags": [1, true, null], "empty": {}}"#, &ParseOptions::default()).unwrap();
+    assert_eq!(root.len(), 3);
+    assert_eq!(root.get("name").and_then(|name| name.as_str()), Some("x"));
+    let tags: Vec<_> = root.get("tags").unwrap().elements().collect();
+    assert_eq!(tags.len(), 3);
+    assert_eq!(tags[0].as_f64(), Some(1.0));
+    assert_eq!(tags[1].as_bool(), Some(true));
+    assert!(tags[2].is_null());
+    assert!(root.get("empty").unwrap().is_empty());
+    assert!(root.get("missing").is_none());
+    let keys: Vec<_> = root.members().map(|(key, _)| key).collect();
+    assert_eq!(keys, ["name", "tags", "empty"]);
+
+    let root = parse_in_arena(&mut arena, "[[], 2]", &ParseOptions::default()).unwrap();
+    assert_eq!(root.to_value(), JsonValue::Array(vec![JsonValue::Array(vec![]), JsonValue::Number(2.0)]));
+    assert!(parse_in_arena(&mut arena, "[1,", &ParseOptions::default()).is_err());
+}
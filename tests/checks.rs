@@ -0,0 +1,34 @@
+use json_parser_rs::{find_mixed_arrays, matches_schema, JsonValue, MixedArray};
+
+fn parse(text: &str) -> JsonValue {
+    text.parse().unwrap()
+}
+
+#[test]
+fn mixed_arrays_are_found_at_any_depth() {
+    assert_eq!(find_mixed_arrays(&parse(r#"[1, "a", true]"#)), [MixedArray { pointer: String::new(), types: vec!["number", "string", "boolean"] }]);
+
+    let document = parse(r#"{"ok": [1, 2.5], "a/b": [{"c": [null, "x", null]}, {}], "empty": []}"#);
+    assert_eq!(find_mixed_arrays(&document), [MixedArray { pointer: "/a~1b/0/c".to_string(), types: vec!["null", "string"] }]);
+}
+
+#[test]
+fn documents_that_fit_the_schema_match() {
+    let schema = parse(r#"{"name": "string", "tags": ["string"], "owner": {"id": "number"}, "extra": "any", "list": []}"#);
+    let document = parse(r#"{"name": "a", "tags": [], "owner": {"id": 1, "role": "admin"}, "extra": null, "list": [1, "x"], "more": true}"#);
+    assert_eq!(matches_schema(&document, &schema), Ok(()));
+    assert_eq!(matches_schema(&parse(r#"["a", "b"]"#), &parse(r#"["string"]"#)), Ok(()));
+}
+
+#[test]
+fn mismatches_name_where_and_why() {
+    let schema = parse(r#"{"name": "string", "tags": ["string"], "owner": {"id": "number"}}"#);
+    let mismatch = |text: &str| matches_schema(&parse(text), &schema).unwrap_err().to_string();
+    assert_eq!(mismatch(r#"{"name": 1, "tags": [], "owner": {"id": 1}}"#), "at /name: expected string, found number");
+    assert_eq!(mismatch(r#"{"name": "a", "tags": ["x", null], "owner": {"id": 1}}"#), "at /tags/1: expected string, found null");
+    assert_eq!(mismatch(r#"{"name": "a", "tags": [], "owner": {}}"#), "at /owner: missing member 'id'");
+    assert_eq!(mismatch("[]"), "at the root: expected object, found array");
+
+    let error = matches_schema(&parse("1"), &parse(r#""integer""#)).unwrap_err();
+    assert_eq!((error.pointer.as_str(), error.message.as_str()), ("", "invalid schema: unknown type 'integer'"));
+}
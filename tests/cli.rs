@@ -0,0 +1,1021 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+use json_parser_rs::JsonValue;
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+        .args(args)
+        .stdin(File::open(stdin).unwrap())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap()
+}
+
+/// An empty directory of its own for each test.
+fn temporary_directory(test: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("json-parser-rs-{}-{}", std::process::id(), test));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+/// Runs every `tests/<topic>/valid*.json` and `invalid*.json`, with the arguments in the `.args`
+/// file beside it when there is one, and compares what it prints with the `.stdout` and
+/// `.stderr` files, a missing one meaning nothing is printed. Valid fixtures must exit with
+/// status 0 and invalid ones with any other, whether the document is malformed or the command
+/// fails on a well-formed one; the `.stderr` file pins which.
+#[test]
+fn fixtures_match_their_expected_output() {
+    let mut fixtures: Vec<PathBuf> = fs::read_dir("tests").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .flat_map(|directory| fs::read_dir(directory).unwrap().map(|entry| entry.unwrap().path()))
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_str().unwrap();
+            (name.starts_with("valid") || name.starts_with("invalid")) && name.ends_with(".json")
+        })
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        let sidecar = |extension: &str| fs::read_to_string(fixture.with_extension(extension)).ok();
+        let args = sidecar("args");
+        let output = Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+            .args(args.as_deref().unwrap_or("").split_whitespace())
+            .arg(fixture)
+            .env_remove("COLUMNS")
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        let name = fixture.display();
+        let valid = fixture.file_name().unwrap().to_str().unwrap().starts_with("valid");
+        if output.status.success() != valid {
+            failures.push(format!("{}: exited with {:?}", name, output.status.code()));
+        }
+        // Without arguments a fixture only has its status checked.
+        if args.is_none() {
+            continue;
+        }
+        if String::from_utf8_lossy(&output.stdout) != sidecar("stdout").unwrap_or_default() {
+            failures.push(format!("{}: standard output differs from {}", name, fixture.with_extension("stdout").display()));
+        }
+        if String::from_utf8_lossy(&output.stderr) != sidecar("stderr").unwrap_or_default() {
+            failures.push(format!("{}: standard error differs from {}", name, fixture.with_extension("stderr").display()));
+        }
+    }
+    assert!(failures.is_empty(), "{} of {} fixtures failed:\n{}", failures.len(), fixtures.len(), failures.join("\n"));
+}
+
+#[test]
+fn several_files_are_checked_independently() {
+    let output = run(&["tests/step2/valid.json", "tests/step2/invalid.json", "does-not-exist.json", "tests/step4/valid.json"], "tests/step2/valid.json");
+    assert_ne!(output.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "tests/step2/valid.json: ok\ntests/step4/valid.json: ok\n");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("tests/step2/invalid.json:1:17: error:"), "{}", stderr);
+    assert!(stderr.contains("does-not-exist.json: error:"), "{}", stderr);
+}
+
+#[test]
+fn bare_invocation_validates_stdin() {
+    let valid = run(&[], "tests/step2/valid.json");
+    assert_eq!(valid.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&valid.stdout), "<stdin>: ok\n");
+
+    let invalid = run(&[], "tests/step2/invalid.json");
+    assert_eq!(invalid.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&invalid.stderr).starts_with("<stdin>:1:17: error: expected a string"));
+}
+
+#[test]
+fn validate_command_matches_bare_invocation() {
+    for input in ["tests/step2/valid.json", "tests/step2/invalid.json"] {
+        let bare = run(&["--lenient"], input);
+        let validate = run(&["validate", "--lenient"], input);
+        assert_eq!(bare.status.code(), validate.status.code());
+        assert_eq!(bare.stdout, validate.stdout);
+        assert_eq!(bare.stderr, validate.stderr);
+    }
+}
+
+#[test]
+fn commands_read_stdin() {
+    let formatted = run(&["format", "--compact"], "tests/step2/valid.json");
+    assert_eq!(String::from_utf8_lossy(&formatted.stdout), "{\"key\":\"value\"}\n");
+
+    let value = run(&["get", "/key"], "tests/step2/valid.json");
+    assert_eq!(String::from_utf8_lossy(&value.stdout), "value\n");
+}
+
+#[test]
+fn global_options_may_come_before_the_command() {
+    let file = "tests/commands/valid6.json";
+    let before = run(&["--lenient", "-o", "-", "format", "--compact", file], "tests/step2/valid.json");
+    let after = run(&["format", "--compact", file, "--lenient", "-o", "-"], "tests/step2/valid.json");
+    assert_eq!(before.status.code(), Some(0), "{}", String::from_utf8_lossy(&before.stderr));
+    assert_eq!(before.stdout, after.stdout);
+
+    // The value of an option is not taken for a command.
+    let named = run(&["--stdin-filename", "format", "-"], "tests/step2/invalid.json");
+    assert!(String::from_utf8_lossy(&named.stderr).starts_with("format:1:"), "{}", String::from_utf8_lossy(&named.stderr));
+}
+
+#[test]
+fn every_command_has_help() {
+    for command in ["validate", "format", "get", "set", "stats", "diff", "merge", "patch", "explore", "generate"] {
+        let help = run(&[command, "--help"], "tests/step2/valid.json");
+        assert_eq!(help.status.code(), Some(0));
+        let text = String::from_utf8_lossy(&help.stdout);
+        assert!(text.contains(&format!("Usage: json-parser-rs {} [OPTIONS]", command)), "{}", text);
+        assert!(text.contains("Global options:"), "{}", text);
+    }
+}
+
+#[test]
+fn help_lists_every_option() {
+    let mut help = String::from_utf8(run(&["--help"], "tests/step2/valid.json").stdout).unwrap();
+    for command in ["validate", "format", "get", "set", "stats", "diff", "merge", "patch", "explore", "generate"] {
+        help.push_str(&String::from_utf8(run(&[command, "--help"], "tests/step2/valid.json").stdout).unwrap());
+    }
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '-';
+    let source = fs::read_to_string("src/cli/args.rs").unwrap();
+    let mut options: Vec<&str> = source.match_indices("\"--")
+        .map(|(start, _)| &source[start + 1..])
+        .map(|rest| &rest[..2 + rest[2..].find(|c| !is_name(c)).unwrap()])
+        .filter(|option| option.len() > 2)
+        .collect();
+    options.sort();
+    options.dedup();
+    for option in options {
+        let listed = help.match_indices(option).any(|(start, _)| !help[start + option.len()..].starts_with(is_name));
+        assert!(listed, "{} is not in any help", option);
+    }
+}
+
+#[test]
+fn exit_status_tells_failures_apart() {
+    let directory = temporary_directory("exit-status");
+    let status = |args: &[&str]| run(args, "tests/step2/valid.json").status.code();
+    assert_eq!(status(&["tests/step2/valid.json"]), Some(0));
+    assert_eq!(status(&["tests/step2/invalid.json"]), Some(1));
+    assert_eq!(status(&["--no-such-option", "tests/step2/valid.json"]), Some(2));
+    assert_eq!(status(&["does-not-exist.json"]), Some(3));
+    // A directory opens but cannot be read, whoever runs the tests.
+    assert_eq!(status(&[directory.to_str().unwrap()]), Some(3));
+    assert_eq!(status(&["tests/step2/invalid.json", "does-not-exist.json"]), Some(3));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn write_replaces_files_in_place() {
+    let directory = temporary_directory("write");
+    let path = directory.join("document.json");
+    fs::write(&path, "{\"b\": [1, 2], \"a\": null}").unwrap();
+
+    let written = run(&["format", "--compact", "--write", path.to_str().unwrap()], "tests/step2/valid.json");
+    assert_eq!(written.status.code(), Some(0));
+    assert!(written.stdout.is_empty());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\"b\":[1,2],\"a\":null}\n");
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn write_preserves_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let directory = temporary_directory("permissions");
+    let path = directory.join("document.json");
+    fs::write(&path, "[1,2]").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    let written = run(&["format", "--write", path.to_str().unwrap()], "tests/step2/valid.json");
+    assert_eq!(written.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[\n  1,\n  2\n]\n");
+    assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn write_skips_unchanged_files() {
+    let directory = temporary_directory("unchanged");
+    let path = directory.join("document.json");
+    fs::write(&path, "{\"a\":1}\n").unwrap();
+    let modified = fs::metadata(&path).unwrap().modified().unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(20));
+
+    let written = run(&["format", "--compact", "--write", "-v", path.to_str().unwrap()], "tests/step2/valid.json");
+    assert_eq!(written.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&written.stderr).contains("unchanged, not written"));
+    assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn failed_write_leaves_the_original() {
+    let directory = temporary_directory("failure");
+    let path = directory.join("document.json");
+    fs::write(&path, "[1,2]").unwrap();
+    // Occupy the temporary file's name, so writing the new contents fails.
+    fs::create_dir(directory.join(".document.json.json-parser-rs.tmp")).unwrap();
+
+    let written = run(&["format", "--write", path.to_str().unwrap()], "tests/step2/valid.json");
+    assert_eq!(written.status.code(), Some(3));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[1,2]");
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn write_rejects_stdin() {
+    let written = run(&["format", "--write"], "tests/step2/valid.json");
+    assert_eq!(written.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&written.stderr).contains("'--write' cannot be used with standard input"));
+}
+
+#[test]
+fn output_writes_results_to_a_file() {
+    let directory = temporary_directory("output");
+    let path = directory.join("formatted.json");
+
+    let written = run(&["format", "--compact", "-o", path.to_str().unwrap(), "tests/step2/valid.json"], "tests/step2/valid.json");
+    assert_eq!(written.status.code(), Some(0));
+    assert!(written.stdout.is_empty());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "{\"key\":\"value\"}\n");
+
+    let dash = run(&["get", "/key", "--output", "-"], "tests/step2/valid.json");
+    assert_eq!(String::from_utf8_lossy(&dash.stdout), "value\n");
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn output_is_written_as_it_comes_and_renamed_at_the_end() {
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    let directory = temporary_directory("output-streamed");
+    let (log, path) = (directory.join("app.log"), directory.join("records.json"));
+    let temporary = directory.join(".records.json.json-parser-rs.tmp");
+    // More than the output buffer holds, so some of it must reach the file before the end.
+    fs::write(&log, "{\"message\": \"something happened\"}\n".repeat(2000)).unwrap();
+    let follower = Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+        .args(["--ndjson", "--follow", "--compact", "-o", path.to_str().unwrap(), log.to_str().unwrap()])
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let started = Instant::now();
+    while fs::metadata(&temporary).map_or(0, |metadata| metadata.len()) == 0 {
+        assert!(started.elapsed() < Duration::from_secs(10), "nothing written before the end");
+        sleep(Duration::from_millis(50));
+    }
+    assert!(!path.exists());
+    Command::new("kill").args(["-INT", &follower.id().to_string()]).status().unwrap();
+
+    let output = follower.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    // Interrupted, perhaps before the last records were read, but every record kept is whole.
+    let written = fs::read_to_string(&path).unwrap();
+    assert!(!written.is_empty());
+    assert!(written.lines().all(|line| line == "{\"message\":\"something happened\"}") && written.ends_with('\n'), "{}", written);
+    assert!(!temporary.exists());
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn output_refuses_to_overwrite_an_input() {
+    let directory = temporary_directory("clobber");
+    let path = directory.join("document.json");
+    fs::write(&path, "[1, 2]").unwrap();
+    let spelled_differently = directory.join(".").join("document.json");
+
+    let refused = run(&["format", "-o", spelled_differently.to_str().unwrap(), path.to_str().unwrap()], "tests/step2/valid.json");
+    assert_eq!(refused.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("would overwrite the input"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[1, 2]");
+
+    let forced = run(&["format", "--compact", "--force", "-o", path.to_str().unwrap(), path.to_str().unwrap()], "tests/step2/valid.json");
+    assert_eq!(forced.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "[1,2]\n");
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn output_reports_a_missing_directory() {
+    let directory = temporary_directory("missing");
+    let path = directory.join("absent").join("out.json");
+
+    let failed = run(&["format", "-o", path.to_str().unwrap()], "tests/step2/valid.json");
+    assert_eq!(failed.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&failed.stderr).starts_with(&format!("{}: error:", path.display())));
+    assert_eq!(fs::read_dir(&directory).unwrap().count(), 0);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn recursive_validates_the_files_in_a_tree() {
+    let directory = temporary_directory("recursive");
+    fs::create_dir_all(directory.join("configs/nested")).unwrap();
+    fs::create_dir_all(directory.join(".git")).unwrap();
+    fs::write(directory.join("configs/app.json"), "{\"a\": 1}").unwrap();
+    fs::write(directory.join("configs/nested/broken.json"), "[1,").unwrap();
+    fs::write(directory.join("configs/nested/events.ndjson"), "{\"x\": 1}\n{\"x\": 2}\n").unwrap();
+    fs::write(directory.join("configs/notes.txt"), "not JSON").unwrap();
+    fs::write(directory.join("configs/image.json"), b"{\0}").unwrap();
+    fs::write(directory.join(".git/config.json"), "[").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("..", directory.join("configs/nested/loop")).unwrap();
+    let root = directory.to_str().unwrap();
+
+    let validated = run(&["validate", "--recursive", root], "tests/step2/valid.json");
+    assert_eq!(validated.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&validated.stdout);
+    let stderr = String::from_utf8_lossy(&validated.stderr);
+    assert_eq!(stdout, format!("{}/configs/app.json: ok\n", root));
+    assert!(stderr.starts_with(&format!("{}/configs/nested/broken.json:1:4: error:", root)), "{}", stderr);
+    assert!(!stderr.contains("image.json") && !stderr.contains(".git"), "{}", stderr);
+
+    let extended = run(&["validate", "-r", "--ext", "json,ndjson", "--hidden", "-v", root], "tests/step2/valid.json");
+    let stdout = String::from_utf8_lossy(&extended.stdout);
+    let stderr = String::from_utf8_lossy(&extended.stderr);
+    assert!(stdout.contains("configs/nested/events.ndjson: ok"), "{}", stdout);
+    assert!(stderr.contains(".git/config.json:1:2: error:"), "{}", stderr);
+    assert!(stderr.contains("configs/image.json: skipped, looks binary"), "{}", stderr);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn recursive_options_need_recursive() {
+    let refused = run(&["validate", "--ext", "jsonc", "tests/step2"], "tests/step2/valid.json");
+    assert_eq!(refused.status.code(), Some(2));
+}
+
+#[test]
+fn files_from_reads_a_newline_separated_list() {
+    let directory = temporary_directory("files-from");
+    let list = directory.join("list.txt");
+    fs::write(&list, "tests/step2/valid.json\r\n\ndoes-not-exist.json\ntests/step2/invalid.json\n").unwrap();
+
+    let validated = run(&["validate", "--files-from", list.to_str().unwrap(), "tests/step4/valid.json"], "tests/step2/valid.json");
+    assert_eq!(validated.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&validated.stdout), "tests/step4/valid.json: ok\ntests/step2/valid.json: ok\n");
+    let stderr = String::from_utf8_lossy(&validated.stderr);
+    assert!(stderr.starts_with("does-not-exist.json: error:"), "{}", stderr);
+    assert!(stderr.contains("tests/step2/invalid.json:1:17: error:"), "{}", stderr);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn files_from_reads_a_nul_separated_list_from_stdin() {
+    let directory = temporary_directory("files-from-null");
+    let awkward = directory.join("a name with\na newline.json");
+    fs::write(&awkward, "[1, 2]").unwrap();
+    let list = directory.join("list");
+    fs::write(&list, format!("{}\0tests/step2/valid.json\0", awkward.display())).unwrap();
+
+    let validated = run(&["validate", "--files-from", "-", "-0"], list.to_str().unwrap());
+    assert_eq!(validated.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&validated.stdout), format!("{}: ok\ntests/step2/valid.json: ok\n", awkward.display()));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn files_from_an_empty_list_reads_nothing() {
+    let directory = temporary_directory("files-from-empty");
+    let list = directory.join("empty");
+    fs::write(&list, "").unwrap();
+
+    let validated = run(&["--files-from", "-"], list.to_str().unwrap());
+    assert_eq!(validated.status.code(), Some(0));
+    assert!(validated.stdout.is_empty());
+    assert!(validated.stderr.is_empty());
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn parallel_runs_report_like_serial_ones() {
+    let directory = temporary_directory("jobs");
+    for index in 0..300 {
+        let text = match index % 3 {
+            0 => format!("{{\"index\": {}}}", index),
+            1 => format!("[{}, ]", index),
+            _ => "x".repeat(index),
+        };
+        fs::write(directory.join(format!("{:03}.json", index)), text).unwrap();
+    }
+    let directory = directory.to_str().unwrap();
+
+    // The summary's timings differ from run to run, so leave it out of the comparison.
+    let serial = run(&["-r", "--summary", "none", "--count-lines", "--jobs", "1", directory], "tests/step2/valid.json");
+    let parallel = run(&["-r", "--summary", "none", "--count-lines", "--jobs", "8", directory], "tests/step2/valid.json");
+    assert_eq!(serial.status.code(), Some(1));
+    assert_eq!(parallel.status.code(), serial.status.code());
+    assert_eq!(String::from_utf8_lossy(&parallel.stdout), String::from_utf8_lossy(&serial.stdout));
+    assert_eq!(String::from_utf8_lossy(&parallel.stderr), String::from_utf8_lossy(&serial.stderr));
+    assert_eq!(String::from_utf8_lossy(&serial.stdout).lines().count(), 100);
+
+    let unreadable = format!("{}/missing.json", directory);
+    let serial = run(&["-r", "--summary", "none", "--jobs", "1", directory, &unreadable], "tests/step2/valid.json");
+    let parallel = run(&["-r", "--summary", "none", "--jobs", "4", directory, &unreadable], "tests/step2/valid.json");
+    assert_eq!(serial.status.code(), Some(3));
+    assert_eq!(parallel.status.code(), serial.status.code());
+    assert_eq!(parallel.stderr, serial.stderr);
+}
+
+#[test]
+fn jobs_must_be_positive() {
+    let zero = run(&["validate", "-j", "0"], "tests/step2/valid.json");
+    assert_eq!(zero.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&zero.stderr).starts_with("error: invalid value for '--jobs': '0' (expected a positive number)"));
+}
+
+#[test]
+fn json_diagnostics_parse_back() {
+    let output = run(&["--error-format=json", "tests/step2/invalid.json", "-"], "tests/numbers/invalid4.json");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let diagnostics: Vec<JsonValue> = stderr.lines().map(|line| line.parse().unwrap()).collect();
+    assert_eq!(diagnostics.len(), 2, "{}", stderr);
+    let expected: JsonValue = r#"{
+        "file": "tests/step2/invalid.json", "severity": "error", "code": "unexpected-token",
+        "message": "expected a string, found '}'", "line": 1, "column": 17, "offset": 16,
+        "end": {"line": 1, "column": 18, "offset": 17}
+    }"#.parse().unwrap();
+    assert_eq!(diagnostics[0], expected);
+    assert_eq!(diagnostics[1].pointer("/file"), Some(&JsonValue::String("<stdin>".to_string())));
+    assert_eq!(diagnostics[1].pointer("/message"), Some(&JsonValue::String("invalid number '123abc'".to_string())));
+    assert_eq!(diagnostics[1].pointer("/column"), Some(&JsonValue::Number(2.0)));
+}
+
+#[test]
+fn max_depth_rejects_deeper_nesting_with_its_location() {
+    let file = "tests/max_depth/invalid3.json";
+    let output = run(&["validate", "--max-depth", "10", "--error-format", "json", file], "tests/step2/valid.json");
+    assert_eq!(output.status.code(), Some(1));
+    let diagnostic: JsonValue = String::from_utf8(output.stderr).unwrap().trim_end().parse().unwrap();
+    assert_eq!(diagnostic.pointer("/code"), Some(&JsonValue::String("too-deep".to_string())));
+    assert_eq!(diagnostic.pointer("/line"), Some(&JsonValue::Number(11.0)));
+    assert_eq!(diagnostic.pointer("/column"), Some(&JsonValue::Number(32.0)));
+
+    let allowed = run(&["validate", "--max-depth", "11", file], "tests/step2/valid.json");
+    assert_eq!(allowed.status.code(), Some(0));
+}
+
+#[test]
+fn normalize_keys_makes_precomposed_and_decomposed_keys_duplicates() {
+    let directory = temporary_directory("normalize-keys");
+    let file = directory.join("keys.json");
+    fs::write(&file, "{\"caf\u{e9}\": 1, \"cafe\u{301}\": 2}").unwrap();
+    let file = file.to_str().unwrap();
+
+    let apart = run(&["--duplicate-keys", "error", file], "tests/step2/valid.json");
+    assert_eq!(apart.status.code(), Some(0));
+    let normalized = run(&["--duplicate-keys", "error", "--normalize-keys", file], "tests/step2/valid.json");
+    if cfg!(feature = "unicode-normalization") {
+        assert_eq!(normalized.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&normalized.stderr).contains("duplicate key"), "{}", String::from_utf8_lossy(&normalized.stderr));
+    } else {
+        assert_eq!(normalized.status.code(), Some(2));
+    }
+}
+
+#[test]
+fn max_depth_is_capped_where_nesting_is_still_handled() {
+    let directory = temporary_directory("max-depth-cap");
+    let (array, object) = (directory.join("array.json"), directory.join("object.json"));
+    fs::write(&array, format!("{}{}", "[".repeat(1000), "]".repeat(1000))).unwrap();
+    fs::write(&object, format!("{}1{}", "{\"k\": ".repeat(1000), "}".repeat(1000))).unwrap();
+    let files = [array.to_str().unwrap(), object.to_str().unwrap()];
+
+    // Two files so they are checked on worker threads, whose stacks are the smallest.
+    for format in ["--pretty", "--sort-keys", "--compact", "--flatten"] {
+        let output = run(&["--max-depth", "1000", "--jobs", "2", format, files[0], files[1]], "tests/step2/valid.json");
+        assert_eq!(output.status.code(), Some(0), "{}: {}", format, String::from_utf8_lossy(&output.stderr));
+    }
+    let deeper = run(&["--max-depth", "999", files[0]], "tests/step2/valid.json");
+    assert_eq!(deeper.status.code(), Some(1));
+
+    let over = run(&["--max-depth", "1001", files[0]], "tests/step2/valid.json");
+    assert_eq!(over.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&over.stderr).starts_with("error: invalid value for '--max-depth': 1001 is over the limit of 1000\n"));
+    assert_eq!(run(&["generate", "--max-depth", "1001"], "tests/step2/valid.json").status.code(), Some(2));
+}
+
+#[test]
+fn length_of_a_scalar_is_told_apart_from_invalid_json() {
+    let scalar = run(&["--length", "/n"], "tests/queries/invalid.json");
+    assert_eq!(scalar.status.code(), Some(6));
+    assert!(String::from_utf8_lossy(&scalar.stderr).contains("which has no length"));
+
+    let missing = run(&["--length", "/nope/0"], "tests/queries/invalid5.json");
+    assert_eq!(missing.status.code(), Some(4));
+
+    let both = run(&["--length", "/z", "--length", "/missing"], "tests/queries/invalid3.json");
+    assert_eq!(both.status.code(), Some(6));
+
+    let malformed = run(&["--length", "/n"], "tests/step2/invalid.json");
+    assert_eq!(malformed.status.code(), Some(1));
+}
+
+/// The `--sample` fixture's other settings, run on its one wide document rather than copies of it.
+#[test]
+fn sample_settings_apply_to_the_wide_document() {
+    for (args, expected, warned) in [
+        (&["--sample", "3"][..], r#"{
+  "users": [
+    {
+      "id": 1,
+      "name": "user1",
+      "tags": [
+        "a"
+      ]
+    },
+    {
+      "id": 2,
+      "name": "user2",
+      "tags": [
+        "a",
+        "b"
+      ]
+    },
+    {
+      "id": 3,
+      "name": "user3",
+      "tags": [
+        "a",
+        "b",
+        "c"
+      ]
+    },
+    … 1,201 more items
+  ],
+  "meta": {
+    "page": 1,
+    "per_page": 1204,
+    "total": 1204,
+    … 1 more key
+  },
+  "ok": true
+}
+"#, "--sample"),
+        (&["--sample", "3", "--depth", "2"][..], r#"{
+  "users": [
+    {…3 keys…},
+    {…3 keys…},
+    {…3 keys…},
+    … 1,201 more items
+  ],
+  "meta": {
+    "page": 1,
+    "per_page": 1204,
+    "total": 1204,
+    … 1 more key
+  },
+  "ok": true
+}
+"#, "--depth"),
+        (&["format", "--compact", "--sample", "2", "--sort-keys", "--max-string-len", "3"][..], r#"{"meta":{"page":1,"per_page":1204,… 2 more keys},"users":[{"id":1,"name":"use…",… 1 more key},{"id":2,"name":"use…",… 1 more key},… 1,202 more items],… 1 more key}
+"#, "--sample"),
+    ] {
+        let output = run(args, "tests/sample/valid.json");
+        assert_eq!(output.status.code(), Some(0), "{:?}", args);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), expected, "{:?}", args);
+        let warning = format!("output printed with '{}' leaves values out and is not valid JSON", warned);
+        assert!(String::from_utf8_lossy(&output.stderr).contains(&warning), "{:?}", args);
+    }
+}
+
+#[test]
+fn unflatten_reverses_flatten() {
+    let directory = temporary_directory("flatten");
+    for input in ["tests/flatten/valid.json", "tests/step4/valid.json", "tests/depth/valid.json"] {
+        for separator in [".", "/"] {
+            let flat = run(&["--flatten", "--separator", separator], input);
+            assert_eq!(flat.status.code(), Some(0), "{}", input);
+            let path = directory.join("flat.json");
+            fs::write(&path, &flat.stdout).unwrap();
+
+            let rebuilt = run(&["--unflatten", "--separator", separator], path.to_str().unwrap());
+            assert_eq!(rebuilt.status.code(), Some(0), "{}", input);
+            let original: JsonValue = fs::read_to_string(input).unwrap().parse().unwrap();
+            let rebuilt: JsonValue = String::from_utf8_lossy(&rebuilt.stdout).parse().unwrap();
+            assert_eq!(rebuilt, original, "{}", input);
+        }
+    }
+}
+
+#[test]
+fn to_ndjson_and_from_ndjson_reverse_each_other() {
+    let directory = temporary_directory("ndjson-conversion");
+    let original = "tests/ndjson_conversion/valid.json";
+    let records = run(&["--to-ndjson"], original);
+    assert_eq!(records.status.code(), Some(0));
+    let path = directory.join("records.ndjson");
+    fs::write(&path, &records.stdout).unwrap();
+
+    let array = run(&["--from-ndjson"], path.to_str().unwrap());
+    assert_eq!(array.status.code(), Some(0));
+    let expected: JsonValue = fs::read_to_string(original).unwrap().parse().unwrap();
+    assert_eq!(String::from_utf8_lossy(&array.stdout).parse::<JsonValue>().unwrap(), expected);
+
+    // Laid out as the whole array would be formatted.
+    for format in [&["--pretty"][..], &["--compact"], &["--indent", "3"], &["--tab", "--sort-keys"]] {
+        let converted = run(&[&["--from-ndjson"], format, &[path.to_str().unwrap()]].concat(), "tests/step2/valid.json");
+        assert_eq!(converted.stdout, run(&[&["format"], format, &[original]].concat(), "tests/step2/valid.json").stdout, "{:?}", format);
+    }
+
+    let path = directory.join("array.json");
+    fs::write(&path, &array.stdout).unwrap();
+    assert_eq!(run(&["--to-ndjson"], path.to_str().unwrap()).stdout, records.stdout);
+}
+
+#[test]
+fn explore_answers_a_script_of_commands() {
+    let directory = temporary_directory("explore");
+    let script = directory.join("script");
+    fs::write(&script, "get /users/0\nkeys /config\ntype /config/retries\nlen /users\nfind password\nfrobnicate\nlen /config/timeout\n\nquit\nkeys\n").unwrap();
+
+    let session = run(&["explore", "--depth", "2", "tests/explore/api.json"], script.to_str().unwrap());
+    assert_eq!(session.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&session.stdout), "\
+{
+  \"name\": \"ada\",
+  \"password\": \"hunter2\",
+  \"roles\": [
+    \"admin\",
+    {\u{2026}1 key\u{2026}}
+  ]
+}
+timeout
+retries
+object
+2
+/users/0/password
+/users/1/Password
+");
+    let errors = String::from_utf8_lossy(&session.stderr);
+    assert!(errors.starts_with("error: unknown command 'frobnicate'\nCommands:\n  get [POINTER]"), "{}", errors);
+    assert!(errors.ends_with("error: value at '/config/timeout' is 30, which has no length\n"), "{}", errors);
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_checks_records_as_they_are_appended() {
+    use std::io::Write;
+    use std::thread::{self, sleep};
+    use std::time::Duration;
+
+    let log = temporary_directory("follow").join("app.log");
+    fs::write(&log, "{\"a\": 1}\n").unwrap();
+    let follower = Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+        .args(["--ndjson", "--follow", log.to_str().unwrap()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let writer = thread::spawn({
+        let log = log.clone();
+        move || {
+            let pause = || sleep(Duration::from_millis(300));
+            let append = |text: &str| fs::OpenOptions::new().append(true).open(&log).unwrap().write_all(text.as_bytes()).unwrap();
+            pause();
+            // Judged only once its newline arrives.
+            append("{\"b\": ");
+            pause();
+            append("}\n");
+            pause();
+            // Truncated, as by log rotation, and then written to again.
+            fs::write(&log, "{\"c\": 3}\n").unwrap();
+            pause();
+            append("[1,]\n");
+            pause();
+        }
+    });
+    writer.join().unwrap();
+    Command::new("kill").args(["-INT", &follower.id().to_string()]).status().unwrap();
+
+    let output = follower.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let errors: Vec<&str> = stderr.lines().filter(|line| line.contains(": error: ")).collect();
+    assert_eq!(errors.len(), 2, "{}", stderr);
+    assert!(errors[0].contains("app.log:2:7: error: "), "{}", stderr);
+    assert!(errors[1].contains("app.log:4:4: error: "), "{}", stderr);
+}
+
+#[cfg(unix)]
+#[test]
+fn follow_reports_on_several_files_as_records_arrive() {
+    use std::io::{BufRead, BufReader, Write};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    let directory = temporary_directory("follow-several");
+    let (first, second) = (directory.join("first.log"), directory.join("second.log"));
+    fs::write(&first, "{\"a\": 1}\n").unwrap();
+    fs::write(&second, "{\"b\": 2}\n").unwrap();
+    let mut follower = Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+        .args(["--ndjson", "--follow", first.to_str().unwrap(), second.to_str().unwrap()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let (sender, receiver) = mpsc::channel();
+    let stderr = BufReader::new(follower.stderr.take().unwrap());
+    let reader = thread::spawn(move || stderr.lines().map_while(Result::ok).for_each(|line| { let _ = sender.send(line); }));
+    let append = |log: &std::path::Path, text: &str| fs::OpenOptions::new().append(true).open(log).unwrap().write_all(text.as_bytes()).unwrap();
+
+    // Each error must be printed while both files are still being followed.
+    for (log, name) in [(&second, "second.log:2:"), (&first, "first.log:2:")] {
+        append(log, "[1,]\n");
+        let line = receiver.recv_timeout(Duration::from_secs(10)).expect("no report before Ctrl-C");
+        assert!(line.contains(name) && line.contains(": error: "), "{}", line);
+        while receiver.recv_timeout(Duration::from_millis(200)).is_ok() {}
+    }
+    Command::new("kill").args(["-INT", &follower.id().to_string()]).status().unwrap();
+
+    let output = follower.wait_with_output().unwrap();
+    reader.join().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn max_bytes_stops_reading_an_endless_input() {
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    let started = Instant::now();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+        .args(["--max-bytes", "64K"])
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let chunk = "[1, 2, 3], ".repeat(1000);
+    let mut written = stdin.write_all(b"[").map(|()| 1).unwrap_or(0);
+    // Generates input until the reader stops taking it.
+    while stdin.write_all(chunk.as_bytes()).is_ok() {
+        written += chunk.len();
+        assert!(written < 1 << 30, "the whole stream was read");
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(output.status.code(), Some(5));
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "<stdin>:1:65537: error: more than 65536 bytes of input\n");
+    assert!(started.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn max_tokens_stops_reading_an_endless_line() {
+    use std::io::Write;
+
+    for args in [&["--max-tokens", "1000"][..], &["--max-tokens", "1000", "--stats"]] {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        let chunk = "1, ".repeat(1000);
+        let mut written = stdin.write_all(b"[").map(|()| 1).unwrap_or(0);
+        // One line that never ends, so only counting its tokens can stop it.
+        while stdin.write_all(chunk.as_bytes()).is_ok() {
+            written += chunk.len();
+            assert!(written < 1 << 30, "the whole stream was read with {:?}", args);
+        }
+        drop(stdin);
+
+        let output = child.wait_with_output().unwrap();
+        assert_eq!(output.status.code(), Some(5), "{:?}", args);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("error: more than 1000 tokens"), "{:?}", args);
+    }
+}
+
+#[test]
+fn generate_is_reproducible_and_valid() {
+    let directory = temporary_directory("generate");
+    for alphabet in ["ascii", "unicode", "hostile"] {
+        let args = ["generate", "--seed", "42", "--count", "20", "--compact", "--max-depth", "6", "--alphabet", alphabet];
+        let first = run(&args, "tests/step1/valid.json");
+        assert_eq!(first.status.code(), Some(0));
+        assert_eq!(first.stdout.iter().filter(|&&byte| byte == b'\n').count(), 20);
+        assert_eq!(first.stdout, run(&args, "tests/step1/valid.json").stdout);
+        assert_ne!(first.stdout, run(&["generate", "--seed", "43", "--count", "20", "--compact", "--alphabet", alphabet], "tests/step1/valid.json").stdout);
+
+        let generated = directory.join(format!("{}.ndjson", alphabet));
+        fs::write(&generated, &first.stdout).unwrap();
+        let checked = run(&["--strict", "--ndjson", "--max-depth", "6", generated.to_str().unwrap()], "tests/step1/valid.json");
+        assert_eq!(checked.status.code(), Some(0), "{}", String::from_utf8_lossy(&checked.stderr));
+    }
+
+    let sized = run(&["generate", "--seed", "1", "--size", "64K", "--compact"], "tests/step1/valid.json");
+    assert!((64 * 1024..70 * 1024).contains(&sized.stdout.len()), "{}", sized.stdout.len());
+}
+
+#[test]
+fn utf16_input_is_recognized_and_decoded() {
+    let detected = run(&[], "tests/encoding/valid.json");
+    assert_eq!(detected.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&detected.stderr), "<stdin>:1:1: error: input appears to be UTF-16LE; re-encode as UTF-8 or pass --encoding utf-16le\n");
+
+    let decoded = run(&["get", "--encoding", "utf-16le", "/tags/1"], "tests/encoding/valid.json");
+    assert_eq!(decoded.status.code(), Some(0));
+    assert_eq!(String::from_utf8_lossy(&decoded.stdout), "🙂\n");
+
+    let directory = temporary_directory("encoding");
+    let records = directory.join("records.ndjson");
+    let text: Vec<u8> = "{\"a\": 1}\n{\"a\": 2}\n".encode_utf16().flat_map(u16::to_be_bytes).collect();
+    fs::write(&records, text).unwrap();
+    let detected = run(&["--ndjson", records.to_str().unwrap()], "tests/step1/valid.json");
+    assert_eq!(detected.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&detected.stderr).contains("input appears to be UTF-16BE"));
+    let decoded = run(&["--ndjson", "--encoding=utf-16be", records.to_str().unwrap()], "tests/step1/valid.json");
+    assert_eq!(decoded.status.code(), Some(0), "{}", String::from_utf8_lossy(&decoded.stderr));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn progress_stays_off_when_stderr_is_not_a_terminal() {
+    for args in [&["--progress"][..], &["validate", "--progress"], &["format", "--progress", "--compact"], &["--progress", "--ndjson"]] {
+        let output = run(args, "tests/step2/valid.json");
+        assert_eq!(output.status.code(), Some(0), "{:?}", args);
+        assert!(output.stderr.is_empty(), "{:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+    }
+    let invalid = run(&["--progress"], "tests/step2/invalid.json");
+    assert!(String::from_utf8_lossy(&invalid.stderr).starts_with("<stdin>:1:17: error:"));
+}
+
+#[test]
+fn sort_keys_gives_reordered_documents_the_same_text() {
+    let directory = temporary_directory("sort-keys");
+    let (first, second) = (directory.join("first.json"), directory.join("second.json"));
+    fs::write(&first, r#"{"b": [{"z": 1, "a": {"y": null, "x": true}}], "a": "é", "A": 0}"#).unwrap();
+    fs::write(&second, r#"{"A": 0, "a": "é", "b": [{"a": {"x": true, "y": null}, "z": 1}]}"#).unwrap();
+
+    let sorted: Vec<_> = [&first, &second].iter().map(|path| run(&["format", "--sort-keys", path.to_str().unwrap()], "tests/step1/valid.json")).collect();
+    assert_eq!(sorted[0].status.code(), Some(0));
+    assert_eq!(sorted[0].stdout, sorted[1].stdout);
+    let expected = "{\n  \"A\": 0,\n  \"a\": \"é\",\n  \"b\": [\n    {\n      \"a\": {\n        \"x\": true,\n        \"y\": null\n      },\n      \"z\": 1\n    }\n  ]\n}\n";
+    assert_eq!(String::from_utf8_lossy(&sorted[0].stdout), expected);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn fail_fast_starts_no_input_after_a_failure() {
+    let directory = temporary_directory("fail-fast");
+    let (good, bad) = (directory.join("good.json"), directory.join("bad.json"));
+    fs::write(&good, "[1]").unwrap();
+    fs::write(&bad, "[1,").unwrap();
+    // Files that do not exist, which would each be reported if they were opened.
+    let missing: Vec<String> = (0..50).map(|index| directory.join(format!("missing{}.json", index)).to_str().unwrap().to_string()).collect();
+    let mut files = vec![good.to_str().unwrap().to_string(), bad.to_str().unwrap().to_string()];
+    files.extend(missing);
+    let files: Vec<&str> = files.iter().map(String::as_str).collect();
+
+    let stopped = run(&[&["--fail-fast", "--jobs", "1"][..], &files].concat(), "tests/step1/valid.json");
+    assert_eq!(stopped.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&stopped.stderr);
+    assert!(stderr.contains("bad.json:1:4: error:") && !stderr.contains("missing"), "{}", stderr);
+    assert_eq!(String::from_utf8_lossy(&stopped.stdout), format!("{}: ok\n", files[0]));
+
+    let kept_going = run(&[&["--fail-fast", "--keep-going", "--jobs", "1"][..], &files].concat(), "tests/step1/valid.json");
+    assert_eq!(kept_going.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&kept_going.stderr).matches("missing").count(), 50);
+
+    // With --jobs, the inputs already started are finished, but no more are.
+    let parallel = run(&[&["validate", "--fail-fast", "--jobs", "4"][..], &files[1..]].concat(), "tests/step1/valid.json");
+    assert_ne!(parallel.status.code(), Some(0));
+    let stderr = String::from_utf8_lossy(&parallel.stderr);
+    assert!(stderr.starts_with(&format!("{}:1:4: error:", files[1])), "{}", stderr);
+    assert!(stderr.matches("missing").count() < 50, "{}", stderr);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn summary_counts_the_outcome_of_each_input() {
+    let directory = temporary_directory("summary");
+    let paths: Vec<String> = ["a.json", "b.json", "c.json", "d.json", "missing.json"].iter().map(|name| directory.join(name).to_str().unwrap().to_string()).collect();
+    fs::write(&paths[0], "[1]").unwrap();
+    fs::write(&paths[1], "{}").unwrap();
+    fs::write(&paths[2], "[1,").unwrap();
+    fs::write(&paths[3], "null").unwrap();
+    let files: Vec<&str> = paths.iter().map(String::as_str).collect();
+
+    for jobs in ["1", "4"] {
+        let text = run(&[&["--jobs", jobs][..], &files].concat(), "tests/step1/valid.json");
+        assert_eq!(text.status.code(), Some(3));
+        let stderr = String::from_utf8_lossy(&text.stderr);
+        let line = stderr.lines().last().unwrap();
+        assert!(line.starts_with("checked 5 files: 3 ok, 1 invalid, 1 io-error ("), "{}", stderr);
+
+        let json = run(&[&["validate", "--summary", "json", "--verbose", "--jobs", jobs][..], &files].concat(), "tests/step1/valid.json");
+        assert_eq!(json.status.code(), Some(3));
+        let summary = String::from_utf8_lossy(&json.stderr).lines().last().unwrap().parse::<JsonValue>().unwrap();
+        for (key, count) in [("files", 5.0), ("ok", 3.0), ("invalid", 1.0), ("io_error", 1.0), ("not_found", 0.0), ("no_length", 0.0), ("exit_code", 3.0)] {
+            assert_eq!(summary.get(key), Some(&JsonValue::Number(count)), "{}", key);
+        }
+        let Some(JsonValue::Array(inputs)) = summary.get("inputs") else { panic!("{}", summary) };
+        let statuses: Vec<_> = inputs.iter().map(|input| input.get("status").cloned().unwrap()).collect();
+        let expected = ["ok", "ok", "invalid", "ok", "io-error"].map(|status| JsonValue::String(status.to_string()));
+        assert_eq!(statuses, expected);
+    }
+
+    // One input, --quiet or 'none' print no summary unless one is asked for.
+    let single = run(&[files[0]], "tests/step1/valid.json");
+    assert_eq!(String::from_utf8_lossy(&single.stderr), "");
+    let quiet = run(&[&["--quiet"][..], &files].concat(), "tests/step1/valid.json");
+    assert!(!String::from_utf8_lossy(&quiet.stderr).contains("checked"));
+    let none = run(&[&["--summary", "none"][..], &files].concat(), "tests/step1/valid.json");
+    assert!(!String::from_utf8_lossy(&none.stderr).contains("checked"));
+    let asked = run(&["--summary", "text", files[0]], "tests/step1/valid.json");
+    assert!(String::from_utf8_lossy(&asked.stderr).starts_with("checked 1 file: 1 ok ("));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn stdin_filename_labels_standard_input() {
+    let human = run(&["--stdin-filename", "src/config.json"], "tests/step2/invalid.json");
+    assert_eq!(human.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&human.stderr).starts_with("src/config.json:1:17: error:"));
+
+    let json = run(&["validate", "--stdin-filename=src/config.json", "--error-format", "json"], "tests/step2/invalid.json");
+    let diagnostic: JsonValue = String::from_utf8_lossy(&json.stderr).parse().unwrap();
+    assert_eq!(diagnostic.get_str("file"), Some("src/config.json"));
+
+    let valid = run(&["format", "--stdin-filename", "src/config.json", "--check"], "tests/step2/valid.json");
+    assert!(String::from_utf8_lossy(&valid.stdout).starts_with("src/config.json: "));
+
+    let with_files = run(&["--stdin-filename", "src/config.json", "tests/step2/valid.json"], "tests/step2/valid.json");
+    assert_eq!(with_files.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&with_files.stderr).starts_with("error: '--stdin-filename' only names standard input"));
+}
+
+#[cfg(unix)]
+#[test]
+fn watch_checks_files_again_when_they_change() {
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+    use std::thread::{self, sleep};
+    use std::time::{Duration, Instant};
+
+    let directory = temporary_directory("watch");
+    let config = directory.join("config.json");
+    fs::write(&config, "{\"a\": 1}").unwrap();
+    let mut watcher = Command::new(env!("CARGO_BIN_EXE_json-parser-rs"))
+        .args(["validate", "--watch", directory.to_str().unwrap()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let collect = |mut stream: Box<dyn Read + Send>| {
+        let text = Arc::new(Mutex::new(String::new()));
+        let written = Arc::clone(&text);
+        thread::spawn(move || {
+            let mut buffer = [0; 1024];
+            while let Ok(read @ 1..) = stream.read(&mut buffer) {
+                written.lock().unwrap().push_str(&String::from_utf8_lossy(&buffer[..read]));
+            }
+        });
+        text
+    };
+    let stdout = collect(Box::new(watcher.stdout.take().unwrap()));
+    let stderr = collect(Box::new(watcher.stderr.take().unwrap()));
+    let wait_for = |text: &Arc<Mutex<String>>, wanted: &str, times: usize| {
+        let started = Instant::now();
+        while text.lock().unwrap().matches(wanted).count() < times {
+            assert!(started.elapsed() < Duration::from_secs(10), "no {:?} in {:?}", wanted, text.lock().unwrap());
+            sleep(Duration::from_millis(20));
+        }
+    };
+
+    wait_for(&stdout, "config.json: ok", 1);
+    assert!(stdout.lock().unwrap().contains("config.json: found"));
+    fs::write(&config, "{\"a\": 1,}").unwrap();
+    wait_for(&stderr, "config.json:1:9: error:", 1);
+    assert!(stdout.lock().unwrap().contains("config.json: changed"));
+
+    // Saved atomically, as editors do: written elsewhere and renamed over the original.
+    let temporary = directory.join(".config.json.tmp");
+    fs::write(&temporary, "{\"a\": 2}").unwrap();
+    fs::rename(&temporary, &config).unwrap();
+    wait_for(&stdout, "config.json: ok", 2);
+    assert_eq!(stdout.lock().unwrap().matches("config.json: changed").count(), 2);
+
+    Command::new("kill").args(["-INT", &watcher.id().to_string()]).status().unwrap();
+    assert_eq!(watcher.wait().unwrap().code(), Some(0));
+    fs::remove_dir_all(&directory).unwrap();
+}
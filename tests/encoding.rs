@@ -0,0 +1,53 @@
+use std::io::{BufReader, Cursor, Read};
+
+use json_parser_rs::{Encoding, ErrorKind, Lexer, SyntaxAnalyser};
+
+fn decode(encoding: Encoding, bytes: Vec<u8>) -> std::io::Result<String> {
+    let mut text = String::new();
+    encoding.decode(Box::new(Cursor::new(bytes))).read_to_string(&mut text)?;
+    Ok(text)
+}
+
+#[test]
+fn detect_finds_utf16_by_its_byte_order_mark_or_nuls() {
+    assert_eq!(Encoding::detect(b"\xff\xfe[\0"), Encoding::Utf16Le);
+    assert_eq!(Encoding::detect(b"\xfe\xff\0["), Encoding::Utf16Be);
+    assert_eq!(Encoding::detect(b"{\0\"\0"), Encoding::Utf16Le);
+    assert_eq!(Encoding::detect(b"\0[\0]"), Encoding::Utf16Be);
+    assert_eq!(Encoding::detect(b"1\0"), Encoding::Utf16Le);
+    assert_eq!(Encoding::detect(b"{\"a\""), Encoding::Utf8);
+    assert_eq!(Encoding::detect(b"{\0}"), Encoding::Utf8);
+    assert_eq!(Encoding::detect(b"[\0\0\0"), Encoding::Utf8);
+    assert_eq!(Encoding::detect(b""), Encoding::Utf8);
+}
+
+#[test]
+fn decode_reads_utf16_as_utf8() {
+    // Long enough that surrogate pairs straddle the chunks the decoder reads.
+    let text = format!("[\"{}\"]", "a🙂".repeat(5000));
+    let little: Vec<u8> = "\u{feff}".encode_utf16().chain(text.encode_utf16()).flat_map(u16::to_le_bytes).collect();
+    assert_eq!(decode(Encoding::Utf16Le, little).unwrap(), text);
+    let big: Vec<u8> = text.encode_utf16().flat_map(u16::to_be_bytes).collect();
+    assert_eq!(decode(Encoding::Utf16Be, big).unwrap(), text);
+    assert_eq!(decode(Encoding::Utf8, text.clone().into_bytes()).unwrap(), text);
+
+    assert!(decode(Encoding::Utf16Le, vec![b'[', 0, b']']).unwrap_err().to_string().contains("halfway through a character"));
+    assert!(decode(Encoding::Utf16Le, vec![b'"', 0, 0x00, 0xD8, b'"', 0]).unwrap_err().to_string().contains("unpaired surrogate 0xD800"));
+}
+
+#[test]
+fn decoding_stops_at_an_unpaired_surrogate_after_what_comes_before_it() {
+    // Far enough in that the surrogate is in a later chunk than the start.
+    let prefix = format!("[\n\"{}", "a".repeat(10000));
+    let mut bytes: Vec<u8> = prefix.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    bytes.extend_from_slice(&[0x00, 0xD8, b'"', 0, b']', 0]);
+
+    let mut decoded = Vec::new();
+    let error = Encoding::Utf16Le.decode(Box::new(Cursor::new(bytes.clone()))).read_to_end(&mut decoded).unwrap_err();
+    assert_eq!(decoded, prefix.as_bytes());
+    assert_eq!(error.to_string(), "invalid UTF-16: unpaired surrogate 0xD800 at byte 20006");
+
+    let reader = BufReader::new(Encoding::Utf16Le.decode(Box::new(Cursor::new(bytes))));
+    let error = SyntaxAnalyser::new(Lexer::new(Box::new(reader))).parse().unwrap_err();
+    assert_eq!((error.kind, error.line, error.column, error.offset), (ErrorKind::InvalidEncoding, 2, 10002, 10003));
+}
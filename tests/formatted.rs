@@ -0,0 +1,59 @@
+use json_parser_rs::{parse_with_spans, FormattedDocument, JsonValue, ParseOptions};
+
+const CONFIG: &str = "{
+  // Where the service listens.
+  \"server\": { \"host\" : \"localhost\",   \"port\": 8080 },
+  \"features\": [
+      \"search\",
+      \"upload\"   /* beta */
+  ]
+}
+";
+
+fn jsonc() -> ParseOptions {
+    ParseOptions { allow_comments: true, ..ParseOptions::default() }
+}
+
+#[test]
+fn set_only_changes_the_edited_value() {
+    let mut document = FormattedDocument::parse(CONFIG.to_string(), jsonc()).unwrap();
+    document.set("/server/port", &JsonValue::Number(9090.0)).unwrap();
+    assert_eq!(document.as_str(), CONFIG.replace("8080", "9090"));
+
+    document.set("/features/1", &"[\"upload\", \"resume\"]".parse().unwrap()).unwrap();
+    assert_eq!(document.as_str(), CONFIG.replace("8080", "9090").replace("\"upload\"   /*", "[\"upload\",\"resume\"]   /*"));
+    assert_eq!(document.value().pointer("/features/1/1"), Some(&JsonValue::String("resume".to_string())));
+}
+
+#[test]
+fn span_covers_a_value_from_its_first_character_to_its_last() {
+    let document = FormattedDocument::parse(CONFIG.to_string(), jsonc()).unwrap();
+    assert_eq!(&CONFIG[document.span("/server").unwrap()], "{ \"host\" : \"localhost\",   \"port\": 8080 }");
+    assert_eq!(&CONFIG[document.span("/features/0").unwrap()], "\"search\"");
+    assert_eq!(&CONFIG[document.span("").unwrap()], CONFIG.trim_end());
+    assert_eq!(document.span("/server/missing"), Err("pointer not found: '/server/missing'".to_string()));
+}
+
+#[test]
+fn parse_rejects_what_the_options_do() {
+    assert!(FormattedDocument::parse(CONFIG.to_string(), ParseOptions::default()).is_err());
+}
+
+#[test]
+fn parse_with_spans_maps_every_pointer_to_its_text() {
+    let text = "{\"a\": {\"b~/c\": [1, \"two\", {}]}, \"d\":null}";
+    let (value, spans) = parse_with_spans(text, &ParseOptions::default()).unwrap();
+    assert_eq!(value, text.parse().unwrap());
+
+    let mut spans: Vec<(&str, &str)> = spans.iter().map(|(pointer, span)| (pointer.as_str(), &text[span.clone()])).collect();
+    spans.sort();
+    assert_eq!(spans, [
+        ("", text),
+        ("/a", "{\"b~/c\": [1, \"two\", {}]}"),
+        ("/a/b~0~1c", "[1, \"two\", {}]"),
+        ("/a/b~0~1c/0", "1"),
+        ("/a/b~0~1c/1", "\"two\""),
+        ("/a/b~0~1c/2", "{}"),
+        ("/d", "null"),
+    ]);
+}
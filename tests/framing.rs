@@ -0,0 +1,34 @@
+use std::io::Cursor;
+
+use json_parser_rs::{read_framed, ErrorKind, JsonValue, ParseOptions};
+
+#[test]
+fn reads_frames_one_after_another() {
+    let (first, second) = ("[1, 2]", "{\"a\": \"\u{e9}\"}\n");
+    let framed = format!("{}\n{}{}\r\n{}2\n{{}}", first.len(), first, second.len(), second);
+    let mut input = Cursor::new(framed.as_bytes());
+    let options = ParseOptions::default();
+
+    assert_eq!(read_framed(&mut input, &options).unwrap(), Some(first.parse::<JsonValue>().unwrap()));
+    assert_eq!(read_framed(&mut input, &options).unwrap(), Some(second.parse::<JsonValue>().unwrap()));
+    assert_eq!(read_framed(&mut input, &options).unwrap(), Some(JsonValue::Object(Vec::new())));
+    assert_eq!(read_framed(&mut input, &options).unwrap(), None);
+}
+
+#[test]
+fn rejects_bad_framing() {
+    let options = ParseOptions::default();
+    let error = |text: &str| read_framed(&mut Cursor::new(text.as_bytes()), &options).unwrap_err();
+
+    let short = error("10\n[1, 2]");
+    assert_eq!(short.kind, ErrorKind::InvalidFrame);
+    assert_eq!(short.message, "input ends 6 bytes into a frame of 10");
+
+    let not_a_number = error("1x\n[]");
+    assert_eq!((not_a_number.kind, not_a_number.column), (ErrorKind::InvalidFrame, 2));
+    assert_eq!(error("[1, 2]").kind, ErrorKind::InvalidFrame);
+    assert_eq!(error("123").message, "input ends inside a length prefix");
+
+    // The length cuts the value short; what is left over would be the next frame.
+    assert_eq!(error("4\n[1, 2]").kind, ErrorKind::UnexpectedEnd);
+}
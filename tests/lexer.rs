@@ -0,0 +1,92 @@
+use std::io::{BufReader, Cursor, Read};
+
+use json_parser_rs::{tokenize, ErrorKind, JsonValue, Lexer, SyntaxAnalyser, TokenType};
+
+fn reader(text: &str) -> Box<Cursor<Vec<u8>>> {
+    Box::new(Cursor::new(text.as_bytes().to_vec()))
+}
+
+#[test]
+fn reset_lexer_reads_each_document_independently() {
+    let mut lexer = Lexer::new(reader("{\"a\": [1, 2]}"));
+    let mut results = Vec::new();
+    for next in ["[true,\n", "\n\n  \"last\""] {
+        let mut parser = SyntaxAnalyser::new(lexer);
+        results.push(parser.parse());
+        lexer = parser.into_lexer();
+        lexer.reset(reader(next));
+    }
+    results.push(SyntaxAnalyser::new(lexer).parse());
+
+    assert_eq!(results[0], "{\"a\": [1, 2]}".parse());
+    // The unfinished array must not leak into the document after it.
+    let error = results[1].clone().unwrap_err();
+    assert_eq!((error.kind, error.line, error.column), (ErrorKind::UnexpectedEnd, 1, 7));
+    // Positions start over with each document.
+    let last = results[2].clone();
+    assert_eq!(last, Ok(JsonValue::String("last".to_string())));
+}
+
+#[test]
+fn reset_lexer_reports_positions_from_the_new_input() {
+    let mut parser = SyntaxAnalyser::new(Lexer::new(reader("[1,\n2,\n3]")));
+    assert!(parser.parse().is_ok());
+
+    let mut lexer = parser.into_lexer();
+    lexer.reset(reader("\n  [x]"));
+    let error = SyntaxAnalyser::new(lexer).parse().unwrap_err();
+    assert_eq!((error.line, error.column, error.offset), (2, 4, 4));
+}
+
+#[test]
+fn tokenize_returns_each_token_with_its_position() {
+    let tokens = tokenize("{\"é\": [1.5, true],\n  // note\n  \"b\": null}").unwrap();
+    let summary: Vec<(TokenType, &str, usize, usize, usize)> = tokens.iter()
+        .map(|token| (token.token_type(), token.text(), token.line(), token.column(), token.offset()))
+        .collect();
+    assert_eq!(summary, vec![
+        (TokenType::LeftBrace, "{", 1, 1, 0),
+        (TokenType::String, "\"é\"", 1, 2, 1),
+        (TokenType::Colon, ":", 1, 5, 5),
+        (TokenType::LeftSquareBracket, "[", 1, 7, 7),
+        (TokenType::Number, "1.5", 1, 8, 8),
+        (TokenType::Comma, ",", 1, 11, 11),
+        (TokenType::True, "true", 1, 13, 13),
+        (TokenType::RightSquareBracket, "]", 1, 17, 17),
+        (TokenType::Comma, ",", 1, 18, 18),
+        (TokenType::Comment, "// note", 2, 3, 22),
+        (TokenType::String, "\"b\"", 3, 3, 32),
+        (TokenType::Colon, ":", 3, 6, 35),
+        (TokenType::Null, "null", 3, 8, 37),
+        (TokenType::RightBrace, "}", 3, 12, 41),
+    ]);
+}
+
+/// Hands out at most `chunk` bytes per read, so multibyte characters arrive split across reads.
+struct Trickle {
+    bytes: Vec<u8>,
+    position: usize,
+    chunk: usize,
+}
+
+impl Read for Trickle {
+    fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.chunk.min(buffer.len()).min(self.bytes.len() - self.position);
+        buffer[..length].copy_from_slice(&self.bytes[self.position..length + self.position]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+#[test]
+fn characters_split_across_reads_are_decoded_whole() {
+    let text = "{\"caf\u{e9}\": \"\u{1F600} \u{4E2D}\u{6587}\",\n \"\u{10348}\": [\"\u{e9}\u{e9}\u{e9}\"]}";
+    let expected: JsonValue = text.parse().unwrap();
+    for chunk in 1..=5 {
+        for capacity in [1, 2, 3, 7] {
+            let trickle = Trickle { bytes: text.as_bytes().to_vec(), position: 0, chunk };
+            let lexer = Lexer::new(Box::new(BufReader::with_capacity(capacity, trickle)));
+            assert_eq!(SyntaxAnalyser::new(lexer).parse(), Ok(expected.clone()), "chunk {}, capacity {}", chunk, capacity);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use std::io::Cursor;
+
+use json_parser_rs::{ErrorKind, Found, Lexer, Messages, StringProblem, SyntaxAnalyser};
+
+/// Rewords two kinds of problem and leaves the rest in English.
+struct Terse;
+
+impl Messages for Terse {
+    fn unexpected(&self, _expected: &str, found: Found) -> String {
+        match found {
+            Found::EndOfInput => "E001 truncated".to_string(),
+            _ => "E002 unexpected".to_string(),
+        }
+    }
+
+    fn invalid_string(&self, problem: StringProblem) -> String {
+        format!("E003 bad string: {:?}", problem)
+    }
+}
+
+fn parse_error(text: &str, messages: Option<Box<dyn Messages>>) -> (ErrorKind, String) {
+    let mut syntax_analyser = SyntaxAnalyser::new(Lexer::new(Box::new(Cursor::new(text.to_string()))));
+    if let Some(messages) = messages {
+        syntax_analyser = syntax_analyser.with_messages(messages);
+    }
+    let error = syntax_analyser.parse().unwrap_err();
+    (error.kind, error.message)
+}
+
+#[test]
+fn custom_messages_replace_the_built_in_text() {
+    assert_eq!(parse_error("[1, 2", Some(Box::new(Terse))), (ErrorKind::UnexpectedEnd, "E001 truncated".to_string()));
+    assert_eq!(parse_error("[1 2]", Some(Box::new(Terse))), (ErrorKind::UnexpectedToken, "E002 unexpected".to_string()));
+    assert_eq!(parse_error(r#""\q""#, Some(Box::new(Terse))), (ErrorKind::InvalidString, "E003 bad string: InvalidEscape('q')".to_string()));
+}
+
+#[test]
+fn messages_not_overridden_stay_english() {
+    assert_eq!(parse_error("[1e999]", Some(Box::new(Terse))).1, "number out of range");
+    assert_eq!(parse_error("[1e999]", None).1, "number out of range");
+    assert_eq!(parse_error("[1 2]", None).1, "expected ',' or ']', found '2'");
+    assert_eq!(parse_error(r#""\q""#, None).1, r"invalid escape '\q'");
+}
@@ -0,0 +1,34 @@
+#![cfg(all(feature = "mmap", unix, target_pointer_width = "64"))]
+
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::Path;
+
+use json_parser_rs::{parse_mmap, to_string_with_options, GenerateOptions, Generator, JsonValue, Lexer, ParseError, PrettyConfig, SerializeOptions, SyntaxAnalyser};
+
+fn parse_buffered(path: &Path) -> Result<JsonValue, ParseError> {
+    SyntaxAnalyser::new(Lexer::new(Box::new(BufReader::new(File::open(path).unwrap())))).parse()
+}
+
+#[test]
+fn mapped_files_parse_like_buffered_ones() {
+    let directory = std::env::temp_dir().join(format!("json-parser-rs-{}-mmap", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("document.json");
+
+    let pretty = SerializeOptions { pretty: Some(PrettyConfig::default()), ..SerializeOptions::default() };
+    for seed in 0..20 {
+        let document = Generator::new(seed, GenerateOptions::default()).document();
+        // Alternate minified and pretty text, so both one long line and many short ones are read.
+        let options = if seed % 2 == 0 { SerializeOptions::default() } else { pretty.clone() };
+        fs::write(&path, to_string_with_options(&document, &options)).unwrap();
+        assert_eq!(parse_mmap(&path).unwrap(), parse_buffered(&path).unwrap(), "seed {}", seed);
+    }
+
+    for text in ["", "{\"caf\u{e9}\": [1, 2,\n 3", "[1, 2]\n\n  x", "\"\u{1f642}\"\n"] {
+        fs::write(&path, text).unwrap();
+        assert_eq!(parse_mmap(&path), parse_buffered(&path), "{:?}", text);
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,195 @@
+use std::cell::RefCell;
+use std::io::{repeat, BufReader, Cursor, Read};
+use std::rc::Rc;
+
+use json_parser_rs::{first_error_context, DuplicateKeys, ErrorKind, Event, EventReader, JsonValue, LeadingZeros, Lexer, NumberParser, ParseError, ParseOptions, Parser, SyntaxAnalyser, Token};
+
+fn parse_with(text: &str, options: ParseOptions) -> Result<JsonValue, ParseError> {
+    SyntaxAnalyser::with_options(Lexer::new(Box::new(Cursor::new(text.to_string()))), options).parse()
+}
+
+/// Keeps the text of every number it is given, and reads each as its length.
+struct Recorder(Rc<RefCell<Vec<String>>>);
+
+impl NumberParser for Recorder {
+    fn parse_number(&mut self, text: &str) -> Result<JsonValue, String> {
+        self.0.borrow_mut().push(text.to_string());
+        Ok(JsonValue::Number(text.len() as f64))
+    }
+}
+
+#[test]
+fn number_parser_is_given_the_digits_as_written() {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let lexer = Lexer::new(Box::new(Cursor::new(r#"{"a": [1, -2.5E+3, 0.10], "b": 12345678901234567890}"#.to_string())));
+    let value = SyntaxAnalyser::new(lexer).with_number_parser(Box::new(Recorder(seen.clone()))).parse().unwrap();
+    assert_eq!(*seen.borrow(), ["1", "-2.5E+3", "0.10", "12345678901234567890"]);
+    assert_eq!(value.pointer("/a/1"), Some(&JsonValue::Number(7.0)));
+    assert_eq!(value.pointer("/b"), Some(&JsonValue::Number(20.0)));
+}
+
+#[test]
+fn duplicate_keys_are_compared_after_decoding_escapes() {
+    let options = || ParseOptions { duplicate_keys: DuplicateKeys::Error, ..ParseOptions::default() };
+    for text in [r#"{"ab": 1, "a\u0062": 2}"#, r#"{"\u00e9": 1, "\u00E9": 2}"#, r#"{"\ud83d\ude00": 1, "\uD83D\uDE00": 2}"#, r#"{"a\/b": 1, "a/b": 2}"#] {
+        let error = parse_with(text, options()).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::DuplicateKey, "{}", text);
+        assert_eq!((error.line, error.column), (1, text.rfind(", ").unwrap() + 3), "{}", text);
+    }
+
+    assert!(parse_with(r#"{"ab": 1, "ac": 2}"#, options()).is_ok());
+}
+
+#[test]
+fn comments_are_returned_beside_the_value() {
+    let text = "// settings\n{\"a\": 1, /* the\nlimit */ \"b\": [2] // last\n}";
+    let options = || ParseOptions { allow_comments: true, ..ParseOptions::default() };
+    let lexer = Lexer::new(Box::new(Cursor::new(text.to_string())));
+    let (value, comments) = SyntaxAnalyser::with_options(lexer, options()).parse_with_comments().unwrap();
+    assert_eq!(value, parse_with(text, options()).unwrap());
+    let found: Vec<(&str, usize, usize)> = comments.iter().map(|comment| (comment.text.as_str(), comment.line, comment.column)).collect();
+    assert_eq!(found, [("// settings", 1, 1), ("/* the\nlimit */", 2, 10), ("// last", 3, 19)]);
+
+    let lexer = Lexer::new(Box::new(Cursor::new(text.to_string())));
+    let error = SyntaxAnalyser::new(lexer).parse_with_comments().unwrap_err();
+    assert_eq!((error.line, error.column), (1, 1));
+}
+
+#[test]
+fn json5_extensions_are_accepted_only_when_allowed() {
+    let options = ParseOptions { allow_single_quotes: true, allow_unquoted_keys: true, allow_nan: true, ..ParseOptions::default() };
+    let text = r#"{name: 'it\'s "quoted"', $id_2: NaN, null: [Infinity, -Infinity], 'key': "a\'b"}"#;
+    let value = parse_with(text, options.clone()).unwrap();
+    assert_eq!(value.pointer("/name"), Some(&JsonValue::String("it's \"quoted\"".to_string())));
+    assert!(matches!(value.pointer("/$id_2"), Some(JsonValue::Number(number)) if number.is_nan()));
+    assert_eq!(value.pointer("/null"), Some(&JsonValue::Array(vec![JsonValue::Number(f64::INFINITY), JsonValue::Number(f64::NEG_INFINITY)])));
+    assert_eq!(value.pointer("/key"), Some(&JsonValue::String("a'b".to_string())));
+
+    // Each is rejected on its own when the others are allowed.
+    let without_single_quotes = ParseOptions { allow_single_quotes: false, ..options.clone() };
+    for (text, options, message) in [
+        ("['a']", without_single_quotes.clone(), "expected a value, found '''"),
+        (r#"["\'"]"#, without_single_quotes, "invalid escape '\\''"),
+        ("{a: 1}", ParseOptions { allow_unquoted_keys: false, ..options.clone() }, "expected a string, found 'a'"),
+        ("[NaN]", ParseOptions { allow_nan: false, ..options.clone() }, "unexpected bareword 'NaN'; string values must be quoted"),
+    ] {
+        assert_eq!(parse_with(text, options).unwrap_err().message, message, "{}", text);
+    }
+    // Keys must still be identifiers, and values still quoted.
+    assert_eq!(parse_with("{1a: 1}", options.clone()).unwrap_err().message, "expected a string, found '1a'");
+    assert_eq!(parse_with("{a: b}", options).unwrap_err().message, "unexpected bareword 'b'; string values must be quoted");
+}
+
+/// Composes the one decomposed character the test uses, as NFC would.
+fn compose_e_acute(key: &str) -> String {
+    key.replace("e\u{301}", "\u{e9}")
+}
+
+#[test]
+fn keys_equal_after_normalization_are_duplicates() {
+    let text = "{\"caf\u{e9}\": 1, \"cafe\u{301}\": 2}";
+    assert!(parse_with(text, ParseOptions { duplicate_keys: DuplicateKeys::Error, ..ParseOptions::default() }).is_ok());
+
+    let options = || ParseOptions { duplicate_keys: DuplicateKeys::Error, normalize_key: Some(compose_e_acute), ..ParseOptions::default() };
+    let error = parse_with(text, options()).unwrap_err();
+    assert_eq!((error.kind, error.column), (ErrorKind::DuplicateKey, 13));
+    let value = parse_with("{\"cafe\u{301}\": 2}", options()).unwrap();
+    assert_eq!(value.pointer("/caf\u{e9}"), Some(&JsonValue::Number(2.0)));
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[test]
+fn nfc_key_makes_precomposed_and_decomposed_keys_duplicates() {
+    let options = || ParseOptions { duplicate_keys: DuplicateKeys::Error, normalize_key: Some(json_parser_rs::nfc_key), ..ParseOptions::default() };
+    let error = parse_with("{\"\u{c5}ngstr\u{f6}m\": 1, \"A\u{30a}ngstro\u{308}m\": 2}", options()).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::DuplicateKey);
+    let value = parse_with("{\"A\u{30a}ngstro\u{308}m\": 2}", options()).unwrap();
+    assert_eq!(value.pointer("/\u{c5}ngstr\u{f6}m"), Some(&JsonValue::Number(2.0)));
+}
+
+#[test]
+fn first_error_context_returns_the_tokens_around_the_error() {
+    let input = "{\"a\": [1, 2,\n  3 4], \"b\": null}";
+    let context = first_error_context(input, &ParseOptions::default(), 3).unwrap();
+    assert_eq!((context.error.line, context.error.column), (2, 5));
+
+    let around = |tokens: &[Token]| tokens.iter().map(|token| (token.text().to_string(), token.line(), token.column())).collect::<Vec<_>>();
+    let expected = |tokens: &[(&str, usize, usize)]| tokens.iter().map(|&(text, line, column)| (text.to_string(), line, column)).collect::<Vec<_>>();
+    assert_eq!(around(&context.before), expected(&[("2", 1, 11), (",", 1, 12), ("3", 2, 3)]));
+    assert_eq!(around(&context.after), expected(&[("4", 2, 5), ("]", 2, 6), (",", 2, 7)]));
+
+    assert_eq!(first_error_context("[1, 2]", &ParseOptions::default(), 3), None);
+    let at_start = first_error_context("}", &ParseOptions::default(), 3).unwrap();
+    assert!(at_start.before.is_empty());
+    assert_eq!(around(&at_start.after), expected(&[("}", 1, 1)]));
+}
+
+#[test]
+fn limits_stop_reading_the_input() {
+    // One line that never ends, so the limit has to cut it short.
+    let endless = Lexer::new(Box::new(BufReader::new(b"[1, ".chain(repeat(b' ')))));
+    let options = ParseOptions { max_bytes: Some(1 << 20), ..ParseOptions::default() };
+    let error = SyntaxAnalyser::with_options(endless, options).parse().unwrap_err();
+    assert_eq!((error.kind, error.message.as_str(), error.offset), (ErrorKind::LimitExceeded, "more than 1048576 bytes of input", 1 << 20));
+
+    let options = ParseOptions { max_tokens: Some(3), ..ParseOptions::default() };
+    let error = parse_with("[1, 2]", options).unwrap_err();
+    assert_eq!((error.kind, error.message.as_str(), error.column), (ErrorKind::LimitExceeded, "more than 3 tokens", 5));
+
+    // The token limit too stops a line that never ends.
+    let endless = Lexer::new(Box::new(BufReader::new(b"[".chain(repeat(b',')))));
+    let options = ParseOptions { max_tokens: Some(1000), ..ParseOptions::default() };
+    let error = SyntaxAnalyser::with_options(endless, options).parse().unwrap_err();
+    assert_eq!((error.kind, error.message.as_str()), (ErrorKind::LimitExceeded, "more than 1000 tokens"));
+}
+
+#[test]
+fn skip_value_passes_over_a_large_array_to_the_next_key() {
+    let rows: Vec<String> = (0..10_000).map(|index| format!("[{}, {{\"name\": \"row {}\", \"tags\": [[], {{}}]}}]", index, index)).collect();
+    let text = format!("{{\"rows\": [{}], \"total\": 10000, \"next\": null}}", rows.join(", "));
+    let mut reader = EventReader::new(Lexer::new(Box::new(Cursor::new(text))));
+    assert_eq!(reader.next_event().unwrap(), Some(Event::StartObject));
+    assert_eq!(reader.next_event().unwrap(), Some(Event::Key("rows".to_string())));
+    reader.skip_value().unwrap();
+    assert_eq!(reader.next_event().unwrap(), Some(Event::Key("total".to_string())));
+    assert_eq!(reader.next_event().unwrap(), Some(Event::Value(JsonValue::Number(10000.0))));
+    assert_eq!(reader.pointer(), "/total");
+    assert_eq!(reader.next_event().unwrap(), Some(Event::Key("next".to_string())));
+}
+
+#[test]
+fn key_and_value_lengths_are_limited_separately() {
+    let text = r#"{"a": {"long key": "short", "k": "a long \u0076alue"}}"#;
+    let options = ParseOptions { max_key_length: Some(4), ..ParseOptions::default() };
+    let error = parse_with(text, options).unwrap_err();
+    assert_eq!((error.kind, error.message.as_str(), error.column), (ErrorKind::LimitExceeded, "key of 8 bytes in the object at /a is longer than the limit of 4", 8));
+
+    let options = ParseOptions { max_value_bytes: Some(12), ..ParseOptions::default() };
+    let error = parse_with(text, options.clone()).unwrap_err();
+    assert_eq!((error.kind, error.message.as_str(), error.column), (ErrorKind::LimitExceeded, "string of 17 bytes at /a/k is longer than the limit of 12", 34));
+
+    // Skipping a value checks the same lengths and names the same place.
+    let mut reader = EventReader::with_options(Lexer::new(Box::new(Cursor::new(text.to_string()))), options);
+    assert_eq!(reader.skip_value().unwrap_err(), error);
+    assert!(parse_with(text, ParseOptions { max_key_length: Some(8), max_value_bytes: Some(17), ..ParseOptions::default() }).is_ok());
+}
+
+#[test]
+fn leading_zeros_are_read_as_the_policy_says() {
+    let options = |leading_zeros| ParseOptions { leading_zeros, ..ParseOptions::default() };
+    let error = parse_with("0755", options(LeadingZeros::Error)).unwrap_err();
+    assert_eq!((error.kind, error.message.as_str()), (ErrorKind::UnexpectedToken, "invalid number '0755': leading zeros are not allowed"));
+    assert_eq!(parse_with("0755", options(LeadingZeros::Decimal)), Ok(JsonValue::Number(755.0)));
+    assert_eq!(parse_with("0755", options(LeadingZeros::Octal)), Ok(JsonValue::Number(493.0)));
+    assert_eq!(parse_with("-0755", options(LeadingZeros::Octal)), Ok(JsonValue::Number(-493.0)));
+    assert_eq!(parse_with("007.5", options(LeadingZeros::Decimal)), Ok(JsonValue::Number(7.5)));
+    assert_eq!(parse_with("0789", options(LeadingZeros::Octal)).unwrap_err().message, "invalid octal number '0789'");
+    assert_eq!(parse_with("[0, 0.5, -0e1]", options(LeadingZeros::Error)).map(|_| ()), Ok(()));
+
+    // Values skipped rather than decoded, and the push parser, apply the same policy.
+    let mut reader = EventReader::new(Lexer::new(Box::new(Cursor::new("[1, 0755]".to_string()))));
+    assert_eq!(reader.skip_value().unwrap_err().column, 5);
+    let mut parser = Parser::with_options(options(LeadingZeros::Octal)).unwrap();
+    parser.feed(b"[07").unwrap();
+    assert_eq!(parser.feed(b"55]").unwrap().first(), Some(&Event::Value(JsonValue::Number(493.0))));
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::io::Cursor;
+
+use json_parser_rs::{DuplicateKeys, Event, EventReader, JsonValue, Lexer, Mode, ParseError, ParseOptions, Parser};
+
+fn pull(text: &str) -> Result<Vec<Event>, ParseError> {
+    pull_with(text, ParseOptions::default())
+}
+
+fn pull_with(text: &str, options: ParseOptions) -> Result<Vec<Event>, ParseError> {
+    let mut reader = EventReader::with_options(Lexer::new(Box::new(Cursor::new(text.to_string()))), options);
+    let mut events = Vec::new();
+    while let Some(event) = reader.next_event()? {
+        events.push(event);
+    }
+    Ok(events)
+}
+
+fn push(text: &str, chunk_size: usize) -> Result<Vec<Event>, ParseError> {
+    push_with(text, chunk_size, ParseOptions::default())
+}
+
+fn push_with(text: &str, chunk_size: usize, options: ParseOptions) -> Result<Vec<Event>, ParseError> {
+    let mut parser = Parser::with_options(options).unwrap();
+    let mut events = Vec::new();
+    for chunk in text.as_bytes().chunks(chunk_size) {
+        events.extend(parser.feed(chunk)?);
+    }
+    events.extend(parser.finish()?);
+    Ok(events)
+}
+
+#[test]
+fn feeding_one_byte_at_a_time_gives_the_events_of_the_pull_parser() {
+    let text = "{\"caf\u{e9}\": [1, -2.5e-3, true, false, null],\n \"\u{1f642}\\u00e9\\\"\": {\"a\": [], \"b\": {}}, \"n\": 123456}";
+    let expected = pull(text).unwrap();
+    for chunk_size in [1, 2, 3, 7, text.len()] {
+        assert_eq!(push(text, chunk_size).unwrap(), expected, "chunks of {}", chunk_size);
+    }
+
+    let mut parser = Parser::new();
+    assert_eq!(parser.feed(b"12").unwrap(), Vec::new());
+    assert_eq!(parser.feed(b"34").unwrap(), Vec::new());
+    assert_eq!(parser.finish().unwrap(), vec![Event::Value(JsonValue::Number(1234.0))]);
+}
+
+#[test]
+fn errors_match_the_pull_parser() {
+    for text in ["[1, 2", "[1 2]", "{\"a\" 1}", "{\"a\": tru}", "[1.]", "[\"a\\x\"]", "[\"\u{e9}\u{1}\"]", "\u{feff}[]", "[] []", "{", "[1,]", "\"abc",
+        "// note\n[]", "[1 /* a\n* b */]", "{\"a\" // note", "[] /**/", "[1 / 2]", "[/* open", "/"] {
+        let expected = pull(text).unwrap_err();
+        assert_eq!(push(text, 1).unwrap_err(), expected, "{}", text);
+    }
+
+    let mut parser = Parser::new();
+    assert!(parser.feed(b"[}").is_err());
+    assert_eq!(parser.feed(b"]").unwrap_err().column, 2);
+}
+
+#[test]
+fn invalid_utf8_split_across_chunks_is_an_error() {
+    let mut parser = Parser::new();
+    parser.feed(b"[\"\xC3").unwrap();
+    let error = parser.feed(b"(\"]").unwrap_err();
+    assert_eq!((error.line, error.column, error.offset), (1, 3, 2));
+    assert!(error.message.starts_with("invalid UTF-8"), "{}", error.message);
+}
+
+#[test]
+fn push_and_pull_agree_on_the_fixtures() {
+    // Fixtures with arguments may be read with options the push parser does not support.
+    let mut checked = 0;
+    for topic in fs::read_dir("tests").unwrap().map(|entry| entry.unwrap().path()).filter(|path| path.is_dir()) {
+        for path in fs::read_dir(&topic).unwrap().map(|entry| entry.unwrap().path()) {
+            if path.extension().is_none_or(|extension| extension != "json") || path.with_extension("args").exists() { continue; }
+            let Ok(text) = fs::read_to_string(&path) else { continue };
+            let expected = pull(&text);
+            for chunk_size in [1, 7, 4096] {
+                assert_eq!(push(&text, chunk_size), expected, "{} in chunks of {}", path.display(), chunk_size);
+            }
+            checked += 1;
+        }
+    }
+    assert!(checked > 50, "only {} fixtures checked", checked);
+}
+
+#[test]
+fn supported_options_agree_with_the_pull_parser() {
+    let lenient = || ParseOptions { mode: Mode::Lenient, allow_trailing_commas: true, max_depth: 3, ..ParseOptions::default() };
+    for text in ["\u{feff}[1e999, \"\\ud800\",]", "[[[1]]]", "[[[[1]]]]", "{\"a\": [1,],}"] {
+        assert_eq!(push_with(text, 1, lenient()), pull_with(text, lenient()), "{}", text);
+    }
+}
+
+#[test]
+fn unsupported_options_are_refused() {
+    let options = ParseOptions { allow_comments: true, duplicate_keys: DuplicateKeys::Error, max_bytes: Some(10), ..ParseOptions::default() };
+    let error = Parser::with_options(options).err().unwrap();
+    assert_eq!(error, "the push parser does not support allow_comments, duplicate_keys, max_bytes");
+}
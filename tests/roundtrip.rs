@@ -0,0 +1,138 @@
+//! Checks the parser against the serializer, the full parser against the reader that only
+//! skips values, and both against serde_json, over documents from `Generator` and the fixtures.
+//! A disagreement is a bug in one of them.
+
+use std::fs;
+use std::io::Cursor;
+
+use json_parser_rs::{to_string_with_options, Alphabet, EventReader, GenerateOptions, Generator, JsonValue, Lexer, ParseOptions, PrettyConfig, SerializeOptions, SyntaxAnalyser};
+
+const SEEDS: u64 = 300;
+
+fn parse(text: &str) -> Result<JsonValue, String> {
+    SyntaxAnalyser::new(Lexer::new(Box::new(Cursor::new(text.to_string())))).parse().map_err(|error| error.to_string())
+}
+
+fn skip(text: &str) -> Result<(), String> {
+    let mut reader = EventReader::with_options(Lexer::new(Box::new(Cursor::new(text.to_string()))), ParseOptions::default());
+    reader.skip_value().and_then(|()| reader.next_event()).map(|_| ()).map_err(|error| error.to_string())
+}
+
+/// Parses `text` with serde_json into a `JsonValue`, keys sorted as serde_json keeps them.
+fn parse_with_serde(text: &str) -> Result<JsonValue, String> {
+    fn convert(value: serde_json::Value) -> JsonValue {
+        match value {
+            serde_json::Value::Object(members) => JsonValue::Object(members.into_iter().map(|(key, value)| (key, convert(value))).collect()),
+            serde_json::Value::Array(elements) => JsonValue::Array(elements.into_iter().map(convert).collect()),
+            serde_json::Value::String(text) => JsonValue::String(text),
+            serde_json::Value::Number(number) => JsonValue::Number(number.as_f64().unwrap()),
+            serde_json::Value::Bool(value) => JsonValue::Bool(value),
+            serde_json::Value::Null => JsonValue::Null,
+        }
+    }
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    deserializer.disable_recursion_limit();
+    let value = serde::Deserialize::deserialize(&mut deserializer).and_then(|value| deserializer.end().map(|()| value));
+    value.map(convert).map_err(|error: serde_json::Error| error.to_string())
+}
+
+/// `value` with the keys of each object sorted, the last of any repeated key kept, as in serde_json.
+fn sorted(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(members) => {
+            let members: std::collections::BTreeMap<_, _> = members.iter().map(|(key, value)| (key.clone(), sorted(value))).collect();
+            JsonValue::Object(members.into_iter().collect())
+        }
+        JsonValue::Array(elements) => JsonValue::Array(elements.iter().map(sorted).collect()),
+        value => value.clone(),
+    }
+}
+
+/// Asserts that the parser and serde_json both accept `text`, with equal values, or both reject it.
+/// Neither limits nesting, as their limits differ.
+fn assert_agrees_with_serde(text: &str, context: &str) {
+    let options = ParseOptions { max_depth: usize::MAX, ..ParseOptions::default() };
+    let ours = SyntaxAnalyser::with_options(Lexer::new(Box::new(Cursor::new(text.to_string()))), options).parse();
+    match (ours, parse_with_serde(text)) {
+        (Ok(ours), Ok(theirs)) => assert_eq!(sorted(&ours), theirs, "{}: {}", context, text),
+        (Err(_), Err(_)) => {}
+        (ours, theirs) => panic!("{}: we gave {:?} but serde_json {:?} for {}", context, ours.map(|_| ()), theirs.map(|_| ()), text),
+    }
+}
+
+fn documents(alphabet: Alphabet) -> impl Iterator<Item = (u64, JsonValue)> {
+    (0..SEEDS).map(move |seed| (seed, Generator::new(seed, GenerateOptions { alphabet, ..GenerateOptions::default() }).document()))
+}
+
+#[test]
+fn generated_documents_survive_a_round_trip() {
+    let styles = [
+        SerializeOptions::default(),
+        SerializeOptions { pretty: Some(PrettyConfig::default()), ..SerializeOptions::default() },
+        SerializeOptions { escape_unicode: true, ..SerializeOptions::default() },
+    ];
+    for alphabet in [Alphabet::Ascii, Alphabet::Unicode, Alphabet::Hostile] {
+        for (seed, document) in documents(alphabet) {
+            for options in &styles {
+                let text = to_string_with_options(&document, options);
+                assert_eq!(parse(&text).as_ref(), Ok(&document), "seed {} with {:?}: {}", seed, alphabet, text);
+            }
+        }
+    }
+}
+
+#[test]
+fn parsing_and_skipping_agree_on_damaged_documents() {
+    for (seed, document) in documents(Alphabet::Hostile) {
+        let text = document.to_string();
+        let characters: Vec<char> = text.chars().collect();
+        // Takes out, and then doubles, a character picked from the seed.
+        let at = (seed as usize * 7919) % characters.len();
+        let removed: String = characters.iter().enumerate().filter(|&(index, _)| index != at).map(|(_, c)| c).collect();
+        let doubled: String = characters[..=at].iter().chain(&characters[at..]).collect();
+        for damaged in [removed, doubled] {
+            assert_eq!(parse(&damaged).map(|_| ()), skip(&damaged), "seed {}: {}", seed, damaged);
+        }
+    }
+}
+
+#[test]
+fn parsing_and_skipping_agree_on_damage_rarely_generated() {
+    // Skipping does not decode escapes, so an unpaired surrogate such as "\ud800" is the one
+    // difference, and is left out.
+    for text in [r#"{"a\": 1}"#, "[\"\u{1}\"]", r#"["\u00"]"#, r#"{"":[1,2,]}"#, "[-]", "[01]", "[1.]", "[1e]", "[1]]", "{\"a\" 1}"] {
+        assert_eq!(parse(text).map(|_| ()), skip(text), "{}", text);
+    }
+}
+
+#[test]
+fn parsing_agrees_with_serde_json() {
+    for alphabet in [Alphabet::Ascii, Alphabet::Unicode, Alphabet::Hostile] {
+        for (seed, document) in documents(alphabet) {
+            let text = document.to_string();
+            assert_agrees_with_serde(&text, &format!("seed {} with {:?}", seed, alphabet));
+            let characters: Vec<char> = text.chars().collect();
+            let at = (seed as usize * 7919) % characters.len();
+            let removed: String = characters.iter().enumerate().filter(|&(index, _)| index != at).map(|(_, c)| c).collect();
+            assert_agrees_with_serde(&removed, &format!("seed {} with {:?}, damaged", seed, alphabet));
+        }
+    }
+    for text in [r#"["\ud800"]"#, r#"["\udc00\ud800"]"#, "[1e400]", "[-0]", "[18446744073709551616]", "[0.1e-400]", "{\"a\": 1, \"a\": 2}", "\u{feff}[]", "[\"\u{7f}\"]"] {
+        assert_agrees_with_serde(text, "by hand");
+    }
+}
+
+#[test]
+fn parsing_agrees_with_serde_json_on_the_fixtures() {
+    // Fixtures with arguments may be read with options serde_json has no equivalent for.
+    let mut checked = 0;
+    for topic in fs::read_dir("tests").unwrap().map(|entry| entry.unwrap().path()).filter(|path| path.is_dir()) {
+        for path in fs::read_dir(&topic).unwrap().map(|entry| entry.unwrap().path()) {
+            if path.extension().is_none_or(|extension| extension != "json") || path.with_extension("args").exists() { continue; }
+            let Ok(text) = fs::read_to_string(&path) else { continue };
+            assert_agrees_with_serde(&text, &path.display().to_string());
+            checked += 1;
+        }
+    }
+    assert!(checked > 50, "only {} fixtures checked", checked);
+}
@@ -0,0 +1,42 @@
+use json_parser_rs::{validate_schema, JsonValue, SchemaError};
+
+fn parse(text: &str) -> JsonValue {
+    text.parse().unwrap()
+}
+
+#[test]
+fn each_keyword_accepts_and_rejects() {
+    // Schema, a value it accepts, and a value it rejects with the error it gives.
+    let cases = [
+        (r#"{"type": ["integer", "null"]}"#, "3", "3.5", ("", "type", "expected integer or null, found number")),
+        (r#"{"enum": [1, {"a": [2]}]}"#, r#"{"a": [2]}"#, r#"{"a": [3]}"#, ("", "enum", r#"{"a":[3]} is not one of 1, {"a":[2]}"#)),
+        (r#"{"minimum": 0}"#, "0", "-1", ("", "minimum", "-1 is less than the minimum of 0")),
+        (r#"{"maximum": 10}"#, "10", "11", ("", "maximum", "11 is greater than the maximum of 10")),
+        (r#"{"minLength": 2}"#, r#""é!""#, r#""é""#, ("", "minLength", "length 1 is less than the minimum of 2")),
+        (r#"{"maxLength": 2}"#, r#""é!""#, r#""é!!""#, ("", "maxLength", "length 3 is greater than the maximum of 2")),
+        (r#"{"required": ["id"]}"#, r#"{"id": null}"#, r#"{"ID": 1}"#, ("", "required", "missing required member 'id'")),
+        (r#"{"properties": {"a/b": {"type": "string"}}}"#, r#"{"a/b": "x", "c": 1}"#, r#"{"a/b": 1}"#, ("/a~1b", "type", "expected string, found number")),
+        (r#"{"items": {"type": "number"}}"#, "[1, 2]", r#"[1, "2"]"#, ("/1", "type", "expected number, found string")),
+        (r#"{"items": [{"type": "string"}]}"#, r#"["a", 2]"#, "[2]", ("/0", "type", "expected string, found number")),
+        (r#"{"properties": {"x": false}}"#, r#"{"y": 1}"#, r#"{"x": 1}"#, ("/x", "false", "no value is allowed here")),
+    ];
+    for (schema, accepted, rejected, (pointer, keyword, message)) in cases {
+        let schema = parse(schema);
+        assert_eq!(validate_schema(&parse(accepted), &schema), [], "{} against {}", accepted, schema);
+        let expected = SchemaError { pointer: pointer.to_string(), keyword, message: message.to_string() };
+        assert_eq!(validate_schema(&parse(rejected), &schema), [expected], "{} against {}", rejected, schema);
+    }
+}
+
+#[test]
+fn every_failure_is_reported() {
+    let schema = parse(r#"{"type": "object", "required": ["a", "b"], "properties": {"c": {"minimum": 5, "type": "integer"}}}"#);
+    let errors: Vec<String> = validate_schema(&parse(r#"{"c": 1.5}"#), &schema).iter().map(ToString::to_string).collect();
+    assert_eq!(errors, [
+        "required at the root: missing required member 'a'",
+        "required at the root: missing required member 'b'",
+        "type at /c: expected integer, found number",
+        "minimum at /c: 1.5 is less than the minimum of 5",
+    ]);
+    assert_eq!(validate_schema(&parse("[1, {}]"), &JsonValue::Bool(true)), []);
+}
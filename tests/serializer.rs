@@ -0,0 +1,103 @@
+use std::cmp::Ordering;
+use std::io::Cursor;
+
+use json_parser_rs::{to_string, to_string_with_limit, to_string_with_options, BigIntegerParser, JsonValue, KeyOrder, Lexer, NumbersAsStrings, PrettyConfig, SerializeOptions, SyntaxAnalyser};
+
+fn numbers_as_strings(numbers_as_strings: NumbersAsStrings) -> SerializeOptions {
+    SerializeOptions { numbers_as_strings, ..SerializeOptions::default() }
+}
+
+fn longest_first(a: &str, b: &str) -> Ordering {
+    b.len().cmp(&a.len()).then(a.cmp(b))
+}
+
+#[test]
+fn key_order_applies_to_every_object() {
+    let document: JsonValue = r#"{"b": 1, "ccc": {"z": 2, "yy": 3, "a": 4}, "a": 5}"#.parse().unwrap();
+    let order = |key_order| to_string_with_options(&document, &SerializeOptions { key_order, ..SerializeOptions::default() });
+    assert_eq!(order(KeyOrder::Preserve), r#"{"b":1,"ccc":{"z":2,"yy":3,"a":4},"a":5}"#);
+    assert_eq!(order(KeyOrder::Sorted), r#"{"a":5,"b":1,"ccc":{"a":4,"yy":3,"z":2}}"#);
+    assert_eq!(order(KeyOrder::Custom(longest_first)), r#"{"ccc":{"yy":3,"a":4,"z":2},"a":5,"b":1}"#);
+}
+
+#[test]
+fn unsafe_integers_are_quoted_only_when_asked() {
+    let document: JsonValue = "[9007199254740992, 42, 1.5]".parse().unwrap();
+    let options = numbers_as_strings(NumbersAsStrings::UnsafeIntegers);
+    assert_eq!(to_string_with_options(&document, &options), r#"["9007199254740992",42,1.5]"#);
+    let options = numbers_as_strings(NumbersAsStrings::Never);
+    assert_eq!(to_string_with_options(&document, &options), "[9007199254740992,42,1.5]");
+}
+
+#[test]
+fn big_integer_parser_keeps_the_digits_as_a_string() {
+    let lexer = Lexer::new(Box::new(Cursor::new("[9007199254740993, 42, 1.5]")));
+    let document = SyntaxAnalyser::new(lexer).with_number_parser(Box::new(BigIntegerParser)).parse().unwrap();
+    assert_eq!(document.pointer("/0"), Some(&JsonValue::String("9007199254740993".to_string())));
+
+    // It is already a string, so it is quoted whatever numbers_as_strings says.
+    let options = numbers_as_strings(NumbersAsStrings::Never);
+    assert_eq!(to_string_with_options(&document, &options), r#"["9007199254740993",42,1.5]"#);
+}
+
+#[test]
+fn unsafe_integers_only_quotes_integers_beyond_two_to_the_53() {
+    let document = JsonValue::Array(vec![
+        JsonValue::Number(9007199254740991.0),
+        JsonValue::Number(-9007199254740994.0),
+        JsonValue::Number(0.5),
+    ]);
+
+    let options = numbers_as_strings(NumbersAsStrings::UnsafeIntegers);
+    assert_eq!(to_string_with_options(&document, &options), r#"[9007199254740991,"-9007199254740994",0.5]"#);
+    let options = numbers_as_strings(NumbersAsStrings::All);
+    assert_eq!(to_string_with_options(&document, &options), r#"["9007199254740991","-9007199254740994","0.5"]"#);
+    assert_eq!(to_string_with_options(&document, &SerializeOptions::default()), "[9007199254740991,-9007199254740994,0.5]");
+}
+
+#[test]
+fn collapse_whitespace_only_changes_string_values() {
+    let document: JsonValue = r#"{"a  b": "  two\n\tlines  and\u00a0more ", "list": ["x\r\n\r\ny", 1, "same"], "nested": {"k": " "}}"#.parse().unwrap();
+    let options = SerializeOptions { collapse_whitespace: true, ..SerializeOptions::default() };
+    assert_eq!(to_string_with_options(&document, &options), r#"{"a  b":" two lines and more ","list":["x y",1,"same"],"nested":{"k":" "}}"#);
+
+    let pretty = SerializeOptions { pretty: Some(PrettyConfig::default()), ..options };
+    assert_eq!(to_string_with_options(&document, &pretty), "{\n  \"a  b\": \" two lines and more \",\n  \"list\": [\n    \"x y\",\n    1,\n    \"same\"\n  ],\n  \"nested\": {\n    \"k\": \" \"\n  }\n}");
+}
+
+#[test]
+fn negative_zero_keeps_its_sign() {
+    for (text, expected) in [("-0", "-0"), ("[-0.0, 0.0]", "[-0,0]"), ("[-0e3, 0E-2]", "[-0,0]")] {
+        let document: JsonValue = text.parse().unwrap();
+        let written = to_string_with_options(&document, &SerializeOptions::default());
+        assert_eq!(written, expected);
+        let reread: JsonValue = written.parse().unwrap();
+        assert_eq!(to_string_with_options(&reread, &SerializeOptions::default()), expected);
+    }
+    let number: JsonValue = "-0".parse().unwrap();
+    assert!(matches!(number, JsonValue::Number(zero) if zero.is_sign_negative()));
+
+    // RFC 8785 writes it as 0.
+    let document: JsonValue = "[-0.0, 0.0]".parse().unwrap();
+    assert_eq!(to_string_with_options(&document, &SerializeOptions::canonical()), "[0,0]");
+}
+
+#[test]
+fn limited_output_is_cut_at_a_character_boundary() {
+    let document = JsonValue::Array((0..40).map(|index| JsonValue::String(format!("é{}", index))).collect());
+    let full = to_string(&document);
+    let (preview, truncated) = to_string_with_limit(&document, 100);
+    assert!(truncated);
+    assert_eq!(preview.len(), 100);
+    assert_eq!(preview, full[..100]);
+
+    // A limit that ends between the two bytes of an "é" leaves the character out whole.
+    let start = full.match_indices('é').map(|(index, _)| index).find(|&index| index > 90).unwrap();
+    let (preview, truncated) = to_string_with_limit(&document, start + 1);
+    assert!(truncated);
+    assert_eq!(preview, full[..start]);
+    assert!(preview.ends_with('"'));
+
+    let (whole, truncated) = to_string_with_limit(&document, full.len());
+    assert_eq!((whole, truncated), (full, false));
+}
@@ -0,0 +1,162 @@
+use json_parser_rs::{ArrayMerge, GetError, JsonValue, PathSegment};
+
+#[test]
+fn into_object_returns_the_members_in_order() {
+    let document: JsonValue = r#"{"b": [1, 2], "a": {"c": null}}"#.parse().unwrap();
+    let expected = document.clone();
+
+    let members = document.into_object().unwrap();
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0], ("b".to_string(), expected.pointer("/b").unwrap().clone()));
+    assert_eq!(members[1], ("a".to_string(), expected.pointer("/a").unwrap().clone()));
+    assert_eq!(JsonValue::Object(members), expected);
+}
+
+#[test]
+fn into_array_returns_the_elements() {
+    let document: JsonValue = r#"["x", {"y": 1}, [true]]"#.parse().unwrap();
+    let expected = document.clone();
+
+    let elements = document.into_array().unwrap();
+    assert_eq!(elements.len(), 3);
+    assert_eq!(elements[0].clone().into_string().as_deref(), Some("x"));
+    assert_eq!(JsonValue::Array(elements), expected);
+}
+
+#[test]
+fn into_methods_reject_other_types() {
+    assert_eq!(JsonValue::Array(Vec::new()).into_object(), None);
+    assert_eq!(JsonValue::Object(Vec::new()).into_array(), None);
+    assert_eq!(JsonValue::Number(1.0).into_string(), None);
+}
+
+#[test]
+fn take_leaves_null_behind() {
+    let mut document: JsonValue = r#"{"items": [1, 2, 3]}"#.parse().unwrap();
+
+    let items = document.pointer_mut("/items").unwrap().take().into_array().unwrap();
+    assert_eq!(items, vec![JsonValue::Number(1.0), JsonValue::Number(2.0), JsonValue::Number(3.0)]);
+    assert_eq!(document.pointer("/items"), Some(&JsonValue::Null));
+}
+
+#[test]
+fn retain_filters_direct_children() {
+    let mut object: JsonValue = r#"{"x-id": 1, "name": "a", "x-trace": {"x-keep": true}, "size": 2}"#.parse().unwrap();
+    object.retain(|key, _| !key.is_some_and(|key| key.starts_with("x-")));
+    assert_eq!(object, r#"{"name": "a", "size": 2}"#.parse::<JsonValue>().unwrap());
+
+    let mut array: JsonValue = "[3, -1, 4, null, 1, -5]".parse().unwrap();
+    array.retain(|key, value| key.is_none() && value.coerce_f64().is_some_and(|number| number > 0.0));
+    assert_eq!(array, "[3, 4, 1]".parse::<JsonValue>().unwrap());
+
+    let mut string = JsonValue::String("x-id".to_string());
+    string.retain(|_, _| false);
+    assert_eq!(string, JsonValue::String("x-id".to_string()));
+}
+
+#[test]
+fn entry_counts_words() {
+    let mut counts = JsonValue::Null;
+    for word in "the cat saw the other cat and the dog".split(' ') {
+        if let JsonValue::Number(count) = counts.entry(word).unwrap().or_insert(JsonValue::Number(0.0)) {
+            *count += 1.0;
+        }
+    }
+    assert_eq!(counts, r#"{"the": 3, "cat": 2, "saw": 1, "other": 1, "and": 1, "dog": 1}"#.parse::<JsonValue>().unwrap());
+
+    let entry = counts.entry("cat").unwrap().and_modify(|count| *count = JsonValue::Null);
+    assert_eq!(entry.key(), "cat");
+    assert_eq!(counts.pointer("/cat"), Some(&JsonValue::Null));
+    assert!(JsonValue::Array(Vec::new()).entry("cat").is_none());
+}
+
+#[test]
+fn get_path_follows_keys_and_indexes() {
+    let document: JsonValue = r#"{"users": [{"name": "a", "roles": ["admin"]}, {"0": "key, not index", "a/b": 1}]}"#.parse().unwrap();
+    let path = |segments: &[PathSegment]| document.get_path(segments).cloned();
+    assert_eq!(path(&["users".into(), 0.into(), "roles".into(), 0.into()]), Some(JsonValue::String("admin".to_string())));
+    assert_eq!(path(&["users".into(), 1.into(), "a/b".into()]), Some(JsonValue::Number(1.0)));
+    assert_eq!(path(&["users".into(), 1.into(), PathSegment::Key("0".to_string())]), Some(JsonValue::String("key, not index".to_string())));
+    assert_eq!(path(&[]), Some(document.clone()));
+
+    // Missing members and elements, and segments of the wrong kind, find nothing.
+    assert_eq!(path(&["users".into(), 2.into()]), None);
+    assert_eq!(path(&["users".into(), 0.into(), "email".into()]), None);
+    assert_eq!(path(&["users".into(), 1.into(), 0.into()]), None);
+    assert_eq!(path(&["users".into(), PathSegment::Key("0".to_string())]), None);
+    assert_eq!(path(&["users".into(), 0.into(), "name".into(), 0.into()]), None);
+}
+
+#[test]
+fn is_empty_covers_containers_and_strings() {
+    for empty in [r#"{}"#, "[]", r#""""#] {
+        assert!(empty.parse::<JsonValue>().unwrap().is_empty(), "{}", empty);
+    }
+    for not_empty in [r#"{"a": {}}"#, "[[]]", r#"" ""#, "0", "false", "null"] {
+        assert!(!not_empty.parse::<JsonValue>().unwrap().is_empty(), "{}", not_empty);
+    }
+}
+
+#[test]
+fn merge_with_combines_arrays_as_asked() {
+    let base: JsonValue = r#"{"list": [1, {"a": 1, "b": [2]}], "nested": {"list": ["x"]}, "mixed": [1]}"#.parse().unwrap();
+    let patch: JsonValue = r#"{"list": [{"b": [2], "a": 1}, 3, 3], "nested": {"list": ["x", "y"]}, "mixed": {"one": 1}}"#.parse().unwrap();
+    let merged = |arrays| {
+        let mut value = base.clone();
+        value.merge_with(&patch, true, arrays);
+        value.to_string()
+    };
+
+    assert_eq!(merged(ArrayMerge::Replace), r#"{"list":[{"b":[2],"a":1},3,3],"nested":{"list":["x","y"]},"mixed":{"one":1}}"#);
+    assert_eq!(merged(ArrayMerge::Concat), r#"{"list":[1,{"a":1,"b":[2]},{"b":[2],"a":1},3,3],"nested":{"list":["x","x","y"]},"mixed":{"one":1}}"#);
+    assert_eq!(merged(ArrayMerge::Union), r#"{"list":[1,{"a":1,"b":[2]},3],"nested":{"list":["x","y"]},"mixed":{"one":1}}"#);
+}
+
+#[test]
+fn typed_accessors_tell_null_from_missing() {
+    let config: JsonValue = r#"{"name": "api", "proxy": null, "port": "80", "workers": 4}"#.parse().unwrap();
+
+    assert_eq!(config.get_or_null::<&str>("name"), Ok(Some("api")));
+    assert_eq!(config.get_or_null::<&str>("proxy"), Ok(None));
+    assert_eq!(config.get_or_null::<&str>("timeout"), Err(GetError::Missing("timeout".to_string())));
+
+    assert_eq!(config.get_required::<i64>("workers"), Ok(4));
+    assert_eq!(config.get_required::<&str>("proxy"), Err(GetError::Null("proxy".to_string())));
+    assert_eq!(config.get_required::<&str>("timeout"), Err(GetError::Missing("timeout".to_string())));
+
+    let wrong_type = config.get_required::<i64>("port").unwrap_err();
+    assert_eq!(wrong_type, GetError::WrongType { key: "port".to_string(), expected: "integer", found: "string" });
+    assert_eq!(wrong_type.to_string(), "key 'port' is string where integer was expected");
+    assert_eq!(config.get_or_null::<bool>("workers").unwrap_err().to_string(), "key 'workers' is number where boolean was expected");
+
+    for key in ["proxy", "timeout", "workers"] {
+        assert_eq!(config.get_str(key), None);
+    }
+}
+
+// 3.14 is data here, not an approximation of pi.
+#[allow(clippy::approx_constant)]
+#[test]
+fn coerce_converts_loosely_typed_values() {
+    let string = |text: &str| JsonValue::String(text.to_string());
+    assert_eq!(string("3.14").coerce_f64(), Some(3.14));
+    assert_eq!(string(" 42 ").coerce_f64(), Some(42.0));
+    assert_eq!(JsonValue::Number(2.5).coerce_f64(), Some(2.5));
+    for value in [string("NaN"), string("inf"), string("3 apples"), string(""), JsonValue::Bool(true), JsonValue::Null] {
+        assert_eq!(value.coerce_f64(), None, "{}", value);
+    }
+
+    assert_eq!(string("42").coerce_i64(), Some(42));
+    assert_eq!(JsonValue::Number(4.2e1).coerce_i64(), Some(42));
+    assert_eq!(string("4.5").coerce_i64(), None);
+
+    assert_eq!(string("TRUE").coerce_bool(), Some(true));
+    assert_eq!(string("false").coerce_bool(), Some(false));
+    assert_eq!(JsonValue::Number(1.0).coerce_bool(), None);
+
+    assert_eq!(JsonValue::Bool(true).coerce_string().as_deref(), Some("true"));
+    assert_eq!(JsonValue::Number(3.0).coerce_string().as_deref(), Some("3"));
+    assert_eq!(JsonValue::Number(0.1).coerce_string().as_deref(), Some("0.1"));
+    assert_eq!(string("x").coerce_string().as_deref(), Some("x"));
+    assert_eq!(JsonValue::Array(vec![]).coerce_string(), None);
+}